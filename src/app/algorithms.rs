@@ -0,0 +1,601 @@
+use base64::{
+  engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+  Engine,
+};
+use jsonwebtoken::Algorithm;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use super::{
+  jwks::is_jwks_url,
+  jwt_encoder::signing_jwk,
+  keygen::{thumbprint, KeyPair},
+  models::StatefulTable,
+  utils::{slurp_file, strip_leading_symbol, JWTError, JWTResult},
+  ActiveBlock, App, RouteId, TextAreaInput,
+};
+
+pub const HMAC_ALGORITHMS: [Algorithm; 3] = [Algorithm::HS256, Algorithm::HS384, Algorithm::HS512];
+
+pub const RSA_ALGORITHMS: [Algorithm; 6] = [
+  Algorithm::RS256,
+  Algorithm::RS384,
+  Algorithm::RS512,
+  Algorithm::PS256,
+  Algorithm::PS384,
+  Algorithm::PS512,
+];
+
+/// the signing algorithms in the order of the picker
+const ALGORITHMS: [(Algorithm, &str); 12] = [
+  (Algorithm::HS256, "HMAC using SHA-256"),
+  (Algorithm::HS384, "HMAC using SHA-384"),
+  (Algorithm::HS512, "HMAC using SHA-512"),
+  (Algorithm::RS256, "RSASSA-PKCS1-v1_5 using SHA-256"),
+  (Algorithm::RS384, "RSASSA-PKCS1-v1_5 using SHA-384"),
+  (Algorithm::RS512, "RSASSA-PKCS1-v1_5 using SHA-512"),
+  (Algorithm::PS256, "RSASSA-PSS using SHA-256"),
+  (Algorithm::PS384, "RSASSA-PSS using SHA-384"),
+  (Algorithm::PS512, "RSASSA-PSS using SHA-512"),
+  (Algorithm::ES256, "ECDSA using P-256 and SHA-256"),
+  (Algorithm::ES384, "ECDSA using P-384 and SHA-384"),
+  (Algorithm::EdDSA, "EdDSA using Ed25519"),
+];
+
+/// RFC 7515 registered header parameters that `jsonwebtoken` encodes, with the type of their value
+const REGISTERED_PARAMS: [(&str, &str); 9] = [
+  ("typ", "a string"),
+  ("cty", "a string"),
+  ("kid", "a string"),
+  ("jku", "a string"),
+  ("jwk", "an object"),
+  ("x5u", "a string"),
+  ("x5c", "an array"),
+  ("x5t", "a string"),
+  ("x5t#S256", "a string"),
+];
+
+/// JWE header parameters, the encryption is set in the Encryption block
+const JWE_PARAMS: [&str; 9] = ["enc", "zip", "epk", "apu", "apv", "iv", "tag", "p2s", "p2c"];
+
+/// the key of the encoder secret
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyInfo {
+  /// e.g. `RSA-2048 private key (PEM)`
+  pub description: String,
+  pub algorithms: Vec<Algorithm>,
+  /// the public key with its `kid`, `None` for HMAC secrets
+  pub public_jwk: Option<Value>,
+  pub kid: Option<String>,
+  /// DER certificates of the key, from the PEM file or the `x5c` of the JWK
+  pub certificates: Vec<Vec<u8>>,
+}
+
+impl KeyInfo {
+  fn hmac(description: &str) -> Self {
+    KeyInfo {
+      description: description.into(),
+      algorithms: HMAC_ALGORITHMS.to_vec(),
+      ..KeyInfo::default()
+    }
+  }
+
+  fn from_key_pair(pair: &KeyPair, format: &str, certificates: Vec<Vec<u8>>) -> Self {
+    let mut jwk = pair.required_members();
+    jwk["kid"] = pair.kid.clone().into();
+    KeyInfo {
+      description: format!("{} private key ({format})", pair.name()),
+      algorithms: pair.algorithms(),
+      public_jwk: Some(jwk),
+      kid: Some(pair.kid.clone()),
+      certificates,
+    }
+  }
+}
+
+/// the key of the encoder secret, read like `encoding_key_from_secret` does for the algorithms it fits.
+/// The `kid` selects the key of a JWKS
+pub fn key_info(secret: &str, kid: Option<&str>) -> JWTResult<KeyInfo> {
+  if secret.is_empty() {
+    return Err(JWTError::Internal("The secret is empty".into()));
+  } else if secret.starts_with("b64:") {
+    return Ok(KeyInfo::hmac("base64 encoded HMAC secret"));
+  } else if is_jwks_url(secret) {
+    return Err(JWTError::Internal(
+      "A JWKS URL only has public keys. Provide a private key to sign".into(),
+    ));
+  }
+
+  let file = secret.starts_with('@');
+  let content = match file {
+    true => slurp_file(strip_leading_symbol(secret))?,
+    false => secret.as_bytes().to_vec(),
+  };
+  let jwk = match file {
+    true => secret.ends_with(".json") || secret.ends_with(".jwk"),
+    false => secret.trim_start().starts_with('{') && serde_json::from_str::<Value>(secret).is_ok(),
+  };
+  if jwk {
+    return jwk_key_info(&signing_jwk(&content, kid)?);
+  }
+  if !file {
+    return Ok(KeyInfo::hmac("HMAC secret"));
+  }
+  if secret.ends_with(".pem") {
+    return pem_key_info(&content);
+  }
+  match KeyPair::from_private_der(&content) {
+    Some(pair) => Ok(KeyInfo::from_key_pair(&pair, "DER", vec![])),
+    // any other file is an HMAC secret
+    None => Ok(KeyInfo::hmac("HMAC secret file")),
+  }
+}
+
+fn pem_key_info(pem: &[u8]) -> JWTResult<KeyInfo> {
+  let blocks = pem_blocks(&String::from_utf8_lossy(pem));
+  let certificates = blocks
+    .iter()
+    .filter(|(label, _)| label == "CERTIFICATE")
+    .map(|(_, der)| der.clone())
+    .collect();
+  blocks
+    .iter()
+    .filter(|(label, _)| label.ends_with("PRIVATE KEY"))
+    .find_map(|(_, der)| KeyPair::from_private_der(der))
+    .map(|pair| KeyInfo::from_key_pair(&pair, "PEM", certificates))
+    .ok_or_else(|| JWTError::Internal("The PEM file has no private key to sign with".into()))
+}
+
+/// label and DER content of the blocks of a PEM file
+fn pem_blocks(pem: &str) -> Vec<(String, Vec<u8>)> {
+  let mut blocks = vec![];
+  let mut rest = pem;
+  while let Some(start) = rest.find("-----BEGIN ") {
+    let after = &rest[start + 11..];
+    let Some(label_end) = after.find("-----") else {
+      break;
+    };
+    let label = &after[..label_end];
+    let body = &after[label_end + 5..];
+    let end_marker = format!("-----END {label}-----");
+    let Some(end) = body.find(&end_marker) else {
+      break;
+    };
+    let base64: String = body[..end].split_whitespace().collect();
+    if let Ok(der) = STANDARD.decode(base64) {
+      blocks.push((label.to_string(), der));
+    }
+    rest = &body[end + end_marker.len()..];
+  }
+  blocks
+}
+
+fn jwk_key_info(jwk: &Value) -> JWTResult<KeyInfo> {
+  let param = |name| jwk.get(name).and_then(Value::as_str).unwrap_or_default();
+  let (kty, crv) = (param("kty"), param("crv"));
+  let (mut algorithms, members): (Vec<Algorithm>, &[&str]) = match (kty, crv) {
+    ("oct", _) => (HMAC_ALGORITHMS.to_vec(), &[]),
+    ("RSA", _) => (RSA_ALGORITHMS.to_vec(), &["e", "kty", "n"]),
+    ("EC", "P-256") => (vec![Algorithm::ES256], &["crv", "kty", "x", "y"]),
+    ("EC", "P-384") => (vec![Algorithm::ES384], &["crv", "kty", "x", "y"]),
+    ("OKP", "Ed25519") => (vec![Algorithm::EdDSA], &["crv", "kty", "x"]),
+    _ => {
+      return Err(JWTError::Internal(format!(
+        "The {kty} {crv} jwk can't sign tokens"
+      )))
+    }
+  };
+  // a JWK with an `alg` is only used with it
+  if let Ok(alg) = param("alg").parse::<Algorithm>() {
+    algorithms.retain(|a| *a == alg);
+  }
+  if kty == "oct" {
+    return Ok(KeyInfo {
+      algorithms,
+      ..KeyInfo::hmac("HMAC secret jwk")
+    });
+  }
+  if jwk.get("d").is_none() {
+    return Err(JWTError::Internal(
+      "The jwk has no private key. Provide a private jwk".into(),
+    ));
+  }
+
+  let mut public: Value = members
+    .iter()
+    .map(|m| (m.to_string(), jwk.get(*m).cloned().unwrap_or_default()))
+    .collect::<Map<_, _>>()
+    .into();
+  let kid = match jwk.get("kid").and_then(Value::as_str) {
+    Some(kid) => kid.to_string(),
+    None => thumbprint(&public),
+  };
+  public["kid"] = kid.clone().into();
+  let certificates = jwk
+    .get("x5c")
+    .and_then(Value::as_array)
+    .into_iter()
+    .flatten()
+    .filter_map(|c| STANDARD.decode(c.as_str()?).ok())
+    .collect();
+  Ok(KeyInfo {
+    description: format!("{} private jwk", if crv.is_empty() { kty } else { crv }),
+    algorithms,
+    public_jwk: Some(public),
+    kid: Some(kid),
+    certificates,
+  })
+}
+
+/// the algorithms fitting the encoder secret, see `open_picker`
+#[derive(Default)]
+pub struct AlgorithmPicker {
+  pub items: StatefulTable<Algorithm>,
+  /// the key of the secret, or why every algorithm is listed
+  pub key: String,
+}
+
+impl AlgorithmPicker {
+  pub fn selected(&self) -> Option<Algorithm> {
+    self
+      .items
+      .state
+      .selected()
+      .and_then(|i| self.items.items.get(i).copied())
+  }
+}
+
+pub fn describe(alg: &Algorithm) -> &'static str {
+  ALGORITHMS
+    .iter()
+    .find(|(a, _)| a == alg)
+    .map_or("", |(_, description)| description)
+}
+
+/// list the algorithms that fit the encoder secret, with the one of the header selected
+pub fn open_picker(app: &mut App) {
+  let header = app.data.encoder.header.input.lines().join("\n");
+  let header: Map<String, Value> = serde_json::from_str(&header).unwrap_or_default();
+  let kid = header.get("kid").and_then(Value::as_str);
+  let (algorithms, key) = match key_info(app.data.encoder.secret.input.value(), kid) {
+    Ok(key) => (
+      key.algorithms,
+      format!("Algorithms that fit the {}", key.description),
+    ),
+    Err(e) => (
+      ALGORITHMS.iter().map(|(alg, _)| *alg).collect(),
+      format!("{e}. Showing all algorithms"),
+    ),
+  };
+  let current = header
+    .get("alg")
+    .and_then(Value::as_str)
+    .and_then(|alg| alg.parse::<Algorithm>().ok())
+    .and_then(|alg| algorithms.iter().position(|a| *a == alg));
+
+  let mut items = StatefulTable::with_items(algorithms);
+  if current.is_some() {
+    items.state.select(current);
+  }
+  app.data.algorithms = AlgorithmPicker { items, key };
+  app.push_navigation_stack(RouteId::Algorithms, ActiveBlock::AlgorithmList);
+}
+
+/// set the selected algorithm in the encoder header and go back to the encoder
+pub fn apply_selected(app: &mut App) {
+  let Some(alg) = app.data.algorithms.selected() else {
+    return;
+  };
+  edit_header(app, |header| {
+    header.insert("alg".into(), format!("{alg:?}").into());
+    Ok(())
+  });
+  while app.get_current_route().id == RouteId::Algorithms && app.pop_navigation_stack().is_some() {}
+}
+
+/// set the `kid` of the key in the encoder header, with its certificate chain (`x5c`, `x5t#S256`)
+/// or else its public key (`jwk`)
+pub fn fill_key_headers(app: &mut App) {
+  let secret = app.data.encoder.secret.input.value().to_string();
+  edit_header(app, |header| {
+    let kid = header.get("kid").and_then(Value::as_str);
+    let key = key_info(&secret, kid)?;
+    let (Some(jwk), Some(kid)) = (key.public_jwk, key.kid) else {
+      return Err(JWTError::Internal(
+        "An HMAC secret has no public key to put in the header".into(),
+      ));
+    };
+    header.insert("kid".into(), kid.into());
+    match key.certificates.first() {
+      Some(certificate) => {
+        let thumbprint = URL_SAFE_NO_PAD.encode(Sha256::digest(certificate));
+        header.insert("x5t#S256".into(), thumbprint.into());
+        let chain = key.certificates.iter().map(|c| STANDARD.encode(c));
+        header.insert("x5c".into(), chain.collect::<Vec<_>>().into());
+      }
+      None => {
+        header.insert("jwk".into(), jwk);
+      }
+    }
+    Ok(())
+  });
+}
+
+/// change the encoder header as a JSON object
+fn edit_header(app: &mut App, edit: impl FnOnce(&mut Map<String, Value>) -> JWTResult<()>) {
+  let header = app.data.encoder.header.input.lines().join("\n");
+  let edited = serde_json::from_str::<Map<String, Value>>(&header)
+    .map_err(|e| JWTError::Internal(format!("Error parsing header: {e}")))
+    .and_then(|mut header| edit(&mut header).map(|_| header));
+  match edited {
+    Ok(header) => {
+      let header = serde_json::to_string_pretty(&header).unwrap_or_default();
+      app.data.encoder.header = TextAreaInput::new(header.lines().map(String::from).collect());
+      app.data.error = String::new();
+    }
+    Err(e) => app.handle_error(e),
+  }
+}
+
+/// problems of the encoder header with the RFC 7515 registered parameters and the secret.
+/// A header that isn't a JSON object is reported when encoding
+pub fn check_header(header: &str, secret: &str) -> Vec<String> {
+  let Ok(header) = serde_json::from_str::<Map<String, Value>>(header) else {
+    return vec![];
+  };
+  let mut problems = vec![];
+  let alg = header.get("alg");
+  let unsigned = alg
+    .and_then(Value::as_str)
+    .is_some_and(|alg| alg.eq_ignore_ascii_case("none"));
+  match alg {
+    None => problems.push("`alg` is required".into()),
+    Some(Value::String(_)) if unsigned => {}
+    Some(Value::String(name)) => match name.parse::<Algorithm>() {
+      Ok(parsed) => {
+        let kid = header.get("kid").and_then(Value::as_str);
+        if let Ok(key) = key_info(secret, kid) {
+          if !key.algorithms.contains(&parsed) {
+            problems.push(format!(
+              "{name} doesn't fit the {}, use {}",
+              key.description,
+              names(&key.algorithms)
+            ));
+          }
+        }
+      }
+      Err(_) => {
+        let all: Vec<Algorithm> = ALGORITHMS.iter().map(|(alg, _)| *alg).collect();
+        problems.push(format!("Unknown `alg` {name:?}, use {}", names(&all)));
+      }
+    },
+    Some(value) => problems.push(format!("`alg` must be a string, not {}", json_type(value))),
+  }
+
+  for (name, value) in header.iter().filter(|(name, _)| *name != "alg") {
+    let name = name.as_str();
+    match REGISTERED_PARAMS.iter().find(|(param, _)| *param == name) {
+      Some((_, expected)) if json_type(value) != *expected => problems.push(format!(
+        "`{name}` must be {expected}, not {}",
+        json_type(value)
+      )),
+      Some(_) => problems.extend(check_value(name, value)),
+      // the header of an unsigned token is encoded as is
+      None if unsigned => {}
+      None if JWE_PARAMS.contains(&name) => problems.push(format!(
+        "`{name}` is a JWE parameter, set the encryption in the Encryption block"
+      )),
+      None => problems.push(format!(
+        "`{name}` is dropped, only registered parameters are encoded"
+      )),
+    }
+  }
+  problems
+}
+
+/// problems of the value of a registered parameter
+fn check_value(name: &str, value: &Value) -> Option<String> {
+  match (name, value) {
+    ("jku" | "x5u", Value::String(url)) if !url.starts_with("https://") => {
+      Some(format!("`{name}` must be an https URL"))
+    }
+    ("x5t" | "x5t#S256", Value::String(thumbprint)) => {
+      let (hash, len) = match name {
+        "x5t" => ("SHA-1", 20),
+        _ => ("SHA-256", 32),
+      };
+      let decoded = URL_SAFE_NO_PAD.decode(thumbprint).map(|t| t.len());
+      (decoded != Ok(len)).then(|| {
+        format!("`{name}` must be the base64url encoded {hash} thumbprint of the certificate")
+      })
+    }
+    ("x5c", Value::Array(chain))
+      if !chain
+        .iter()
+        .all(|c| c.as_str().is_some_and(|c| STANDARD.decode(c).is_ok())) =>
+    {
+      Some("`x5c` must be base64 encoded DER certificates".into())
+    }
+    ("jwk", Value::Object(jwk)) if jwk.contains_key("d") || jwk.contains_key("k") => {
+      Some("`jwk` must be a public key, it contains the private key".into())
+    }
+    _ => None,
+  }
+}
+
+fn json_type(value: &Value) -> &'static str {
+  match value {
+    Value::Null => "null",
+    Value::Bool(_) => "a boolean",
+    Value::Number(_) => "a number",
+    Value::String(_) => "a string",
+    Value::Array(_) => "an array",
+    Value::Object(_) => "an object",
+  }
+}
+
+fn names(algorithms: &[Algorithm]) -> String {
+  let names: Vec<String> = algorithms.iter().map(|alg| format!("{alg:?}")).collect();
+  names.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+
+  use serde_json::json;
+
+  use super::*;
+  use crate::app::{
+    jwt_encoder::encode_jwt_token,
+    keygen::{KeyFormat, KeyType},
+    TextInput,
+  };
+
+  fn out_path(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("jwtui-algorithms-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name).display().to_string()
+  }
+
+  fn set_header(app: &mut App, header: &str) {
+    app.data.encoder.header = TextAreaInput::new(header.lines().map(String::from).collect());
+  }
+
+  fn header(app: &App) -> Value {
+    serde_json::from_str(&app.data.encoder.header.input.lines().join("\n")).unwrap()
+  }
+
+  #[test]
+  fn test_key_info() {
+    let hmac = key_info("secret", None).unwrap();
+    assert_eq!(hmac.algorithms, HMAC_ALGORITHMS.to_vec());
+    assert_eq!(hmac.public_jwk, None);
+    assert_eq!(
+      key_info("b64:c2VjcmV0", None).unwrap().description,
+      "base64 encoded HMAC secret"
+    );
+    assert!(key_info("https://example.com", None).is_err());
+
+    let pair = KeyPair::generate(KeyType::P256, None).unwrap();
+    let out = out_path("p256");
+    pair
+      .write(&out, &[KeyFormat::Pem, KeyFormat::Der], true)
+      .unwrap();
+    let pem = key_info(&format!("@{out}.pem"), None).unwrap();
+    assert_eq!(pem.description, "P-256 private key (PEM)");
+    assert_eq!(pem.algorithms, vec![Algorithm::ES256]);
+    assert_eq!(pem.kid, Some(pair.kid.clone()));
+    let der = key_info(&format!("@{out}.der"), None).unwrap();
+    assert_eq!(der.algorithms, vec![Algorithm::ES256]);
+    // the public key can't sign
+    assert!(key_info(&format!("@{out}.pub.pem"), None).is_err());
+
+    let jwk = serde_json::to_string(&pair.private_jwk()).unwrap();
+    let jwk = key_info(&jwk, None).unwrap();
+    assert_eq!(jwk.description, "P-256 private jwk");
+    assert_eq!(jwk.public_jwk, pem.public_jwk);
+  }
+
+  #[test]
+  fn test_check_header() {
+    let check = |header: Value| check_header(&header.to_string(), "secret");
+    assert!(check(json!({ "alg": "HS256", "typ": "JWT", "kid": "1" })).is_empty());
+    assert_eq!(
+      check(json!({ "alg": "RS256" })),
+      vec!["RS256 doesn't fit the HMAC secret, use HS256, HS384, HS512"]
+    );
+    assert_eq!(
+      check(json!({ "typ": 1, "foo": "bar", "enc": "A256GCM" })),
+      vec![
+        "`alg` is required",
+        "`enc` is a JWE parameter, set the encryption in the Encryption block",
+        "`foo` is dropped, only registered parameters are encoded",
+        "`typ` must be a string, not a number",
+      ]
+    );
+    assert!(check(json!({ "alg": "HS257" }))[0].starts_with("Unknown `alg` \"HS257\", use HS256"));
+    assert_eq!(
+      check(json!({ "alg": "HS256", "jku": "http://example.com", "x5t#S256": "abc" })),
+      vec![
+        "`jku` must be an https URL",
+        "`x5t#S256` must be the base64url encoded SHA-256 thumbprint of the certificate",
+      ]
+    );
+    assert_eq!(
+      check(json!({ "alg": "HS256", "jwk": { "kty": "oct", "k": "c2VjcmV0" } })),
+      vec!["`jwk` must be a public key, it contains the private key"]
+    );
+    // the header of an unsigned token is encoded as is
+    assert!(check(json!({ "alg": "none", "foo": "bar" })).is_empty());
+  }
+
+  #[test]
+  fn test_pick_algorithm() {
+    let mut app = App::new(None, "secret".into());
+    set_header(&mut app, r#"{ "alg": "HS384", "typ": "JWT" }"#);
+    app.route_encoder();
+
+    open_picker(&mut app);
+    assert_eq!(app.get_current_route().id, RouteId::Algorithms);
+    assert_eq!(app.data.algorithms.items.items, HMAC_ALGORITHMS.to_vec());
+    assert_eq!(app.data.algorithms.selected(), Some(Algorithm::HS384));
+
+    app.data.algorithms.items.state.select(Some(2));
+    apply_selected(&mut app);
+    assert_eq!(app.get_current_route().id, RouteId::Encoder);
+    assert_eq!(header(&app), json!({ "alg": "HS512", "typ": "JWT" }));
+
+    // every algorithm when the key can't be read
+    app.data.encoder.secret = TextInput::new("@missing.pem".into());
+    open_picker(&mut app);
+    assert_eq!(app.data.algorithms.items.items.len(), ALGORITHMS.len());
+  }
+
+  #[test]
+  fn test_fill_key_headers() {
+    let mut app = App::new(None, "secret".into());
+    fill_key_headers(&mut app);
+    assert_eq!(
+      app.data.error,
+      "An HMAC secret has no public key to put in the header"
+    );
+
+    let pair = KeyPair::generate(KeyType::Ed25519, None).unwrap();
+    let out = out_path("ed25519");
+    pair.write(&out, &[KeyFormat::Pem], true).unwrap();
+    app.data.encoder.secret = TextInput::new(format!("@{out}.pem"));
+    set_header(&mut app, r#"{ "alg": "EdDSA" }"#);
+    fill_key_headers(&mut app);
+    assert_eq!(app.data.error, "");
+    let mut jwk = pair.public_jwk();
+    jwk
+      .as_object_mut()
+      .unwrap()
+      .retain(|k, _| !matches!(k.as_str(), "alg" | "use"));
+    assert_eq!(
+      header(&app),
+      json!({ "alg": "EdDSA", "kid": pair.kid, "jwk": jwk })
+    );
+
+    // the certificate chain of a JWK instead of the public key
+    let mut private = pair.private_jwk();
+    private["x5c"] = json!([STANDARD.encode(b"certificate")]);
+    app.data.encoder.secret = TextInput::new(private.to_string());
+    set_header(&mut app, r#"{ "alg": "EdDSA" }"#);
+    fill_key_headers(&mut app);
+    let header = header(&app);
+    assert_eq!(header["x5c"], private["x5c"]);
+    assert_eq!(
+      header["x5t#S256"],
+      URL_SAFE_NO_PAD.encode(Sha256::digest(b"certificate"))
+    );
+    assert!(header.get("jwk").is_none());
+
+    // the encoder signs with the filled header
+    app.data.encoder.payload = TextAreaInput::new(vec![r#"{ "sub": "alice" }"#.into()]);
+    encode_jwt_token(&mut app);
+    assert_eq!(app.data.error, "");
+    assert!(app.data.encoder.header_problems.is_empty());
+  }
+}
@@ -0,0 +1,229 @@
+use chrono::{DateTime, Local, TimeZone, Utc};
+use serde_json::Value;
+
+use super::json_tree::{RowKind, TreeRow};
+
+/// registered JWT claims and common OIDC/OAuth claims
+const CLAIMS: [(&str, &str); 26] = [
+  ("iss", "Issuer"),
+  ("sub", "Subject, the user or client the token is about"),
+  ("aud", "Audience, the recipients the token is intended for"),
+  ("exp", "Expiration time"),
+  ("nbf", "Not before"),
+  ("iat", "Issued at"),
+  ("jti", "JWT ID, unique identifier of the token"),
+  (
+    "azp",
+    "Authorized party, the client the token was issued to",
+  ),
+  ("acr", "Authentication context class reference"),
+  ("amr", "Authentication methods references"),
+  ("cnf", "Confirmation, the key the token is bound to"),
+  ("scope", "Scopes granted to the client"),
+  ("scp", "Scopes granted to the client"),
+  ("auth_time", "Time the user authenticated"),
+  ("sid", "Session ID"),
+  ("nonce", "Value binding the ID token to the client session"),
+  ("at_hash", "Access token hash"),
+  ("c_hash", "Authorization code hash"),
+  ("client_id", "Client the token was issued to"),
+  ("typ", "Token type"),
+  ("name", "Full name of the user"),
+  ("email", "Email address of the user"),
+  ("email_verified", "Whether the email address was verified"),
+  ("preferred_username", "Username the user prefers"),
+  ("updated_at", "Time the user's information was last updated"),
+  ("roles", "Roles of the user"),
+];
+
+/// registered JWS and JWE header parameters
+const HEADER_PARAMS: [(&str, &str); 17] = [
+  ("alg", "Algorithm of the signature or key encryption"),
+  ("typ", "Media type of the token"),
+  ("cty", "Content type, `JWT` for a nested token"),
+  ("kid", "Key ID, selects the key to verify or decrypt with"),
+  ("x5t", "SHA-1 thumbprint of the X.509 certificate"),
+  ("x5t#S256", "SHA-256 thumbprint of the X.509 certificate"),
+  ("x5u", "URL of the X.509 certificate chain"),
+  ("x5c", "X.509 certificate chain"),
+  ("jku", "URL of the JWKS with the key"),
+  ("jwk", "Public key embedded in the token"),
+  ("crit", "Extensions the recipient must understand"),
+  ("enc", "Content encryption algorithm"),
+  ("zip", "Compression of the plaintext"),
+  ("epk", "Ephemeral public key of ECDH-ES"),
+  ("apu", "Agreement PartyUInfo of ECDH-ES"),
+  ("apv", "Agreement PartyVInfo of ECDH-ES"),
+  ("b64", "Whether the payload is base64url encoded"),
+];
+
+/// claims that are seconds since the epoch
+const TIMESTAMP_CLAIMS: [&str; 5] = ["exp", "nbf", "iat", "auth_time", "updated_at"];
+
+/// description of a top-level claim or header parameter, with the time of a timestamp claim
+pub fn annotate(row: &TreeRow, header: bool, now: i64) -> Option<String> {
+  let key = row.key.as_deref()?;
+  let (glossary, depth): (&[(&str, &str)], _) = match header {
+    true => (&HEADER_PARAMS, header_depth(&row.pointer)),
+    false => (&CLAIMS, 1),
+  };
+  if row.depth != depth || matches!(row.kind, RowKind::Close(_)) {
+    return None;
+  }
+  let description = glossary.iter().find(|(k, _)| *k == key).map(|(_, d)| *d);
+  let time = match &row.kind {
+    RowKind::Leaf(value) if !header && TIMESTAMP_CLAIMS.contains(&key) => {
+      timestamp(value).map(|t| describe_time(key, t, now))
+    }
+    _ => None,
+  };
+  match (description, time) {
+    (Some(description), Some(time)) => Some(format!("{description}: {time}")),
+    (description, time) => time.or(description.map(String::from)),
+  }
+}
+
+/// the parameters of a JWE header with a nested JWS header are under `jwe` and `jws`
+fn header_depth(pointer: &str) -> usize {
+  match pointer.starts_with("/jwe/") || pointer.starts_with("/jws/") {
+    true => 2,
+    false => 1,
+  }
+}
+
+/// also a timestamp converted to a UTC date, see `Payload::convert_timestamps`
+fn timestamp(value: &Value) -> Option<i64> {
+  match value {
+    Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+    Value::String(s) => DateTime::parse_from_rfc3339(s).ok().map(|d| d.timestamp()),
+    _ => None,
+  }
+}
+
+/// e.g. `expires in 12m, 2024-01-01 13:00:00 +01:00, 2024-01-01 12:00:00 UTC`
+fn describe_time(claim: &str, timestamp: i64, now: i64) -> String {
+  let ago = format_relative(now - timestamp);
+  let within = format_relative(timestamp - now);
+  let relative = match (claim, timestamp > now) {
+    ("exp", true) => format!("expires in {within}"),
+    ("exp", false) => format!("expired {ago} ago"),
+    ("nbf", true) => format!("valid in {within}"),
+    ("nbf", false) => format!("valid since {ago}"),
+    ("iat", true) => format!("issued in {within}, in the future"),
+    ("iat", false) => format!("issued {ago} ago"),
+    ("auth_time", _) => format!("authenticated {ago} ago"),
+    (_, _) => format!("updated {ago} ago"),
+  };
+  let Some(utc) = Utc.timestamp_opt(timestamp, 0).single() else {
+    return relative;
+  };
+  format!(
+    "{relative}, {}, {}",
+    utc.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S %:z"),
+    utc.format("%Y-%m-%d %H:%M:%S UTC")
+  )
+}
+
+/// the largest unit of a duration, e.g. `3d` or `12m`
+fn format_relative(seconds: i64) -> String {
+  let seconds = seconds.max(0);
+  [("y", 365 * 86400), ("d", 86400), ("h", 3600), ("m", 60)]
+    .iter()
+    .find(|(_, size)| seconds >= *size)
+    .map_or(format!("{seconds}s"), |(unit, size)| {
+      format!("{}{unit}", seconds / size)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  const NOW: i64 = 1_700_000_000;
+
+  fn row(pointer: &str, depth: usize, kind: RowKind) -> TreeRow {
+    TreeRow {
+      pointer: pointer.into(),
+      depth,
+      key: pointer.rsplit('/').next().map(String::from),
+      kind,
+      comma: false,
+    }
+  }
+
+  fn leaf(pointer: &str, value: Value) -> TreeRow {
+    row(pointer, 1, RowKind::Leaf(value))
+  }
+
+  #[test]
+  fn test_format_relative() {
+    assert_eq!(format_relative(0), "0s");
+    assert_eq!(format_relative(59), "59s");
+    assert_eq!(format_relative(12 * 60 + 5), "12m");
+    assert_eq!(format_relative(3 * 86400 + 7200), "3d");
+    assert_eq!(format_relative(2 * 365 * 86400), "2y");
+  }
+
+  #[test]
+  fn test_annotate_timestamps() {
+    let exp = annotate(&leaf("/exp", json!(NOW + 720)), false, NOW).unwrap();
+    assert!(exp.starts_with("Expiration time: expires in 12m, "));
+    assert!(exp.ends_with(", 2023-11-14 22:25:20 UTC"));
+
+    let iat = annotate(&leaf("/iat", json!(NOW - 3 * 86400)), false, NOW).unwrap();
+    assert!(iat.starts_with("Issued at: issued 3d ago, "));
+
+    // a date shown in UTC mode
+    let nbf = annotate(
+      &leaf("/nbf", json!("2023-11-14T22:13:20+00:00")),
+      false,
+      NOW - 300,
+    )
+    .unwrap();
+    assert!(nbf.starts_with("Not before: valid in 5m, "));
+  }
+
+  #[test]
+  fn test_annotate_glossary() {
+    assert_eq!(
+      annotate(&leaf("/azp", json!("client")), false, NOW),
+      Some("Authorized party, the client the token was issued to".into())
+    );
+    let amr = row(
+      "/amr",
+      1,
+      RowKind::Open {
+        bracket: '[',
+        len: 1,
+        collapsed: false,
+      },
+    );
+    assert_eq!(
+      annotate(&amr, false, NOW),
+      Some("Authentication methods references".into())
+    );
+    assert_eq!(
+      annotate(&row("/amr", 1, RowKind::Close(']')), false, NOW),
+      None
+    );
+    // only top-level claims
+    assert_eq!(
+      annotate(&row("/cnf/sub", 2, RowKind::Leaf(json!("a"))), false, NOW),
+      None
+    );
+    assert_eq!(annotate(&leaf("/custom", json!(1)), false, NOW), None);
+
+    assert_eq!(
+      annotate(&leaf("/kid", json!("key-1")), true, NOW),
+      Some("Key ID, selects the key to verify or decrypt with".into())
+    );
+    assert_eq!(
+      annotate(&row("/jws/x5t", 2, RowKind::Leaf(json!("a"))), true, NOW),
+      Some("SHA-1 thumbprint of the X.509 certificate".into())
+    );
+    // a claim isn't a header parameter
+    assert_eq!(annotate(&leaf("/azp", json!("client")), true, NOW), None);
+  }
+}
@@ -0,0 +1,415 @@
+use std::{
+  collections::BTreeMap,
+  fmt, fs,
+  io::{self, BufRead, BufReader},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{
+  jwe::JweHeader,
+  jwt_decoder::{decode_jwt_token, DecodedToken, Payload},
+  utils::{JWTError, JWTResult},
+  validation::{CheckStatus, ValidationReport},
+  App, TextInput,
+};
+
+/// a token read in batch mode and where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchToken {
+  /// `file:line`
+  pub source: String,
+  pub token: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStatus {
+  Valid,
+  Expired,
+  BadSignature,
+  /// a claim check failed, e.g. `iss` or `aud`
+  InvalidClaims,
+  /// no secret to verify the signature with
+  Unverified,
+  Malformed,
+}
+
+impl TokenStatus {
+  const ALL: [TokenStatus; 6] = [
+    TokenStatus::Valid,
+    TokenStatus::Expired,
+    TokenStatus::BadSignature,
+    TokenStatus::InvalidClaims,
+    TokenStatus::Unverified,
+    TokenStatus::Malformed,
+  ];
+}
+
+impl fmt::Display for TokenStatus {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let name = match self {
+      TokenStatus::Valid => "valid",
+      TokenStatus::Expired => "expired",
+      TokenStatus::BadSignature => "bad signature",
+      TokenStatus::InvalidClaims => "invalid claims",
+      TokenStatus::Unverified => "unverified",
+      TokenStatus::Malformed => "malformed",
+    };
+    write!(f, "{name}")
+  }
+}
+
+/// outcome of a token, printed as one NDJSON line
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResult {
+  pub source: String,
+  pub status: TokenStatus,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+  /// the JWS header, also of an unsigned token. `None` when a JWE contains the claims directly
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub header: Option<Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub jwe_header: Option<JweHeader>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub payload: Option<Payload>,
+  pub validation: ValidationReport,
+}
+
+/// read the tokens of the given files, glob patterns or `-` for STDIN
+pub fn read_tokens(inputs: &[String]) -> JWTResult<Vec<BatchToken>> {
+  let mut tokens = vec![];
+  for input in inputs {
+    if input == "-" {
+      tokens.extend(tokens_from_reader("<stdin>", io::stdin().lock())?);
+      continue;
+    }
+    let paths: Vec<_> = glob::glob(input)
+      .map_err(|e| JWTError::Internal(format!("Invalid batch input {input:?}: {e}")))?
+      .filter_map(Result::ok)
+      .filter(|path| path.is_file())
+      .collect();
+    if paths.is_empty() {
+      return Err(JWTError::Internal(format!("No files match {input:?}")));
+    }
+    for path in paths {
+      let file = fs::File::open(&path)
+        .map_err(|e| JWTError::Internal(format!("Unable to read {}: {e}", path.display())))?;
+      tokens.extend(tokens_from_reader(
+        &path.display().to_string(),
+        BufReader::new(file),
+      )?);
+    }
+  }
+  Ok(tokens)
+}
+
+fn tokens_from_reader(name: &str, reader: impl BufRead) -> JWTResult<Vec<BatchToken>> {
+  let mut tokens = vec![];
+  for (i, line) in reader.lines().enumerate() {
+    if let Some(token) = extract_token(&line?) {
+      tokens.push(BatchToken {
+        source: format!("{name}:{}", i + 1),
+        token: token.to_string(),
+      });
+    }
+  }
+  Ok(tokens)
+}
+
+/// the token of an `Authorization: Bearer` line, or a line that only holds a token.
+/// other lines, e.g. the rest of a log, and words without the dots of a JWS or JWE are skipped
+pub fn extract_token(line: &str) -> Option<&str> {
+  let line = line.trim();
+  // ASCII lowercasing keeps the byte offsets of the line, unlike `to_lowercase`
+  let bearer = line.to_ascii_lowercase().find("bearer ");
+  match bearer {
+    Some(start) => {
+      let rest = line[start + "bearer ".len()..].trim_start();
+      let end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '=')))
+        .unwrap_or(rest.len());
+      Some(&rest[..end]).filter(|token| is_candidate(token))
+    }
+    None if is_candidate(line) && !line.contains(char::is_whitespace) => Some(line),
+    None => None,
+  }
+}
+
+/// a JWS has two dots and a JWE four, the rest isn't a token
+fn is_candidate(token: &str) -> bool {
+  token.matches('.').count() >= 2
+}
+
+/// decode and verify every token with the secret and validation settings of the decoder
+pub fn decode_batch(app: &mut App, tokens: Vec<BatchToken>, no_verify: bool) -> Vec<BatchResult> {
+  tokens
+    .into_iter()
+    .map(|token| {
+      app.data.decoder.encoded = TextInput::new(token.token);
+      app.data.error = String::new();
+      decode_jwt_token(app, no_verify);
+
+      let decoder = &app.data.decoder;
+      let decoded = decoder.get_decoded();
+      let validation = decoder.get_report();
+      let has_secret = !decoder.secret.input.value().is_empty() || decoder.trust_entry.is_some();
+      let failed = |check: &str, statuses: &[CheckStatus]| {
+        validation
+          .checks
+          .iter()
+          .any(|c| c.check == check && statuses.contains(&c.status))
+      };
+
+      let status = match &decoded {
+        None => TokenStatus::Malformed,
+        Some(_) if !no_verify && !has_secret => TokenStatus::Unverified,
+        Some(_) if failed("signature", &[CheckStatus::Fail]) => TokenStatus::BadSignature,
+        // expired tokens are only a warning in the decoder, see `Decoder::ignore_exp`
+        Some(decoded)
+          if decoded.claims.timestamp("exp").is_some()
+            && failed("exp", &[CheckStatus::Fail, CheckStatus::Warn]) =>
+        {
+          TokenStatus::Expired
+        }
+        Some(_) if !validation.valid => TokenStatus::InvalidClaims,
+        Some(_) => TokenStatus::Valid,
+      };
+      let error = match status {
+        TokenStatus::Valid => None,
+        TokenStatus::Expired => Some("The token has expired".into()),
+        // the schema violations are the only failed claims checks without an error
+        TokenStatus::InvalidClaims
+          if app.data.error.is_empty() && !decoder.schema_violations.is_empty() =>
+        {
+          let violations: Vec<String> = decoder
+            .schema_violations
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+          Some(format!("Schema violations: {}", violations.join(", ")))
+        }
+        _ => Some(app.data.error.clone()).filter(|e| !e.is_empty()),
+      };
+
+      BatchResult {
+        source: token.source,
+        status,
+        error,
+        header: decoded.as_ref().and_then(DecodedToken::header_value),
+        jwe_header: decoded.as_ref().and_then(|d| d.jwe_header.clone()),
+        payload: decoded.map(|d| d.claims),
+        // a token that can't be decoded has no checks, but isn't valid either
+        validation: match status {
+          TokenStatus::Malformed => ValidationReport::default(),
+          _ => validation,
+        },
+      }
+    })
+    .collect()
+}
+
+/// one JSON object per token
+pub fn to_ndjson(results: &[BatchResult]) -> String {
+  results
+    .iter()
+    .map(|result| serde_json::to_string(result).unwrap_or_default())
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// the tokens that aren't valid, followed by the count of every status
+pub fn to_summary(results: &[BatchResult]) -> String {
+  let mut lines = vec![];
+  for result in results.iter().filter(|r| r.status != TokenStatus::Valid) {
+    let error = result.error.as_deref().unwrap_or_default();
+    lines.push(format!(
+      "{:<30} {:<15} {error}",
+      result.source,
+      result.status.to_string()
+    ));
+  }
+  if !lines.is_empty() {
+    lines.push(String::new());
+  }
+
+  let mut counts: BTreeMap<TokenStatus, usize> = BTreeMap::new();
+  for result in results {
+    *counts.entry(result.status).or_default() += 1;
+  }
+  lines.push(format!("{:<15} {:>7}", "Status", "Count"));
+  for status in TokenStatus::ALL {
+    let count = counts.get(&status).copied().unwrap_or_default();
+    lines.push(format!("{:<15} {count:>7}", status.to_string()));
+  }
+  lines.push(format!("{:<15} {:>7}", "total", results.len()));
+  lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use jsonwebtoken::{encode, EncodingKey, Header};
+  use serde_json::json;
+
+  use super::{super::schema::Schemas, *};
+
+  fn token(claims: serde_json::Value, secret: &str) -> String {
+    encode(
+      &Header::default(),
+      &claims,
+      &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_extract_token() {
+    assert_eq!(extract_token("  eyJ.eyJ.sig  "), Some("eyJ.eyJ.sig"));
+    assert_eq!(
+      extract_token(r#"10:01 GET /api "Authorization: Bearer eyJ.eyJ.sig" 200"#),
+      Some("eyJ.eyJ.sig")
+    );
+    assert_eq!(
+      extract_token("authorization: bearer a-b_c.d.e"),
+      Some("a-b_c.d.e")
+    );
+    assert_eq!(extract_token("10:02 GET /health 200"), None);
+    assert_eq!(extract_token(""), None);
+    assert_eq!(extract_token("Authorization: Bearer "), None);
+    // lowercasing `İ` changes its length
+    assert_eq!(
+      extract_token("İ Authorization: Bearer eyJ.eyJ.sig"),
+      Some("eyJ.eyJ.sig")
+    );
+    assert_eq!(extract_token("İ Authorization: Bearer ééé"), None);
+    assert_eq!(extract_token("Authorization: Bearer opaque-token"), None);
+    assert_eq!(extract_token("---"), None);
+    assert_eq!(extract_token("v1.2"), None);
+  }
+
+  #[test]
+  fn test_tokens_from_reader() {
+    let log = "eyJ.a.b\nGET /health\n\nstarting\nAuthorization: Bearer eyJ.c.d\n";
+    let tokens = tokens_from_reader("app.log", log.as_bytes()).unwrap();
+    assert_eq!(
+      tokens,
+      vec![
+        BatchToken {
+          source: "app.log:1".into(),
+          token: "eyJ.a.b".into()
+        },
+        BatchToken {
+          source: "app.log:5".into(),
+          token: "eyJ.c.d".into()
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_decode_batch() {
+    let tokens = [
+      token(json!({ "sub": "a", "exp": 4102444800_i64 }), "secret"),
+      token(json!({ "sub": "b", "exp": 1516239022 }), "secret"),
+      token(json!({ "sub": "c" }), "other"),
+      "not.a.token".into(),
+    ];
+    let tokens = tokens
+      .into_iter()
+      .enumerate()
+      .map(|(i, token)| BatchToken {
+        source: format!("tokens.txt:{}", i + 1),
+        token,
+      })
+      .collect();
+
+    let mut app = App::new(None, "secret".into());
+    let results = decode_batch(&mut app, tokens, false);
+
+    let statuses: Vec<_> = results.iter().map(|r| r.status).collect();
+    assert_eq!(
+      statuses,
+      vec![
+        TokenStatus::Valid,
+        TokenStatus::Expired,
+        TokenStatus::BadSignature,
+        TokenStatus::Malformed
+      ]
+    );
+    assert!(results[0].error.is_none());
+    assert_eq!(results[0].payload.as_ref().unwrap().0["sub"], "a");
+
+    let ndjson = to_ndjson(&results);
+    let first: serde_json::Value = serde_json::from_str(ndjson.lines().next().unwrap()).unwrap();
+    assert_eq!(first["status"], "valid");
+    assert_eq!(first["source"], "tokens.txt:1");
+    assert_eq!(ndjson.lines().count(), 4);
+
+    let summary = to_summary(&results);
+    assert!(
+      summary.contains("tokens.txt:2                   expired         The token has expired")
+    );
+    assert!(summary.ends_with(
+      "Status            Count
+valid                 1
+expired               1
+bad signature         1
+invalid claims        0
+unverified            0
+malformed             1
+total                 4"
+    ));
+  }
+
+  #[test]
+  fn test_decode_batch_without_secret() {
+    let tokens = vec![BatchToken {
+      source: "<stdin>:1".into(),
+      token: token(json!({ "sub": "a" }), "secret"),
+    }];
+
+    let mut app = App::new(None, "".into());
+    assert_eq!(
+      decode_batch(&mut app, tokens.clone(), false)[0].status,
+      TokenStatus::Unverified
+    );
+    assert_eq!(
+      decode_batch(&mut app, tokens, true)[0].status,
+      TokenStatus::Valid
+    );
+  }
+
+  #[test]
+  fn test_decode_batch_unsigned() {
+    let tokens = vec![BatchToken {
+      source: "<stdin>:1".into(),
+      token: "eyJhbGciOiJub25lIn0.eyJzdWIiOiJhIn0.".into(),
+    }];
+
+    let mut app = App::new(None, "secret".into());
+    let result = &decode_batch(&mut app, tokens, false)[0];
+    assert_eq!(result.status, TokenStatus::BadSignature);
+    assert_eq!(result.header, Some(json!({ "alg": "none" })));
+  }
+
+  #[test]
+  fn test_decode_batch_with_schema() {
+    let tokens = vec![BatchToken {
+      source: "<stdin>:1".into(),
+      token: token(json!({ "sub": "a" }), "secret"),
+    }];
+
+    let mut app = App::new(None, "secret".into());
+    app.data.schemas = Some(
+      Schemas::from_file(std::path::Path::new("./test_data/test_claims_schema.json")).unwrap(),
+    );
+    let result = &decode_batch(&mut app, tokens, false)[0];
+    assert_eq!(result.status, TokenStatus::InvalidClaims);
+    assert_eq!(
+      result.error.as_deref(),
+      Some("Schema violations: /exp is required")
+    );
+  }
+}
@@ -0,0 +1,378 @@
+use serde_json::{Map, Value};
+
+use super::{
+  jwks::request_jwks,
+  jwt_decoder::{decode_or_decrypt_token, DecodeArgs, DecodedToken},
+  models::{BlockState, StatefulTable},
+  utils::{format_duration, JWTError, JWTResult},
+  validation::ValidationSettings,
+  ActiveBlock, App, InputMode, Route, RouteId, TextInput,
+};
+
+/// claims shown as a delta when they differ
+const TIMESTAMP_CLAIMS: [&str; 4] = ["exp", "nbf", "iat", "auth_time"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+  Unchanged,
+  /// only in the right token
+  Added,
+  /// only in the left token
+  Removed,
+  Changed,
+}
+
+/// a value of the headers or claims, e.g. `claims.roles[0]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+  pub path: String,
+  pub kind: DiffKind,
+  pub left: Option<Value>,
+  pub right: Option<Value>,
+}
+
+impl DiffLine {
+  /// how much later the right timestamp claim is, e.g. `+1h 30m`
+  pub fn delta(&self) -> Option<String> {
+    let claim = self.path.strip_prefix("claims.")?;
+    if self.kind != DiffKind::Changed || !TIMESTAMP_CLAIMS.contains(&claim) {
+      return None;
+    }
+    let left = self.left.as_ref()?.as_i64()?;
+    let right = self.right.as_ref()?.as_i64()?;
+    Some(format_delta(right - left))
+  }
+}
+
+pub struct Compare {
+  pub left: TextInput,
+  pub right: TextInput,
+  /// secret both tokens are verified with
+  pub secret: TextInput,
+  pub diff: StatefulTable<DiffLine>,
+  /// signature verification result of both tokens
+  pub verification: String,
+  pub blocks: BlockState,
+}
+
+impl Default for Compare {
+  fn default() -> Self {
+    Self::new(String::new())
+  }
+}
+
+impl Compare {
+  pub fn new(secret: String) -> Self {
+    Self {
+      left: TextInput::default(),
+      right: TextInput::default(),
+      secret: TextInput::new(secret),
+      diff: StatefulTable::new(),
+      verification: String::new(),
+      blocks: BlockState::new(vec![
+        Route {
+          id: RouteId::Compare,
+          active_block: ActiveBlock::CompareLeft,
+        },
+        Route {
+          id: RouteId::Compare,
+          active_block: ActiveBlock::CompareRight,
+        },
+        Route {
+          id: RouteId::Compare,
+          active_block: ActiveBlock::CompareSecret,
+        },
+        Route {
+          id: RouteId::Compare,
+          active_block: ActiveBlock::CompareDiff,
+        },
+      ]),
+    }
+  }
+}
+
+/// decode both tokens, diff their headers and claims and verify them with the same secret
+pub fn compare_tokens(app: &mut App) {
+  let compare = &app.data.compare;
+  let secret = compare.secret.input.value().to_string();
+  if compare.secret.input_mode != InputMode::Editing {
+    request_jwks(&secret);
+  }
+  let (left, right) = (compare.left.input.value(), compare.right.input.value());
+  let decoded = match (left.trim(), right.trim()) {
+    ("", _) | (_, "") => None,
+    (left, right) => Some((
+      decode("left", left, &secret),
+      decode("right", right, &secret),
+    )),
+  };
+
+  let (left, right) = match decoded {
+    Some((Ok(left), Ok(right))) => (left, right),
+    failed => {
+      app.data.compare.diff.set_items(vec![]);
+      app.data.compare.verification = String::new();
+      if let Some((Err(e), _) | (_, Err(e))) = failed {
+        app.handle_error(e);
+      }
+      return;
+    }
+  };
+
+  let lines = diff_tokens(&left.0, &right.0);
+  if lines != app.data.compare.diff.items {
+    app.data.compare.diff.set_items(lines);
+  }
+  app.data.compare.verification = verification_summary(&secret, &left.1, &right.1);
+  app.data.error = String::new();
+}
+
+fn decode(
+  side: &str,
+  token: &str,
+  secret: &str,
+) -> JWTResult<(DecodedToken, JWTResult<DecodedToken>)> {
+  let args = DecodeArgs {
+    jwt: token.into(),
+    secret: secret.into(),
+    time_format_utc: false,
+    // only the signature is compared, claims like `exp` are part of the diff
+    ignore_exp: true,
+    validation: ValidationSettings::default(),
+  };
+  match decode_or_decrypt_token(&args) {
+    (Ok(decoded), verified) => Ok((decoded, verified)),
+    (Err(e), _) => Err(JWTError::Internal(format!(
+      "Unable to decode the {side} token: {e}"
+    ))),
+  }
+}
+
+fn verification_summary(
+  secret: &str,
+  left: &JWTResult<DecodedToken>,
+  right: &JWTResult<DecodedToken>,
+) -> String {
+  if secret.is_empty() {
+    return "Enter a secret to verify both tokens".into();
+  }
+  let status = |verified: &JWTResult<DecodedToken>| match verified {
+    Ok(_) => "Signature verified".to_string(),
+    Err(e) => e.to_string(),
+  };
+  let conclusion = match (left.is_ok(), right.is_ok()) {
+    (true, true) => "Both tokens verify with the secret",
+    (true, false) => "Only the left token verifies with the secret",
+    (false, true) => "Only the right token verifies with the secret",
+    (false, false) => "Neither token verifies with the secret",
+  };
+  format!(
+    "{conclusion}\nLeft:  {}\nRight: {}",
+    status(left),
+    status(right)
+  )
+}
+
+/// every value of the headers and claims, with the differences between the tokens
+pub fn diff_tokens(left: &DecodedToken, right: &DecodedToken) -> Vec<DiffLine> {
+  let mut lines = vec![];
+  for (path, left, right) in [
+    (
+      "jwe_header",
+      to_value(&left.jwe_header),
+      to_value(&right.jwe_header),
+    ),
+    ("header", header_value(left), header_value(right)),
+    ("claims", to_value(&left.claims), to_value(&right.claims)),
+  ] {
+    diff_values(path, left.as_ref(), right.as_ref(), &mut lines);
+  }
+  lines
+}
+
+fn header_value(token: &DecodedToken) -> Option<Value> {
+  match &token.unsigned_header {
+    Some(unsigned) => Some(Value::Object(unsigned.clone())),
+    None => to_value(&token.header),
+  }
+}
+
+fn to_value<T: serde::Serialize>(value: &T) -> Option<Value> {
+  serde_json::to_value(value)
+    .ok()
+    .map(without_nulls)
+    .filter(|v| !v.is_null())
+}
+
+/// unset header fields are serialized as `null`
+fn without_nulls(value: Value) -> Value {
+  match value {
+    Value::Object(map) => Value::Object(
+      map
+        .into_iter()
+        .filter(|(_, v)| !v.is_null())
+        .map(|(k, v)| (k, without_nulls(v)))
+        .collect::<Map<_, _>>(),
+    ),
+    v => v,
+  }
+}
+
+fn diff_values(path: &str, left: Option<&Value>, right: Option<&Value>, out: &mut Vec<DiffLine>) {
+  match (left, right) {
+    (Some(Value::Object(l)), Some(Value::Object(r))) => {
+      let mut keys: Vec<&String> = l.keys().chain(r.keys()).collect();
+      keys.sort();
+      keys.dedup();
+      for key in keys {
+        diff_values(&format!("{path}.{key}"), l.get(key), r.get(key), out);
+      }
+    }
+    (Some(Value::Array(l)), Some(Value::Array(r))) => {
+      for i in 0..l.len().max(r.len()) {
+        diff_values(&format!("{path}[{i}]"), l.get(i), r.get(i), out);
+      }
+    }
+    (None, None) => {}
+    (left, right) => {
+      let kind = match (left, right) {
+        (Some(l), Some(r)) if l == r => DiffKind::Unchanged,
+        (Some(_), Some(_)) => DiffKind::Changed,
+        (None, _) => DiffKind::Added,
+        (_, None) => DiffKind::Removed,
+      };
+      out.push(DiffLine {
+        path: path.into(),
+        kind,
+        left: left.cloned(),
+        right: right.cloned(),
+      });
+    }
+  }
+}
+
+/// signed duration like `+1d 2h` or `-30s`
+fn format_delta(seconds: i64) -> String {
+  let duration = format_duration(seconds.unsigned_abs());
+  match seconds {
+    0 => duration,
+    s if s < 0 => format!("-{duration}"),
+    _ => format!("+{duration}"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use jsonwebtoken::{encode, EncodingKey, Header};
+  use serde_json::json;
+
+  use super::*;
+
+  fn token(claims: Value, secret: &str) -> String {
+    encode(
+      &Header::default(),
+      &claims,
+      &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .unwrap()
+  }
+
+  fn line(path: &str, kind: DiffKind, left: Option<Value>, right: Option<Value>) -> DiffLine {
+    DiffLine {
+      path: path.into(),
+      kind,
+      left,
+      right,
+    }
+  }
+
+  #[test]
+  fn test_format_delta() {
+    assert_eq!(format_delta(0), "0s");
+    assert_eq!(format_delta(5400), "+1h 30m");
+    assert_eq!(format_delta(-90061), "-1d 1h 1m 1s");
+  }
+
+  #[test]
+  fn test_compare_tokens() {
+    let mut app = App::default();
+    app.data.compare.left = TextInput::new(token(
+      json!({ "sub": "alice", "exp": 1516239022, "roles": ["admin", "user"] }),
+      "secret",
+    ));
+    app.data.compare.right = TextInput::new(token(
+      json!({ "sub": "alice", "exp": 1516242622, "roles": ["admin"], "scope": "read" }),
+      "other",
+    ));
+    app.data.compare.secret = TextInput::new("secret".into());
+
+    compare_tokens(&mut app);
+
+    assert_eq!(app.data.error, "");
+    assert_eq!(
+      app.data.compare.diff.items,
+      vec![
+        line(
+          "header.alg",
+          DiffKind::Unchanged,
+          Some(json!("HS256")),
+          Some(json!("HS256"))
+        ),
+        line(
+          "header.typ",
+          DiffKind::Unchanged,
+          Some(json!("JWT")),
+          Some(json!("JWT"))
+        ),
+        line(
+          "claims.exp",
+          DiffKind::Changed,
+          Some(json!(1516239022)),
+          Some(json!(1516242622))
+        ),
+        line(
+          "claims.roles[0]",
+          DiffKind::Unchanged,
+          Some(json!("admin")),
+          Some(json!("admin"))
+        ),
+        line(
+          "claims.roles[1]",
+          DiffKind::Removed,
+          Some(json!("user")),
+          None
+        ),
+        line("claims.scope", DiffKind::Added, None, Some(json!("read"))),
+        line(
+          "claims.sub",
+          DiffKind::Unchanged,
+          Some(json!("alice")),
+          Some(json!("alice"))
+        ),
+      ]
+    );
+    assert_eq!(
+      app.data.compare.diff.items[2].delta(),
+      Some("+1h".to_string())
+    );
+    assert_eq!(
+      app.data.compare.verification,
+      "Only the left token verifies with the secret\nLeft:  Signature verified\nRight: The JWT provided has an invalid signature. Provide a valid secret: InvalidSignature"
+    );
+  }
+
+  #[test]
+  fn test_compare_invalid_token() {
+    let mut app = App::default();
+    app.data.compare.left = TextInput::new(token(json!({ "sub": "alice" }), "secret"));
+    app.data.compare.right = TextInput::new("not-a-token".into());
+
+    compare_tokens(&mut app);
+
+    assert!(app
+      .data
+      .error
+      .starts_with("Unable to decode the right token:"));
+    assert!(app.data.compare.diff.items.is_empty());
+  }
+}
@@ -0,0 +1,370 @@
+use std::{collections::HashSet, fmt, ops::Range, str::from_utf8};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{
+  de::{self, MapAccess, SeqAccess, Visitor},
+  Deserialize, Deserializer,
+};
+use serde_json::Value;
+
+/// names of the segments of a JWS and of a JWE
+const JWS_SEGMENTS: [&str; 3] = ["header", "payload", "signature"];
+const JWE_SEGMENTS: [&str; 5] = ["header", "encrypted key", "iv", "ciphertext", "tag"];
+
+/// a problem of a token that can't be decoded, with the offending part of the token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+  pub message: String,
+  /// byte range in the token
+  pub span: Range<usize>,
+}
+
+impl Problem {
+  fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+    Self {
+      message: message.into(),
+      span,
+    }
+  }
+}
+
+/// what a tolerant parse of a token could decode, and what is wrong with it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diagnosis {
+  pub header: Option<Value>,
+  pub claims: Option<Value>,
+  pub problems: Vec<Problem>,
+}
+
+/// parse the token segment by segment, carrying on after a problem so that every problem is found
+pub fn diagnose_token(token: &str) -> Diagnosis {
+  let mut diagnosis = Diagnosis::default();
+  let start = token.len() - token.trim_start().len();
+  let end = token.trim_end().len();
+  if start > 0 {
+    diagnosis.problems.push(Problem::new(
+      "Leading whitespace before the token",
+      0..start,
+    ));
+  }
+  if end < token.len() && end > start {
+    diagnosis.problems.push(Problem::new(
+      "Trailing whitespace after the token",
+      end..token.len(),
+    ));
+  }
+  if start >= end {
+    return diagnosis;
+  }
+
+  let mut segments = vec![];
+  let mut offset = start;
+  for segment in token[start..end].split('.') {
+    segments.push(offset..offset + segment.len());
+    offset += segment.len() + 1;
+  }
+  let names: &[&str] = match segments.len() {
+    5 => &JWE_SEGMENTS,
+    3 => &JWS_SEGMENTS,
+    count => {
+      diagnosis.problems.push(Problem::new(
+        format!("Expected 3 segments (JWS) or 5 (JWE) separated by `.`, found {count}"),
+        start..end,
+      ));
+      &JWS_SEGMENTS
+    }
+  };
+
+  for (i, span) in segments.iter().enumerate() {
+    let name = names.get(i).copied().unwrap_or("extra segment");
+    let bytes = decode_segment(token, name, span.clone(), &mut diagnosis.problems);
+    // only the header of a JWE is JSON, the rest is encrypted
+    let is_json = i == 0 || (i == 1 && names.len() == JWS_SEGMENTS.len());
+    let (true, Some(bytes)) = (is_json, bytes) else {
+      continue;
+    };
+    let value = parse_json(&bytes, name, span.clone(), &mut diagnosis.problems);
+    match i {
+      0 => diagnosis.header = value,
+      _ => diagnosis.claims = value,
+    }
+  }
+  diagnosis
+}
+
+/// base64url decode a segment, also when it has padding or characters of the standard alphabet
+fn decode_segment(
+  token: &str,
+  name: &str,
+  span: Range<usize>,
+  problems: &mut Vec<Problem>,
+) -> Option<Vec<u8>> {
+  let segment = &token[span.clone()];
+  if segment.is_empty() {
+    if name == "header" || name == "payload" {
+      problems.push(Problem::new(format!("The {name} is empty"), span));
+    }
+    return Some(vec![]);
+  }
+
+  let unpadded = segment.trim_end_matches('=');
+  if unpadded.len() < segment.len() {
+    problems.push(Problem::new(
+      format!("The {name} has base64 padding (`=`), JWTs use unpadded base64url"),
+      span.start + unpadded.len()..span.end,
+    ));
+  }
+  let mut invalid = false;
+  for (i, c) in unpadded.char_indices() {
+    if !(c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+      let at = span.start + i;
+      problems.push(Problem::new(
+        format!(
+          "Invalid base64url character {c:?} in the {name} at column {}",
+          token[..at].chars().count() + 1
+        ),
+        at..at + c.len_utf8(),
+      ));
+      invalid |= c != '+' && c != '/';
+    }
+  }
+  if invalid {
+    return None;
+  }
+
+  let normalized = unpadded.replace('+', "-").replace('/', "_");
+  match URL_SAFE_NO_PAD.decode(normalized) {
+    Ok(bytes) => Some(bytes),
+    Err(_) => {
+      problems.push(Problem::new(
+        format!("The {name} isn't valid base64url, it may be truncated"),
+        span,
+      ));
+      None
+    }
+  }
+}
+
+/// parse a decoded segment as JSON, the span of a problem is the base64 group of the offending byte
+fn parse_json(
+  bytes: &[u8],
+  name: &str,
+  span: Range<usize>,
+  problems: &mut Vec<Problem>,
+) -> Option<Value> {
+  let group = |byte: usize| {
+    let start = span.start + byte / 3 * 4;
+    start.min(span.end)..(start + 4).min(span.end)
+  };
+  let text = match from_utf8(bytes) {
+    Ok(text) => text,
+    Err(e) => {
+      problems.push(Problem::new(
+        format!("The {name} isn't valid UTF-8 at byte {}", e.valid_up_to()),
+        group(e.valid_up_to()),
+      ));
+      return None;
+    }
+  };
+  if text.is_empty() {
+    return None;
+  }
+
+  let position = |e: &serde_json::Error| {
+    let line_start: usize = text
+      .split_inclusive('\n')
+      .take(e.line().saturating_sub(1))
+      .map(str::len)
+      .sum();
+    group(line_start + e.column().saturating_sub(1))
+  };
+  let value = match serde_json::from_str::<Value>(text) {
+    Ok(value) => value,
+    Err(e) => {
+      problems.push(Problem::new(
+        format!(
+          "Invalid JSON in the {name} at line {} column {}: {}",
+          e.line(),
+          e.column(),
+          message_of(&e)
+        ),
+        position(&e),
+      ));
+      return None;
+    }
+  };
+  if let Err(e) = serde_json::from_str::<UniqueKeys>(text) {
+    let message = message_of(&e);
+    let message = message[..1].to_uppercase() + &message[1..];
+    problems.push(Problem::new(
+      format!(
+        "{message} in the {name} at line {} column {}",
+        e.line(),
+        e.column()
+      ),
+      position(&e),
+    ));
+  }
+  if !value.is_object() {
+    problems.push(Problem::new(
+      format!("The {name} is not a JSON object"),
+      span,
+    ));
+  }
+  Some(value)
+}
+
+/// the message of a JSON error without its position
+fn message_of(e: &serde_json::Error) -> String {
+  let message = e.to_string();
+  let position = format!(" at line {} column {}", e.line(), e.column());
+  message
+    .strip_suffix(&position)
+    .unwrap_or(&message)
+    .to_string()
+}
+
+/// any JSON value, fails on the first duplicate key of an object which `Value` silently drops
+struct UniqueKeys;
+
+impl<'de> Deserialize<'de> for UniqueKeys {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_any(UniqueKeysVisitor)
+  }
+}
+
+struct UniqueKeysVisitor;
+
+impl<'de> Visitor<'de> for UniqueKeysVisitor {
+  type Value = UniqueKeys;
+
+  fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "a JSON value")
+  }
+
+  fn visit_bool<E>(self, _: bool) -> Result<UniqueKeys, E> {
+    Ok(UniqueKeys)
+  }
+
+  fn visit_i64<E>(self, _: i64) -> Result<UniqueKeys, E> {
+    Ok(UniqueKeys)
+  }
+
+  fn visit_u64<E>(self, _: u64) -> Result<UniqueKeys, E> {
+    Ok(UniqueKeys)
+  }
+
+  fn visit_f64<E>(self, _: f64) -> Result<UniqueKeys, E> {
+    Ok(UniqueKeys)
+  }
+
+  fn visit_str<E>(self, _: &str) -> Result<UniqueKeys, E> {
+    Ok(UniqueKeys)
+  }
+
+  fn visit_unit<E>(self) -> Result<UniqueKeys, E> {
+    Ok(UniqueKeys)
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<UniqueKeys, A::Error> {
+    while seq.next_element::<UniqueKeys>()?.is_some() {}
+    Ok(UniqueKeys)
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<UniqueKeys, A::Error> {
+    let mut keys = HashSet::new();
+    while let Some(key) = map.next_key::<String>()? {
+      if !keys.insert(key.clone()) {
+        return Err(de::Error::custom(format!("duplicate key `{key}`")));
+      }
+      map.next_value::<UniqueKeys>()?;
+    }
+    Ok(UniqueKeys)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  fn b64(json: &str) -> String {
+    URL_SAFE_NO_PAD.encode(json)
+  }
+
+  fn messages(diagnosis: &Diagnosis) -> Vec<&str> {
+    diagnosis
+      .problems
+      .iter()
+      .map(|p| p.message.as_str())
+      .collect()
+  }
+
+  #[test]
+  fn test_diagnose_valid_token() {
+    let token = format!(
+      "{}.{}.c2ln",
+      b64(r#"{"alg":"HS256"}"#),
+      b64(r#"{"sub":"a"}"#)
+    );
+    let diagnosis = diagnose_token(&token);
+    assert_eq!(diagnosis.problems, vec![]);
+    assert_eq!(diagnosis.header, Some(json!({ "alg": "HS256" })));
+    assert_eq!(diagnosis.claims, Some(json!({ "sub": "a" })));
+  }
+
+  #[test]
+  fn test_diagnose_segments_and_characters() {
+    let header = b64(r#"{"alg":"HS256"}"#);
+    let token = format!("{header}.e*J9= \n");
+    let diagnosis = diagnose_token(&token);
+    assert_eq!(
+      messages(&diagnosis),
+      vec![
+        "Trailing whitespace after the token",
+        "Expected 3 segments (JWS) or 5 (JWE) separated by `.`, found 2",
+        "The payload has base64 padding (`=`), JWTs use unpadded base64url",
+        "Invalid base64url character '*' in the payload at column 23",
+      ]
+    );
+    let star = header.len() + 2;
+    assert_eq!(diagnosis.problems[3].span, star..star + 1);
+    assert_eq!(diagnosis.header, Some(json!({ "alg": "HS256" })));
+    assert_eq!(diagnosis.claims, None);
+  }
+
+  #[test]
+  fn test_diagnose_json() {
+    let token = format!(
+      "{}.{}.",
+      b64("{\"alg\":\"HS256\",\n\"alg\":\"none\"}"),
+      b64(r#"{"sub":"a",}"#)
+    );
+    let diagnosis = diagnose_token(&token);
+    assert_eq!(
+      messages(&diagnosis),
+      vec![
+        "Duplicate key `alg` in the header at line 2 column 5",
+        "Invalid JSON in the payload at line 1 column 12: trailing comma",
+      ]
+    );
+    // the duplicate key ends at byte 20 of the header, in its 7th base64 group
+    assert_eq!(diagnosis.problems[0].span, 24..28);
+    assert_eq!(diagnosis.header, Some(json!({ "alg": "none" })));
+  }
+
+  #[test]
+  fn test_diagnose_utf8() {
+    let token = format!(
+      "{}.{}.",
+      b64(r#"{"alg":"HS256"}"#),
+      URL_SAFE_NO_PAD.encode(b"{\"sub\":\"\xff\"}")
+    );
+    let diagnosis = diagnose_token(&token);
+    assert_eq!(
+      messages(&diagnosis),
+      vec!["The payload isn't valid UTF-8 at byte 8"]
+    );
+  }
+}
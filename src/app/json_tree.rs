@@ -0,0 +1,374 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use super::{
+  models::{Scrollable, StatefulTable},
+  TextInput,
+};
+
+/// a line of the tree, a value or the closing bracket of an object or array
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeRow {
+  /// JSON pointer of the value, `""` for the root
+  pub pointer: String,
+  pub depth: usize,
+  /// key of the value in its object
+  pub key: Option<String>,
+  pub kind: RowKind,
+  /// whether a comma follows the value
+  pub comma: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowKind {
+  Leaf(Value),
+  /// `{` or `[` of a value with `len` entries, a collapsed one is shown on a single line
+  Open {
+    bracket: char,
+    len: usize,
+    collapsed: bool,
+  },
+  Close(char),
+}
+
+/// the header or payload JSON as a collapsible tree
+#[derive(Debug, Default)]
+pub struct JsonTree {
+  /// the JSON as shown before, copied when the root is selected
+  txt: String,
+  value: Option<Value>,
+  /// pointers of the collapsed objects and arrays
+  collapsed: HashSet<String>,
+  pub rows: StatefulTable<TreeRow>,
+  /// case-insensitive search of the keys
+  pub search: TextInput,
+}
+
+impl JsonTree {
+  pub fn new(txt: String) -> JsonTree {
+    let mut tree = JsonTree {
+      value: serde_json::from_str(&txt).ok(),
+      txt,
+      ..JsonTree::default()
+    };
+    tree.build_rows();
+    tree
+  }
+
+  pub fn get_txt(&self) -> String {
+    self.txt.clone()
+  }
+
+  pub fn selected(&self) -> Option<&TreeRow> {
+    self.rows.items.get(self.rows.state.selected()?)
+  }
+
+  /// the selected value, strings without quotes and the root as the original text
+  pub fn selected_value(&self) -> Option<String> {
+    let row = self.selected()?;
+    if row.pointer.is_empty() {
+      return Some(self.get_txt());
+    }
+    match self.value.as_ref()?.pointer(&row.pointer)? {
+      Value::String(s) => Some(s.clone()),
+      value => serde_json::to_string_pretty(value).ok(),
+    }
+  }
+
+  /// collapse or expand the selected object or array, also from its closing bracket
+  pub fn toggle_selected(&mut self) {
+    let Some(row) = self.selected() else {
+      return;
+    };
+    if matches!(row.kind, RowKind::Leaf(_)) {
+      return;
+    }
+    let pointer = row.pointer.clone();
+    if !self.collapsed.remove(&pointer) {
+      self.collapsed.insert(pointer.clone());
+    }
+    self.build_rows();
+    self.select(&pointer);
+  }
+
+  /// whether the key of the row matches the search
+  pub fn is_match(&self, row: &TreeRow) -> bool {
+    let search = self.search.input.value().trim().to_lowercase();
+    !search.is_empty()
+      && !matches!(row.kind, RowKind::Close(_))
+      && row
+        .key
+        .as_ref()
+        .is_some_and(|key| key.to_lowercase().contains(&search))
+  }
+
+  /// indexes of the rows matching the search
+  pub fn matches(&self) -> Vec<usize> {
+    (0..self.rows.items.len())
+      .filter(|i| self.is_match(&self.rows.items[*i]))
+      .collect()
+  }
+
+  /// expand every match of the search and select the first from the selected row on
+  pub fn search_keys(&mut self) {
+    let search = self.search.input.value().trim().to_lowercase();
+    if search.is_empty() {
+      return;
+    }
+    if let Some(value) = &self.value {
+      let mut pointers = vec![];
+      matching_pointers(value, "", &search, &mut pointers);
+      for pointer in pointers {
+        // the ancestors of a match
+        for (i, _) in pointer.match_indices('/') {
+          self.collapsed.remove(&pointer[..i]);
+        }
+      }
+    }
+    self.build_rows();
+    let selected = self.rows.state.selected().unwrap_or_default();
+    self.select_match(selected);
+  }
+
+  /// select the next match of the search, wrapping around
+  pub fn next_match(&mut self) {
+    let selected = self.rows.state.selected().unwrap_or_default();
+    self.select_match(selected + 1);
+  }
+
+  fn select_match(&mut self, from: usize) {
+    let matches = self.matches();
+    if let Some(i) = matches
+      .iter()
+      .find(|i| **i >= from)
+      .or_else(|| matches.first())
+    {
+      self.rows.state.select(Some(*i));
+    }
+  }
+
+  fn select(&mut self, pointer: &str) {
+    if let Some(i) = self.rows.items.iter().position(|r| r.pointer == pointer) {
+      self.rows.state.select(Some(i));
+    }
+  }
+
+  fn build_rows(&mut self) {
+    let mut rows = vec![];
+    if let Some(value) = &self.value {
+      push_rows(value, "", None, 0, false, &self.collapsed, &mut rows);
+    }
+    self.rows.set_items(rows);
+  }
+}
+
+impl Scrollable for JsonTree {
+  fn scroll_down(&mut self, increment: usize) {
+    self.rows.scroll_down(increment);
+  }
+
+  fn scroll_up(&mut self, decrement: usize) {
+    self.rows.scroll_up(decrement);
+  }
+}
+
+fn push_rows(
+  value: &Value,
+  pointer: &str,
+  key: Option<&str>,
+  depth: usize,
+  comma: bool,
+  collapsed: &HashSet<String>,
+  rows: &mut Vec<TreeRow>,
+) {
+  let row = |kind, comma| TreeRow {
+    pointer: pointer.to_string(),
+    depth,
+    key: key.map(String::from),
+    kind,
+    comma,
+  };
+  // children with their key in an object
+  let (bracket, children): (char, Vec<(Option<&str>, &Value)>) = match value {
+    Value::Object(map) => (
+      '{',
+      map.iter().map(|(k, v)| (Some(k.as_str()), v)).collect(),
+    ),
+    Value::Array(items) => ('[', items.iter().map(|v| (None, v)).collect()),
+    leaf => {
+      rows.push(row(RowKind::Leaf(leaf.clone()), comma));
+      return;
+    }
+  };
+
+  let is_collapsed = collapsed.contains(pointer);
+  rows.push(row(
+    RowKind::Open {
+      bracket,
+      len: children.len(),
+      collapsed: is_collapsed,
+    },
+    // a collapsed value is a single row with the comma
+    comma && is_collapsed,
+  ));
+  if is_collapsed {
+    return;
+  }
+  let last = children.len().saturating_sub(1);
+  for (i, (child_key, child)) in children.into_iter().enumerate() {
+    let token = child_key.map_or(i.to_string(), escape);
+    push_rows(
+      child,
+      &format!("{pointer}/{token}"),
+      child_key,
+      depth + 1,
+      i < last,
+      collapsed,
+      rows,
+    );
+  }
+  rows.push(TreeRow {
+    key: None,
+    ..row(
+      RowKind::Close(if bracket == '{' { '}' } else { ']' }),
+      comma,
+    )
+  });
+}
+
+/// pointers of the values whose key contains the search
+fn matching_pointers(value: &Value, pointer: &str, search: &str, out: &mut Vec<String>) {
+  match value {
+    Value::Object(map) => {
+      for (key, child) in map {
+        let child_pointer = format!("{pointer}/{}", escape(key));
+        if key.to_lowercase().contains(search) {
+          out.push(child_pointer.clone());
+        }
+        matching_pointers(child, &child_pointer, search, out);
+      }
+    }
+    Value::Array(items) => {
+      for (i, child) in items.iter().enumerate() {
+        matching_pointers(child, &format!("{pointer}/{i}"), search, out);
+      }
+    }
+    _ => {}
+  }
+}
+
+/// escape a key as a JSON pointer reference token
+fn escape(key: &str) -> String {
+  key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  fn tree() -> JsonTree {
+    JsonTree::new(
+      serde_json::to_string_pretty(&json!({
+        "sub": "alice",
+        "realm_access": { "roles": ["admin", "user"] },
+        "a/b": 1
+      }))
+      .unwrap(),
+    )
+  }
+
+  fn pointers(tree: &JsonTree) -> Vec<&str> {
+    tree.rows.items.iter().map(|r| r.pointer.as_str()).collect()
+  }
+
+  #[test]
+  fn test_rows() {
+    let tree = tree();
+    assert_eq!(
+      pointers(&tree),
+      vec![
+        "",
+        "/a~1b",
+        "/realm_access",
+        "/realm_access/roles",
+        "/realm_access/roles/0",
+        "/realm_access/roles/1",
+        "/realm_access/roles",
+        "/realm_access",
+        "/sub",
+        "",
+      ]
+    );
+    assert_eq!(
+      tree.rows.items[4],
+      TreeRow {
+        pointer: "/realm_access/roles/0".into(),
+        depth: 3,
+        key: None,
+        kind: RowKind::Leaf(json!("admin")),
+        comma: true,
+      }
+    );
+    assert_eq!(tree.rows.items[7].kind, RowKind::Close('}'));
+    assert!(tree.rows.items[7].comma);
+    assert!(!tree.rows.items[8].comma);
+  }
+
+  #[test]
+  fn test_toggle_and_copy() {
+    let mut tree = tree();
+    tree.rows.state.select(Some(2));
+    assert_eq!(
+      tree.selected_value(),
+      Some("{\n  \"roles\": [\n    \"admin\",\n    \"user\"\n  ]\n}".into())
+    );
+
+    tree.toggle_selected();
+    assert_eq!(
+      pointers(&tree),
+      vec!["", "/a~1b", "/realm_access", "/sub", ""]
+    );
+    assert_eq!(
+      tree.rows.items[2].kind,
+      RowKind::Open {
+        bracket: '{',
+        len: 1,
+        collapsed: true
+      }
+    );
+    assert!(tree.rows.items[2].comma);
+
+    tree.toggle_selected();
+    assert_eq!(tree.rows.items.len(), 10);
+    tree.rows.state.select(Some(5));
+    assert_eq!(tree.selected_value(), Some("user".into()));
+    tree.rows.state.select(Some(0));
+    assert_eq!(tree.selected_value(), Some(tree.get_txt()));
+  }
+
+  #[test]
+  fn test_search_keys() {
+    let mut tree = tree();
+    tree.rows.state.select(Some(2));
+    tree.toggle_selected();
+
+    tree.search.input = "ROLE".into();
+    tree.search_keys();
+    // the collapsed parent of the match is expanded
+    assert_eq!(tree.rows.items.len(), 10);
+    assert_eq!(tree.matches(), vec![3]);
+    assert_eq!(tree.rows.state.selected(), Some(3));
+
+    tree.search.input = "s".into();
+    tree.search_keys();
+    assert_eq!(tree.matches(), vec![2, 3, 8]);
+    assert_eq!(tree.rows.state.selected(), Some(3));
+    tree.next_match();
+    assert_eq!(tree.rows.state.selected(), Some(8));
+    tree.next_match();
+    assert_eq!(tree.rows.state.selected(), Some(2));
+  }
+}
@@ -0,0 +1,1224 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use aes::{Aes128, Aes192, Aes256};
+use aes_gcm::{
+  aead::{consts::U12, Aead, Payload as AeadPayload},
+  AesGcm, KeyInit,
+};
+use aes_kw::Kek;
+use base64::{
+  engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+  Engine,
+};
+use cbc::cipher::{block_padding::Pkcs7, BlockCipher, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use hmac::{Hmac, Mac};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use rand::{rngs::OsRng, RngCore};
+use rsa::{
+  pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey},
+  pkcs8::{DecodePrivateKey, DecodePublicKey},
+  BigUint, Oaep, RsaPrivateKey, RsaPublicKey,
+};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha1::Sha1;
+use sha2::{Sha256, Sha384, Sha512};
+
+use super::utils::{get_jwe_secret_from_file_or_input, JWTError, JWTResult, SecretType};
+
+/// key management algorithms, see RFC 7518 section 4.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAlgorithm {
+  RsaOaep,
+  RsaOaep256,
+  EcdhEs,
+  EcdhEsA128Kw,
+  EcdhEsA192Kw,
+  EcdhEsA256Kw,
+  A128Kw,
+  A192Kw,
+  A256Kw,
+  Dir,
+}
+
+impl KeyAlgorithm {
+  pub const ALL: [KeyAlgorithm; 10] = [
+    KeyAlgorithm::RsaOaep,
+    KeyAlgorithm::RsaOaep256,
+    KeyAlgorithm::EcdhEs,
+    KeyAlgorithm::EcdhEsA128Kw,
+    KeyAlgorithm::EcdhEsA192Kw,
+    KeyAlgorithm::EcdhEsA256Kw,
+    KeyAlgorithm::A128Kw,
+    KeyAlgorithm::A192Kw,
+    KeyAlgorithm::A256Kw,
+    KeyAlgorithm::Dir,
+  ];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      KeyAlgorithm::RsaOaep => "RSA-OAEP",
+      KeyAlgorithm::RsaOaep256 => "RSA-OAEP-256",
+      KeyAlgorithm::EcdhEs => "ECDH-ES",
+      KeyAlgorithm::EcdhEsA128Kw => "ECDH-ES+A128KW",
+      KeyAlgorithm::EcdhEsA192Kw => "ECDH-ES+A192KW",
+      KeyAlgorithm::EcdhEsA256Kw => "ECDH-ES+A256KW",
+      KeyAlgorithm::A128Kw => "A128KW",
+      KeyAlgorithm::A192Kw => "A192KW",
+      KeyAlgorithm::A256Kw => "A256KW",
+      KeyAlgorithm::Dir => "dir",
+    }
+  }
+
+  /// whether the key is a shared secret rather than a private key
+  pub fn is_symmetric(&self) -> bool {
+    matches!(
+      self,
+      KeyAlgorithm::A128Kw | KeyAlgorithm::A192Kw | KeyAlgorithm::A256Kw | KeyAlgorithm::Dir
+    )
+  }
+
+  fn is_rsa(&self) -> bool {
+    matches!(self, KeyAlgorithm::RsaOaep | KeyAlgorithm::RsaOaep256)
+  }
+
+  /// length in bytes of the AES key wrapping key, if the CEK is wrapped
+  fn key_wrap_len(&self) -> Option<usize> {
+    match self {
+      KeyAlgorithm::A128Kw | KeyAlgorithm::EcdhEsA128Kw => Some(16),
+      KeyAlgorithm::A192Kw | KeyAlgorithm::EcdhEsA192Kw => Some(24),
+      KeyAlgorithm::A256Kw | KeyAlgorithm::EcdhEsA256Kw => Some(32),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for KeyAlgorithm {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+impl FromStr for KeyAlgorithm {
+  type Err = JWTError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    KeyAlgorithm::ALL
+      .into_iter()
+      .find(|alg| alg.name() == s)
+      .ok_or_else(|| JWTError::Internal(format!("Unsupported JWE key algorithm {s:?}")))
+  }
+}
+
+/// content encryption algorithms, see RFC 7518 section 5.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncryption {
+  A128CbcHs256,
+  A192CbcHs384,
+  A256CbcHs512,
+  A128Gcm,
+  A192Gcm,
+  A256Gcm,
+}
+
+impl ContentEncryption {
+  pub const ALL: [ContentEncryption; 6] = [
+    ContentEncryption::A128CbcHs256,
+    ContentEncryption::A192CbcHs384,
+    ContentEncryption::A256CbcHs512,
+    ContentEncryption::A128Gcm,
+    ContentEncryption::A192Gcm,
+    ContentEncryption::A256Gcm,
+  ];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      ContentEncryption::A128CbcHs256 => "A128CBC-HS256",
+      ContentEncryption::A192CbcHs384 => "A192CBC-HS384",
+      ContentEncryption::A256CbcHs512 => "A256CBC-HS512",
+      ContentEncryption::A128Gcm => "A128GCM",
+      ContentEncryption::A192Gcm => "A192GCM",
+      ContentEncryption::A256Gcm => "A256GCM",
+    }
+  }
+
+  /// length in bytes of the content encryption key
+  pub fn key_len(&self) -> usize {
+    match self {
+      ContentEncryption::A128CbcHs256 => 32,
+      ContentEncryption::A192CbcHs384 => 48,
+      ContentEncryption::A256CbcHs512 => 64,
+      ContentEncryption::A128Gcm => 16,
+      ContentEncryption::A192Gcm => 24,
+      ContentEncryption::A256Gcm => 32,
+    }
+  }
+}
+
+impl fmt::Display for ContentEncryption {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+impl FromStr for ContentEncryption {
+  type Err = JWTError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    ContentEncryption::ALL
+      .into_iter()
+      .find(|enc| enc.name() == s)
+      .ok_or_else(|| JWTError::Internal(format!("Unsupported JWE content encryption {s:?}")))
+  }
+}
+
+/// protected header of a JWE, readable without the key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JweHeader {
+  pub alg: String,
+  pub enc: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub typ: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cty: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub kid: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub zip: Option<String>,
+  /// ephemeral public key of ECDH-ES
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub epk: Option<Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub apu: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub apv: Option<String>,
+  #[serde(flatten)]
+  pub other: BTreeMap<String, Value>,
+}
+
+/// claims encrypted to a public key carry no proof of who encrypted them
+pub const UNSIGNED_JWE: &str =
+  "JWE without a nested JWS isn't signed, anyone with the recipient's public key can encrypt its claims";
+
+impl JweHeader {
+  /// whether decrypting proves the sender had the key. Only shared keys do, anyone can encrypt to
+  /// an RSA or EC public key
+  pub fn authenticates_sender(&self) -> bool {
+    self
+      .alg
+      .parse::<KeyAlgorithm>()
+      .is_ok_and(|alg| alg.is_symmetric())
+  }
+}
+
+/// JWE compact serialization has five parts, a JWS only three
+pub fn is_jwe(token: &str) -> bool {
+  token.trim().split('.').count() == 5
+}
+
+pub fn decode_jwe_header(token: &str) -> JWTResult<JweHeader> {
+  let header = token.trim().split('.').next().unwrap_or_default();
+  Ok(serde_json::from_slice(&decode_part(header)?)?)
+}
+
+/// decrypt a JWE in compact serialization, returns its protected header and plaintext
+pub fn decrypt_jwe(token: &str, secret_string: &str) -> JWTResult<(JweHeader, Vec<u8>)> {
+  let parts: Vec<&str> = token.trim().split('.').collect();
+  let [protected, encrypted_key, iv, ciphertext, tag] = parts[..] else {
+    return Err(JWTError::Internal(
+      "The JWE provided is invalid, expected 5 parts".into(),
+    ));
+  };
+  let header = decode_jwe_header(token)?;
+  let alg: KeyAlgorithm = header.alg.parse()?;
+  let enc: ContentEncryption = header.enc.parse()?;
+  if let Some(zip) = &header.zip {
+    return Err(JWTError::Internal(format!(
+      "Compressed JWE payloads are not supported (zip {zip:?})"
+    )));
+  }
+  if secret_string.is_empty() {
+    return Err(JWTError::Internal(
+      "Provide a key to decrypt the JWE".into(),
+    ));
+  }
+
+  let key = decryption_key(&alg, &header, secret_string)?;
+  let cek = content_encryption_key(&alg, &enc, &header, key, &decode_part(encrypted_key)?)?;
+  let plaintext = decrypt_content(
+    &enc,
+    &cek,
+    &decode_part(iv)?,
+    protected.as_bytes(),
+    &decode_part(ciphertext)?,
+    &decode_part(tag)?,
+  )?;
+  Ok((header, plaintext))
+}
+
+fn decode_part(part: &str) -> JWTResult<Vec<u8>> {
+  URL_SAFE_NO_PAD
+    .decode(part)
+    .map_err(|_| JWTError::Internal("The JWE provided is invalid".into()))
+}
+
+fn decryption_failed() -> JWTError {
+  JWTError::Internal("Unable to decrypt the JWE. Provide a valid key".into())
+}
+
+enum DecryptionKey {
+  Symmetric(Vec<u8>),
+  Rsa(Box<RsaPrivateKey>),
+  P256(p256::SecretKey),
+  P384(p384::SecretKey),
+}
+
+fn decryption_key(
+  alg: &KeyAlgorithm,
+  header: &JweHeader,
+  secret_string: &str,
+) -> JWTResult<DecryptionKey> {
+  let (secret, secret_type) = get_jwe_secret_from_file_or_input(alg, secret_string);
+  let secret = secret?;
+  match alg {
+    KeyAlgorithm::A128Kw | KeyAlgorithm::A192Kw | KeyAlgorithm::A256Kw | KeyAlgorithm::Dir => {
+      symmetric_key(alg, secret, secret_type, header.kid.as_deref())
+        .map(|(key, _)| DecryptionKey::Symmetric(key))
+    }
+    KeyAlgorithm::RsaOaep | KeyAlgorithm::RsaOaep256 => {
+      let key = match secret_type {
+        SecretType::Pem => {
+          let pem = String::from_utf8_lossy(&secret);
+          RsaPrivateKey::from_pkcs8_pem(&pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(&pem))
+            .ok()
+        }
+        SecretType::Der => RsaPrivateKey::from_pkcs8_der(&secret)
+          .or_else(|_| RsaPrivateKey::from_pkcs1_der(&secret))
+          .ok(),
+        SecretType::Jwks => rsa_key_from_jwk(&find_jwk(&secret, header.kid.as_deref(), "RSA")?),
+        _ => {
+          return Err(JWTError::Internal(format!(
+            "Invalid secret file type for {alg}"
+          )))
+        }
+      };
+      key.map(|k| DecryptionKey::Rsa(Box::new(k))).ok_or_else(|| {
+        JWTError::Internal("The secret provided isn't a valid RSA private key".into())
+      })
+    }
+    _ => {
+      let curve = header
+        .epk
+        .as_ref()
+        .and_then(|epk| epk.get("crv"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| JWTError::Internal("Missing `epk` in the JWE header".into()))?;
+      let d = match secret_type {
+        SecretType::Jwks => Some(jwk_param(
+          &find_jwk(&secret, header.kid.as_deref(), "EC")?,
+          "d",
+        )?),
+        _ => None,
+      };
+      let invalid_key =
+        || JWTError::Internal("The secret provided isn't a valid EC private key".into());
+      match curve {
+        "P-256" => match (&d, secret_type) {
+          (Some(d), _) => p256::SecretKey::from_slice(d).ok(),
+          (None, SecretType::Pem) => {
+            let pem = String::from_utf8_lossy(&secret);
+            p256::SecretKey::from_pkcs8_pem(&pem)
+              .ok()
+              .or_else(|| p256::SecretKey::from_sec1_pem(&pem).ok())
+          }
+          (None, _) => p256::SecretKey::from_pkcs8_der(&secret)
+            .ok()
+            .or_else(|| p256::SecretKey::from_sec1_der(&secret).ok()),
+        }
+        .map(DecryptionKey::P256)
+        .ok_or_else(invalid_key),
+        "P-384" => match (&d, secret_type) {
+          (Some(d), _) => p384::SecretKey::from_slice(d).ok(),
+          (None, SecretType::Pem) => {
+            let pem = String::from_utf8_lossy(&secret);
+            p384::SecretKey::from_pkcs8_pem(&pem)
+              .ok()
+              .or_else(|| p384::SecretKey::from_sec1_pem(&pem).ok())
+          }
+          (None, _) => p384::SecretKey::from_pkcs8_der(&secret)
+            .ok()
+            .or_else(|| p384::SecretKey::from_sec1_der(&secret).ok()),
+        }
+        .map(DecryptionKey::P384)
+        .ok_or_else(invalid_key),
+        _ => Err(JWTError::Internal(format!(
+          "Unsupported curve {curve:?} for {alg}, use P-256 or P-384"
+        ))),
+      }
+    }
+  }
+}
+
+/// shared key and the `kid` of its JWK
+fn symmetric_key(
+  alg: &KeyAlgorithm,
+  secret: Vec<u8>,
+  secret_type: SecretType,
+  kid: Option<&str>,
+) -> JWTResult<(Vec<u8>, Option<String>)> {
+  match secret_type {
+    SecretType::Plain => Ok((secret, None)),
+    SecretType::B64 => STANDARD
+      .decode(&secret)
+      .map(|key| (key, None))
+      .map_err(|_| JWTError::Internal("Invalid base64 secret".into())),
+    SecretType::Jwks => {
+      let jwk = find_jwk(&secret, kid, "oct")?;
+      Ok((jwk_param(&jwk, "k")?, jwk_kid(&jwk)))
+    }
+    _ => Err(JWTError::Internal(format!(
+      "Invalid secret file type for {alg}"
+    ))),
+  }
+}
+
+/// find the key by `kid`, or the first key of the given type when there is no `kid`. A single JWK
+/// is used as a JWKS with one key, like for signing
+fn find_jwk(secret: &[u8], kid: Option<&str>, kty: &str) -> JWTResult<Value> {
+  let jwks: Value = serde_json::from_slice(secret)
+    .map_err(|_| JWTError::Internal("Invalid jwks secret format".into()))?;
+  let keys = match jwks.get("keys") {
+    Some(keys) => keys
+      .as_array()
+      .ok_or_else(|| JWTError::Internal("Invalid jwks secret format".into()))?,
+    None if jwks.get("kty").is_some_and(Value::is_string) => std::slice::from_ref(&jwks),
+    None => return Err(JWTError::Internal("Invalid jwks secret format".into())),
+  };
+  let key = match kid {
+    Some(kid) => keys
+      .iter()
+      .find(|k| k.get("kid").and_then(Value::as_str) == Some(kid))
+      .ok_or_else(|| JWTError::Internal(format!("No jwk found for 'kid' {kid:?}")))?,
+    None => keys
+      .iter()
+      .find(|k| k.get("kty").and_then(Value::as_str) == Some(kty))
+      .ok_or_else(|| JWTError::Internal(format!("No {kty} jwk found in the jwks")))?,
+  };
+  Ok(key.clone())
+}
+
+fn jwk_kid(jwk: &Value) -> Option<String> {
+  jwk.get("kid").and_then(Value::as_str).map(String::from)
+}
+
+pub fn jwk_param(jwk: &Value, param: &str) -> JWTResult<Vec<u8>> {
+  jwk
+    .get(param)
+    .and_then(Value::as_str)
+    .and_then(|v| URL_SAFE_NO_PAD.decode(v).ok())
+    .ok_or_else(|| JWTError::Internal(format!("Missing or invalid `{param}` in the jwk")))
+}
+
+pub fn rsa_key_from_jwk(jwk: &Value) -> Option<RsaPrivateKey> {
+  let param = |name| {
+    jwk_param(jwk, name)
+      .ok()
+      .map(|v| BigUint::from_bytes_be(&v))
+  };
+  let primes = match (param("p"), param("q")) {
+    (Some(p), Some(q)) => vec![p, q],
+    _ => vec![],
+  };
+  RsaPrivateKey::from_components(param("n")?, param("e")?, param("d")?, primes).ok()
+}
+
+/// decrypt or derive the content encryption key (CEK)
+fn content_encryption_key(
+  alg: &KeyAlgorithm,
+  enc: &ContentEncryption,
+  header: &JweHeader,
+  key: DecryptionKey,
+  encrypted_key: &[u8],
+) -> JWTResult<Vec<u8>> {
+  let cek = match (alg, key) {
+    (KeyAlgorithm::Dir, DecryptionKey::Symmetric(key)) => key,
+    (KeyAlgorithm::RsaOaep, DecryptionKey::Rsa(key)) => key
+      .decrypt(Oaep::new::<Sha1>(), encrypted_key)
+      .map_err(|_| decryption_failed())?,
+    (KeyAlgorithm::RsaOaep256, DecryptionKey::Rsa(key)) => key
+      .decrypt(Oaep::new::<Sha256>(), encrypted_key)
+      .map_err(|_| decryption_failed())?,
+    (_, DecryptionKey::Symmetric(kek)) => unwrap_key(&kek, encrypted_key)?,
+    (_, key) => {
+      let shared_secret = ecdh_shared_secret(header, key)?;
+      match alg.key_wrap_len() {
+        // the derived key is used as the CEK directly
+        None => concat_kdf(&shared_secret, enc.name(), enc.key_len(), header)?,
+        Some(len) => {
+          let kek = concat_kdf(&shared_secret, alg.name(), len, header)?;
+          unwrap_key(&kek, encrypted_key)?
+        }
+      }
+    }
+  };
+  if cek.len() != enc.key_len() {
+    return Err(JWTError::Internal(format!(
+      "Invalid key length for {enc}, expected {} bytes",
+      enc.key_len()
+    )));
+  }
+  Ok(cek)
+}
+
+/// AES key unwrap, see RFC 3394
+fn unwrap_key(kek: &[u8], wrapped: &[u8]) -> JWTResult<Vec<u8>> {
+  let mut cek = vec![0u8; wrapped.len().saturating_sub(8)];
+  let unwrapped = match kek.len() {
+    16 => Kek::<Aes128>::try_from(kek).and_then(|k| k.unwrap(wrapped, &mut cek)),
+    24 => Kek::<Aes192>::try_from(kek).and_then(|k| k.unwrap(wrapped, &mut cek)),
+    32 => Kek::<Aes256>::try_from(kek).and_then(|k| k.unwrap(wrapped, &mut cek)),
+    _ => {
+      return Err(JWTError::Internal(
+        "Invalid key length for AES key wrap, expected 16, 24 or 32 bytes".into(),
+      ))
+    }
+  };
+  unwrapped.map_err(|_| decryption_failed())?;
+  Ok(cek)
+}
+
+fn ecdh_shared_secret(header: &JweHeader, key: DecryptionKey) -> JWTResult<Vec<u8>> {
+  let epk = header
+    .epk
+    .as_ref()
+    .ok_or_else(|| JWTError::Internal("Missing `epk` in the JWE header".into()))?;
+  let mut point = vec![0x04];
+  point.extend(jwk_param(epk, "x")?);
+  point.extend(jwk_param(epk, "y")?);
+  let invalid_epk = || JWTError::Internal("Invalid `epk` in the JWE header".into());
+  let shared_secret = match key {
+    DecryptionKey::P256(key) => {
+      let epk = p256::PublicKey::from_sec1_bytes(&point).map_err(|_| invalid_epk())?;
+      p256::ecdh::diffie_hellman(key.to_nonzero_scalar(), epk.as_affine())
+        .raw_secret_bytes()
+        .to_vec()
+    }
+    DecryptionKey::P384(key) => {
+      let epk = p384::PublicKey::from_sec1_bytes(&point).map_err(|_| invalid_epk())?;
+      p384::ecdh::diffie_hellman(key.to_nonzero_scalar(), epk.as_affine())
+        .raw_secret_bytes()
+        .to_vec()
+    }
+    _ => return Err(decryption_failed()),
+  };
+  Ok(shared_secret)
+}
+
+/// Concat KDF as used by ECDH-ES, see RFC 7518 section 4.6.2
+fn concat_kdf(
+  shared_secret: &[u8],
+  algorithm_id: &str,
+  key_len: usize,
+  header: &JweHeader,
+) -> JWTResult<Vec<u8>> {
+  let party_info = |info: &Option<String>| match info {
+    Some(info) => decode_part(info),
+    None => Ok(vec![]),
+  };
+  let mut other_info = vec![];
+  for field in [
+    algorithm_id.as_bytes().to_vec(),
+    party_info(&header.apu)?,
+    party_info(&header.apv)?,
+  ] {
+    other_info.extend((field.len() as u32).to_be_bytes());
+    other_info.extend(field);
+  }
+  other_info.extend(((key_len * 8) as u32).to_be_bytes());
+
+  let mut key = vec![0u8; key_len];
+  concat_kdf::derive_key_into::<Sha256>(shared_secret, &other_info, &mut key)
+    .map_err(|_| decryption_failed())?;
+  Ok(key)
+}
+
+fn decrypt_content(
+  enc: &ContentEncryption,
+  cek: &[u8],
+  iv: &[u8],
+  aad: &[u8],
+  ciphertext: &[u8],
+  tag: &[u8],
+) -> JWTResult<Vec<u8>> {
+  match enc {
+    ContentEncryption::A128Gcm => decrypt_gcm::<Aes128>(cek, iv, aad, ciphertext, tag),
+    ContentEncryption::A192Gcm => decrypt_gcm::<Aes192>(cek, iv, aad, ciphertext, tag),
+    ContentEncryption::A256Gcm => decrypt_gcm::<Aes256>(cek, iv, aad, ciphertext, tag),
+    ContentEncryption::A128CbcHs256 => {
+      decrypt_cbc_hmac::<Aes128, Hmac<Sha256>>(cek, iv, aad, ciphertext, tag)
+    }
+    ContentEncryption::A192CbcHs384 => {
+      decrypt_cbc_hmac::<Aes192, Hmac<Sha384>>(cek, iv, aad, ciphertext, tag)
+    }
+    ContentEncryption::A256CbcHs512 => {
+      decrypt_cbc_hmac::<Aes256, Hmac<Sha512>>(cek, iv, aad, ciphertext, tag)
+    }
+  }
+}
+
+fn decrypt_gcm<C>(
+  cek: &[u8],
+  iv: &[u8],
+  aad: &[u8],
+  ciphertext: &[u8],
+  tag: &[u8],
+) -> JWTResult<Vec<u8>>
+where
+  AesGcm<C, U12>: KeyInit + Aead,
+{
+  if iv.len() != 12 {
+    return Err(JWTError::Internal(
+      "Invalid JWE initialization vector, expected 12 bytes".into(),
+    ));
+  }
+  let cipher = AesGcm::<C, U12>::new_from_slice(cek).map_err(|_| decryption_failed())?;
+  let msg = [ciphertext, tag].concat();
+  cipher
+    .decrypt(iv.into(), AeadPayload { msg: &msg, aad })
+    .map_err(|_| decryption_failed())
+}
+
+/// AES-CBC with HMAC-SHA2, see RFC 7518 section 5.2
+fn decrypt_cbc_hmac<C, M>(
+  cek: &[u8],
+  iv: &[u8],
+  aad: &[u8],
+  ciphertext: &[u8],
+  tag: &[u8],
+) -> JWTResult<Vec<u8>>
+where
+  C: BlockCipher + BlockDecryptMut + aes::cipher::KeyInit,
+  M: Mac + hmac::digest::KeyInit,
+{
+  let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+  if tag.len() != mac_key.len() {
+    return Err(decryption_failed());
+  }
+  cbc_mac::<M>(mac_key, aad, iv, ciphertext)?
+    .verify_truncated_left(tag)
+    .map_err(|_| decryption_failed())?;
+
+  cbc::Decryptor::<C>::new_from_slices(enc_key, iv)
+    .map_err(|_| decryption_failed())?
+    .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+    .map_err(|_| decryption_failed())
+}
+
+fn cbc_mac<M>(mac_key: &[u8], aad: &[u8], iv: &[u8], ciphertext: &[u8]) -> JWTResult<M>
+where
+  M: Mac + hmac::digest::KeyInit,
+{
+  let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(mac_key)
+    .map_err(|_| JWTError::Internal("Invalid MAC key".into()))?;
+  mac.update(aad);
+  mac.update(iv);
+  mac.update(ciphertext);
+  mac.update(&((aad.len() * 8) as u64).to_be_bytes());
+  Ok(mac)
+}
+
+/// encryption stage of the encoder, e.g. `alg=RSA-OAEP-256 enc=A256GCM nested`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptionSettings {
+  pub alg: KeyAlgorithm,
+  pub enc: ContentEncryption,
+  /// selects the key of a JWKS and is added to the header
+  pub kid: Option<String>,
+  /// sign the token first and encrypt the JWS, otherwise the claims are encrypted directly
+  pub nested: bool,
+}
+
+impl FromStr for EncryptionSettings {
+  type Err = JWTError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (mut alg, mut enc, mut kid, mut nested) = (None, None, None, false);
+    for rule in s.split_whitespace() {
+      match rule.split_once('=') {
+        Some(("alg", value)) => alg = Some(value.parse()?),
+        Some(("enc", value)) => enc = Some(value.parse()?),
+        Some(("kid", value)) => kid = Some(value.to_string()),
+        None if rule == "nested" => nested = true,
+        _ => {
+          return Err(JWTError::Internal(format!(
+            "Unknown encryption setting {rule:?}. Supported settings: alg=, enc=, kid=, nested"
+          )))
+        }
+      }
+    }
+    Ok(EncryptionSettings {
+      alg: alg.ok_or_else(|| JWTError::Internal("Missing `alg=` encryption setting".into()))?,
+      enc: enc.ok_or_else(|| JWTError::Internal("Missing `enc=` encryption setting".into()))?,
+      kid,
+      nested,
+    })
+  }
+}
+
+impl fmt::Display for EncryptionSettings {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "alg={} enc={}", self.alg, self.enc)?;
+    if let Some(kid) = &self.kid {
+      write!(f, " kid={kid}")?;
+    }
+    if self.nested {
+      write!(f, " nested")?;
+    }
+    Ok(())
+  }
+}
+
+/// encrypt the plaintext for the recipient key, returns the JWE in compact serialization
+pub fn encrypt_jwe(
+  plaintext: &[u8],
+  settings: &EncryptionSettings,
+  secret_string: &str,
+) -> JWTResult<String> {
+  if secret_string.is_empty() {
+    return Err(JWTError::Internal(
+      "Provide a key to encrypt the JWE".into(),
+    ));
+  }
+  let (alg, enc) = (&settings.alg, &settings.enc);
+  let (key, jwk_kid) = encryption_key(alg, settings.kid.as_deref(), secret_string)?;
+  let mut header = JweHeader {
+    alg: alg.name().into(),
+    enc: enc.name().into(),
+    typ: None,
+    cty: settings.nested.then(|| "JWT".into()),
+    kid: settings.kid.clone().or(jwk_kid),
+    zip: None,
+    epk: None,
+    apu: None,
+    apv: None,
+    other: BTreeMap::new(),
+  };
+
+  let mut cek = vec![0u8; enc.key_len()];
+  OsRng.fill_bytes(&mut cek);
+  let (cek, encrypted_key) = match (alg, key) {
+    (KeyAlgorithm::Dir, EncryptionKey::Symmetric(key)) => (key, vec![]),
+    (KeyAlgorithm::RsaOaep, EncryptionKey::Rsa(key)) => {
+      let encrypted_key = key
+        .encrypt(&mut OsRng, Oaep::new::<Sha1>(), &cek)
+        .map_err(|e| JWTError::Internal(format!("Unable to encrypt the key: {e}")))?;
+      (cek, encrypted_key)
+    }
+    (KeyAlgorithm::RsaOaep256, EncryptionKey::Rsa(key)) => {
+      let encrypted_key = key
+        .encrypt(&mut OsRng, Oaep::new::<Sha256>(), &cek)
+        .map_err(|e| JWTError::Internal(format!("Unable to encrypt the key: {e}")))?;
+      (cek, encrypted_key)
+    }
+    (_, EncryptionKey::Symmetric(kek)) => {
+      let encrypted_key = wrap_key(&kek, &cek)?;
+      (cek, encrypted_key)
+    }
+    (_, key) => {
+      let (epk, shared_secret) = ecdh_ephemeral_secret(key)?;
+      header.epk = Some(epk);
+      match alg.key_wrap_len() {
+        None => (
+          concat_kdf(&shared_secret, enc.name(), enc.key_len(), &header)?,
+          vec![],
+        ),
+        Some(len) => {
+          let kek = concat_kdf(&shared_secret, alg.name(), len, &header)?;
+          let encrypted_key = wrap_key(&kek, &cek)?;
+          (cek, encrypted_key)
+        }
+      }
+    }
+  };
+  if cek.len() != enc.key_len() {
+    return Err(JWTError::Internal(format!(
+      "Invalid key length for {enc}, expected {} bytes",
+      enc.key_len()
+    )));
+  }
+
+  let protected = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?);
+  let (iv, ciphertext, tag) = encrypt_content(enc, &cek, protected.as_bytes(), plaintext)?;
+  Ok(
+    [
+      protected,
+      URL_SAFE_NO_PAD.encode(encrypted_key),
+      URL_SAFE_NO_PAD.encode(iv),
+      URL_SAFE_NO_PAD.encode(ciphertext),
+      URL_SAFE_NO_PAD.encode(tag),
+    ]
+    .join("."),
+  )
+}
+
+enum EncryptionKey {
+  Symmetric(Vec<u8>),
+  Rsa(Box<RsaPublicKey>),
+  P256(p256::PublicKey),
+  P384(p384::PublicKey),
+}
+
+/// recipient key and the `kid` of its JWK. Public keys can also be read from a private key
+fn encryption_key(
+  alg: &KeyAlgorithm,
+  kid: Option<&str>,
+  secret_string: &str,
+) -> JWTResult<(EncryptionKey, Option<String>)> {
+  let (secret, secret_type) = get_jwe_secret_from_file_or_input(alg, secret_string);
+  let secret = secret?;
+  if alg.is_symmetric() {
+    return symmetric_key(alg, secret, secret_type, kid)
+      .map(|(key, kid)| (EncryptionKey::Symmetric(key), kid));
+  }
+  let jwk = match secret_type {
+    SecretType::Jwks => Some(find_jwk(
+      &secret,
+      kid,
+      if alg.is_rsa() { "RSA" } else { "EC" },
+    )?),
+    SecretType::Pem | SecretType::Der => None,
+    _ => {
+      return Err(JWTError::Internal(format!(
+        "Invalid secret file type for {alg}"
+      )))
+    }
+  };
+  let pem = String::from_utf8_lossy(&secret);
+  let key = if alg.is_rsa() {
+    match &jwk {
+      Some(jwk) => RsaPublicKey::new(
+        BigUint::from_bytes_be(&jwk_param(jwk, "n")?),
+        BigUint::from_bytes_be(&jwk_param(jwk, "e")?),
+      )
+      .ok(),
+      None if secret_type == SecretType::Pem => RsaPublicKey::from_public_key_pem(&pem)
+        .or_else(|_| RsaPublicKey::from_pkcs1_pem(&pem))
+        .ok()
+        .or_else(|| {
+          RsaPrivateKey::from_pkcs8_pem(&pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(&pem))
+            .ok()
+            .map(|k| k.to_public_key())
+        }),
+      None => RsaPublicKey::from_public_key_der(&secret)
+        .or_else(|_| RsaPublicKey::from_pkcs1_der(&secret))
+        .ok()
+        .or_else(|| {
+          RsaPrivateKey::from_pkcs8_der(&secret)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_der(&secret))
+            .ok()
+            .map(|k| k.to_public_key())
+        }),
+    }
+    .map(|k| EncryptionKey::Rsa(Box::new(k)))
+  } else {
+    match &jwk {
+      Some(jwk) => {
+        let mut point = vec![0x04];
+        point.extend(jwk_param(jwk, "x")?);
+        point.extend(jwk_param(jwk, "y")?);
+        match jwk.get("crv").and_then(Value::as_str) {
+          Some("P-256") => p256::PublicKey::from_sec1_bytes(&point)
+            .ok()
+            .map(EncryptionKey::P256),
+          Some("P-384") => p384::PublicKey::from_sec1_bytes(&point)
+            .ok()
+            .map(EncryptionKey::P384),
+          crv => {
+            return Err(JWTError::Internal(format!(
+              "Unsupported curve {crv:?} for {alg}, use P-256 or P-384"
+            )))
+          }
+        }
+      }
+      None if secret_type == SecretType::Pem => ec_public_key_from_pem(&pem),
+      None => ec_public_key_from_der(&secret),
+    }
+  };
+  key
+    .map(|key| (key, jwk.as_ref().and_then(jwk_kid)))
+    .ok_or_else(|| JWTError::Internal(format!("The secret provided isn't a valid key for {alg}")))
+}
+
+fn ec_public_key_from_pem(pem: &str) -> Option<EncryptionKey> {
+  p256::PublicKey::from_public_key_pem(pem)
+    .ok()
+    .or_else(|| {
+      p256::SecretKey::from_pkcs8_pem(pem)
+        .ok()
+        .map(|k| k.public_key())
+    })
+    .map(EncryptionKey::P256)
+    .or_else(|| {
+      p384::PublicKey::from_public_key_pem(pem)
+        .ok()
+        .or_else(|| {
+          p384::SecretKey::from_pkcs8_pem(pem)
+            .ok()
+            .map(|k| k.public_key())
+        })
+        .map(EncryptionKey::P384)
+    })
+}
+
+fn ec_public_key_from_der(der: &[u8]) -> Option<EncryptionKey> {
+  p256::PublicKey::from_public_key_der(der)
+    .ok()
+    .or_else(|| {
+      p256::SecretKey::from_pkcs8_der(der)
+        .ok()
+        .map(|k| k.public_key())
+    })
+    .map(EncryptionKey::P256)
+    .or_else(|| {
+      p384::PublicKey::from_public_key_der(der)
+        .ok()
+        .or_else(|| {
+          p384::SecretKey::from_pkcs8_der(der)
+            .ok()
+            .map(|k| k.public_key())
+        })
+        .map(EncryptionKey::P384)
+    })
+}
+
+/// AES key wrap, see RFC 3394
+fn wrap_key(kek: &[u8], cek: &[u8]) -> JWTResult<Vec<u8>> {
+  let mut wrapped = vec![0u8; cek.len() + 8];
+  let result = match kek.len() {
+    16 => Kek::<Aes128>::try_from(kek).and_then(|k| k.wrap(cek, &mut wrapped)),
+    24 => Kek::<Aes192>::try_from(kek).and_then(|k| k.wrap(cek, &mut wrapped)),
+    32 => Kek::<Aes256>::try_from(kek).and_then(|k| k.wrap(cek, &mut wrapped)),
+    _ => {
+      return Err(JWTError::Internal(
+        "Invalid key length for AES key wrap, expected 16, 24 or 32 bytes".into(),
+      ))
+    }
+  };
+  result.map_err(|e| JWTError::Internal(format!("Unable to wrap the key: {e}")))?;
+  Ok(wrapped)
+}
+
+/// generates the ephemeral key, returns its public JWK and the shared secret
+fn ecdh_ephemeral_secret(key: EncryptionKey) -> JWTResult<(Value, Vec<u8>)> {
+  let (crv, point, shared_secret) = match key {
+    EncryptionKey::P256(key) => {
+      let ephemeral = p256::ecdh::EphemeralSecret::random(&mut OsRng);
+      let point = ephemeral.public_key().to_encoded_point(false);
+      let shared_secret = ephemeral.diffie_hellman(&key);
+      (
+        "P-256",
+        point.as_bytes().to_vec(),
+        shared_secret.raw_secret_bytes().to_vec(),
+      )
+    }
+    EncryptionKey::P384(key) => {
+      let ephemeral = p384::ecdh::EphemeralSecret::random(&mut OsRng);
+      let point = ephemeral.public_key().to_encoded_point(false);
+      let shared_secret = ephemeral.diffie_hellman(&key);
+      (
+        "P-384",
+        point.as_bytes().to_vec(),
+        shared_secret.raw_secret_bytes().to_vec(),
+      )
+    }
+    _ => return Err(JWTError::Internal("Invalid key for ECDH-ES".into())),
+  };
+  // uncompressed point: 0x04 || x || y
+  let (x, y) = point[1..].split_at((point.len() - 1) / 2);
+  let epk = json!({
+    "kty": "EC",
+    "crv": crv,
+    "x": URL_SAFE_NO_PAD.encode(x),
+    "y": URL_SAFE_NO_PAD.encode(y),
+  });
+  Ok((epk, shared_secret))
+}
+
+/// returns the initialization vector, ciphertext and authentication tag
+fn encrypt_content(
+  enc: &ContentEncryption,
+  cek: &[u8],
+  aad: &[u8],
+  plaintext: &[u8],
+) -> JWTResult<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+  match enc {
+    ContentEncryption::A128Gcm => encrypt_gcm::<Aes128>(cek, aad, plaintext),
+    ContentEncryption::A192Gcm => encrypt_gcm::<Aes192>(cek, aad, plaintext),
+    ContentEncryption::A256Gcm => encrypt_gcm::<Aes256>(cek, aad, plaintext),
+    ContentEncryption::A128CbcHs256 => {
+      encrypt_cbc_hmac::<Aes128, Hmac<Sha256>>(cek, aad, plaintext)
+    }
+    ContentEncryption::A192CbcHs384 => {
+      encrypt_cbc_hmac::<Aes192, Hmac<Sha384>>(cek, aad, plaintext)
+    }
+    ContentEncryption::A256CbcHs512 => {
+      encrypt_cbc_hmac::<Aes256, Hmac<Sha512>>(cek, aad, plaintext)
+    }
+  }
+}
+
+fn encrypt_gcm<C>(
+  cek: &[u8],
+  aad: &[u8],
+  plaintext: &[u8],
+) -> JWTResult<(Vec<u8>, Vec<u8>, Vec<u8>)>
+where
+  AesGcm<C, U12>: KeyInit + Aead,
+{
+  let mut iv = vec![0u8; 12];
+  OsRng.fill_bytes(&mut iv);
+  let cipher = AesGcm::<C, U12>::new_from_slice(cek)
+    .map_err(|_| JWTError::Internal("Invalid content encryption key".into()))?;
+  let mut ciphertext = cipher
+    .encrypt(
+      iv.as_slice().into(),
+      AeadPayload {
+        msg: plaintext,
+        aad,
+      },
+    )
+    .map_err(|_| JWTError::Internal("Unable to encrypt the JWE".into()))?;
+  let tag = ciphertext.split_off(ciphertext.len() - 16);
+  Ok((iv, ciphertext, tag))
+}
+
+fn encrypt_cbc_hmac<C, M>(
+  cek: &[u8],
+  aad: &[u8],
+  plaintext: &[u8],
+) -> JWTResult<(Vec<u8>, Vec<u8>, Vec<u8>)>
+where
+  C: BlockCipher + BlockEncryptMut + aes::cipher::KeyInit,
+  M: Mac + hmac::digest::KeyInit,
+{
+  let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+  let mut iv = vec![0u8; 16];
+  OsRng.fill_bytes(&mut iv);
+  let ciphertext = cbc::Encryptor::<C>::new_from_slices(enc_key, &iv)
+    .map_err(|_| JWTError::Internal("Invalid content encryption key".into()))?
+    .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+  let tag = cbc_mac::<M>(mac_key, aad, &iv, &ciphertext)?
+    .finalize()
+    .into_bytes()[..mac_key.len()]
+    .to_vec();
+  Ok((iv, ciphertext, tag))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    super::keygen::{KeyPair, KeyType},
+    *,
+  };
+
+  const CLAIMS: &str = r#"{"sub":"1234567890","name":"John Doe","iat":1516239022}"#;
+
+  #[test]
+  fn test_is_jwe() {
+    assert!(is_jwe("a.b.c.d.e"));
+    assert!(!is_jwe("eyJhbGciOiJIUzI1NiJ9.e30.sig"));
+  }
+
+  #[test]
+  fn test_decode_jwe_header_without_key() {
+    let header = decode_jwe_header("eyJhbGciOiJFQ0RILUVTK0ExMjhLVyIsImVuYyI6IkExOTJHQ00iLCJlcGsiOnsia3R5IjoiRUMiLCJjcnYiOiJQLTM4NCIsIngiOiJwQm5hRkI1bGdoTTJoMjNwNlBIaDZmUEFBN1lab1E4b29jd3cyVzNfcWxTdWVwY25pbEx6ejc1Qk11UVQ3eXZmIiwieSI6IjdoSEVaVmN3b0JRbEMySV9BeDNOSUNtd20yNnhYR1NsekdyeXpQa3dFOHZjWlJkRFpVOFYwWEkwT3pvS29PVnEifX0.p0mU2yS2QAwXHBftEWVAFZzYuRunq4KZNh1EB8xUjhI.Lo78aPBDBTI6IrEZ.wbrLHL0spWydzyeB5pq22wpbhQKnNKCsGAthgr5vuSbVbZHQFQzaX5wNHGr74zO6LOVG_vV7KA.WHpzSwvvCvVy8AA9sdeNeQ").unwrap();
+
+    assert_eq!(header.alg, "ECDH-ES+A128KW");
+    assert_eq!(header.enc, "A192GCM");
+    assert_eq!(header.epk.unwrap()["crv"], "P-384");
+    assert_eq!(
+      decrypt_jwe("eyJhbGciOiJFQ0RILUVTK0ExMjhLVyIsImVuYyI6IkExOTJHQ00iLCJlcGsiOnsia3R5IjoiRUMiLCJjcnYiOiJQLTM4NCIsIngiOiJwQm5hRkI1bGdoTTJoMjNwNlBIaDZmUEFBN1lab1E4b29jd3cyVzNfcWxTdWVwY25pbEx6ejc1Qk11UVQ3eXZmIiwieSI6IjdoSEVaVmN3b0JRbEMySV9BeDNOSUNtd20yNnhYR1NsekdyeXpQa3dFOHZjWlJkRFpVOFYwWEkwT3pvS29PVnEifX0.p0mU2yS2QAwXHBftEWVAFZzYuRunq4KZNh1EB8xUjhI.Lo78aPBDBTI6IrEZ.wbrLHL0spWydzyeB5pq22wpbhQKnNKCsGAthgr5vuSbVbZHQFQzaX5wNHGr74zO6LOVG_vV7KA.WHpzSwvvCvVy8AA9sdeNeQ", "").unwrap_err(),
+      JWTError::Internal("Provide a key to decrypt the JWE".into())
+    );
+  }
+
+  #[test]
+  fn test_decrypt_a128kw_rfc7516_example() {
+    // RFC 7516 appendix A.3
+    let (header, plaintext) = decrypt_jwe(
+      "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.AxY8DCtDaGlsbGljb3RoZQ.KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.U0m_YmjN04DJvceFICbCVQ",
+      "b64:GawgguFyGrWKav7AX4VKUg==",
+    )
+    .unwrap();
+
+    assert_eq!(header.alg, "A128KW");
+    assert_eq!(plaintext, b"Live long and prosper.");
+    assert_eq!(
+      decrypt_jwe(
+        "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.AxY8DCtDaGlsbGljb3RoZQ.KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.U0m_YmjN04DJvceFICbCVQ",
+        "b64:AAAAAAAAAAAAAAAAAAAAAA==",
+      )
+      .unwrap_err(),
+      JWTError::Internal("Unable to decrypt the JWE. Provide a valid key".into())
+    );
+  }
+
+  #[test]
+  fn test_decrypt_rsa_oaep() {
+    let (_, plaintext) = decrypt_jwe(
+      "eyJhbGciOiJSU0EtT0FFUC0yNTYiLCJlbmMiOiJBMjU2R0NNIn0.NGPkdP6wv2PkzgqNVf_uYkIIAReuwQQ70Dr8tqf-tBxnCB1hIuSZ3jxnUA6ovS-EPBa08nchOC3s8BeFtrH2AEtTdcfm21vtZKdCue1KcAqYNbz9jodAbnnWmgLj1Byg7O_Ka-Ay_RrPxUEzZIrwMsIuDhvWh25C8uI9VEb_n_VOoAubmGxvgpJhQvPFIVc30yjSe4iCIVtSjh--niWbG768iwed9KS6vrO8Q_Hig1pFFxcJqrzE9oJR-wV60LiVhoyXmX5iU2uu7FJbgQjEuRsl_2gRRJ6SSQ2cUNExEdmsLDKF772mbBOJ9A_76dxCiVMyDtjaJO8KzFx_vJjH8g.P3tURW4Vw5ryrnxP.f5ZC_RgqvY4KgB2MrlpmNaKAzBYBR5bA_NcoUrMDsIFoRJYB-86UoHJzqQBMRWWSjQJv9RN0IA.dwjlKnSKuSfH6BQkWv0yfg",
+      "@./test_data/test_rsa_private_key.pem",
+    )
+    .unwrap();
+    assert_eq!(plaintext, CLAIMS.as_bytes());
+
+    let (_, plaintext) = decrypt_jwe(
+      "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkExMjhDQkMtSFMyNTYifQ.ryT9iyi15dK9PwaYMSuDhbMNxhz4MWPVCFzxi-2fb-N4m0CgKmznxNBfMD1iPZzy_9ShXbC9DMjsrh9alTGqkMStsIl9nSEat5eHpSvVuURiF2nIlqjtk-QFpKLW122vg-5ZzmNzGcvjVEtCljcbZ_uDs6Blf9g8ic17eKaShlYUVOi6mDPA1neagUaVACB52XJh22WorSnCbt9hplf_Pb_EemxbHbzrD5aas0VQPy_3DLaaKNv0LTuKKh6u6tFS8s80Gikn5jbHPAt3Hd0bfkMzaQCt1dcbtTyJ25ITIoYORKg0dZCwV0IXspWP5brqe30iYWUYMMtO_Ts9hq4ABw.vjmGnoLnI5LVjOntvakQUQ.wMpO7T13xy6kqmtgBh5dwEf5DkhmMjGVAcIF7uYyCQu9ANqCp8wVyet2e3AoGGZktCKkfeqqHaOKUbhamWN2fQ.uBqCk7ZKlnVL0SGYWwN8Ng",
+      "@./test_data/test_rsa_private_key.pem",
+    )
+    .unwrap();
+    assert_eq!(plaintext, CLAIMS.as_bytes());
+  }
+
+  #[test]
+  fn test_decrypt_ecdh_es() {
+    let (_, plaintext) = decrypt_jwe(
+      "eyJhbGciOiJFQ0RILUVTK0ExMjhLVyIsImVuYyI6IkExOTJHQ00iLCJlcGsiOnsia3R5IjoiRUMiLCJjcnYiOiJQLTM4NCIsIngiOiJwQm5hRkI1bGdoTTJoMjNwNlBIaDZmUEFBN1lab1E4b29jd3cyVzNfcWxTdWVwY25pbEx6ejc1Qk11UVQ3eXZmIiwieSI6IjdoSEVaVmN3b0JRbEMySV9BeDNOSUNtd20yNnhYR1NsekdyeXpQa3dFOHZjWlJkRFpVOFYwWEkwT3pvS29PVnEifX0.p0mU2yS2QAwXHBftEWVAFZzYuRunq4KZNh1EB8xUjhI.Lo78aPBDBTI6IrEZ.wbrLHL0spWydzyeB5pq22wpbhQKnNKCsGAthgr5vuSbVbZHQFQzaX5wNHGr74zO6LOVG_vV7KA.WHpzSwvvCvVy8AA9sdeNeQ",
+      "@./test_data/test_ecdsa_private_key.pem",
+    )
+    .unwrap();
+    assert_eq!(plaintext, CLAIMS.as_bytes());
+
+    let (_, plaintext) = decrypt_jwe(
+      "eyJhbGciOiJFQ0RILUVTIiwiZW5jIjoiQTI1NkNCQy1IUzUxMiIsImVwayI6eyJrdHkiOiJFQyIsImNydiI6IlAtMzg0IiwieCI6Img0T2xkUmx4aWdXWU5oWEFZXzZPMGptcDdUendUeWVkYllJWWVSVWFLaTYxc1J5Rjg1anBReWJhMUV1a1BkaVciLCJ5IjoiMTZVTlN3MzhXdEpwbXNuNE9MRkNJV21XeVJnQ2k1S3NDY1l4NmJZaE9HeGdONUNTaThZZmctdnlyaWZiOENOciJ9fQ..B-bggpn6K578dC30xYIWew.XG9Kri3VU_Xr8uchtS19kMU_LtTNeuKCmTA8-QCCBWV1uIpHT6PflMwZTYLhLARKCOE19WOaZ12BbW95s8furA.HWBsheDBlN1EHV0xHJrxq0D9MrY-djOyjN4SS0pL2-U",
+      "@./test_data/test_ecdsa_private_key.pem",
+    )
+    .unwrap();
+    assert_eq!(plaintext, CLAIMS.as_bytes());
+  }
+
+  #[test]
+  fn test_decrypt_dir() {
+    let token = "eyJhbGciOiJkaXIiLCJlbmMiOiJBMTI4R0NNIn0..z6x9yUEotkUeld1a.Eg3d3eWqV0QyIS83EV2yBe0fSZbV8bWxK4APoMmqvZN0DRJnh2WaUJNMAZyYPCzGEDovHoSyVg.DfSMZHSTLld-jlB_KbU_Sw";
+    let (_, plaintext) = decrypt_jwe(token, "0123456789abcdef").unwrap();
+    assert_eq!(plaintext, CLAIMS.as_bytes());
+
+    assert_eq!(
+      decrypt_jwe(token, "too-short").unwrap_err(),
+      JWTError::Internal("Invalid key length for A128GCM, expected 16 bytes".into())
+    );
+  }
+
+  #[test]
+  fn test_encryption_settings() {
+    let settings: EncryptionSettings = "alg=RSA-OAEP-256 enc=A256GCM kid=enc nested"
+      .parse()
+      .unwrap();
+    assert_eq!(settings.alg, KeyAlgorithm::RsaOaep256);
+    assert_eq!(settings.enc, ContentEncryption::A256Gcm);
+    assert_eq!(settings.kid.as_deref(), Some("enc"));
+    assert!(settings.nested);
+    assert_eq!(
+      settings.to_string(),
+      "alg=RSA-OAEP-256 enc=A256GCM kid=enc nested"
+    );
+
+    assert_eq!(
+      "alg=dir".parse::<EncryptionSettings>().unwrap_err(),
+      JWTError::Internal("Missing `enc=` encryption setting".into())
+    );
+    assert_eq!(
+      "alg=dir enc=A128GCM zip=DEF"
+        .parse::<EncryptionSettings>()
+        .unwrap_err(),
+      JWTError::Internal(
+        "Unknown encryption setting \"zip=DEF\". Supported settings: alg=, enc=, kid=, nested"
+          .into()
+      )
+    );
+  }
+
+  #[test]
+  fn test_encrypt_round_trip() {
+    let cases = [
+      (
+        "alg=RSA-OAEP-256 enc=A256GCM",
+        "@./test_data/test_rsa_public_key.pem",
+        "@./test_data/test_rsa_private_key.pem",
+      ),
+      (
+        "alg=RSA-OAEP enc=A128CBC-HS256",
+        "@./test_data/test_rsa_private_key.pem",
+        "@./test_data/test_rsa_private_key.pem",
+      ),
+      (
+        "alg=ECDH-ES enc=A256CBC-HS512",
+        "@./test_data/test_ecdsa_public_key.pem",
+        "@./test_data/test_ecdsa_private_key.pem",
+      ),
+      (
+        "alg=ECDH-ES+A256KW enc=A192GCM",
+        "@./test_data/test_ecdsa_public_jwks.json",
+        "@./test_data/test_ecdsa_private_key.pk8",
+      ),
+      (
+        "alg=A128KW enc=A192CBC-HS384",
+        "b64:GawgguFyGrWKav7AX4VKUg==",
+        "b64:GawgguFyGrWKav7AX4VKUg==",
+      ),
+      (
+        "alg=dir enc=A128GCM",
+        "0123456789abcdef",
+        "0123456789abcdef",
+      ),
+    ];
+    for (settings, encryption_key, decryption_key) in cases {
+      let settings: EncryptionSettings = settings.parse().unwrap();
+      let token = encrypt_jwe(CLAIMS.as_bytes(), &settings, encryption_key).unwrap();
+      let (header, plaintext) = decrypt_jwe(&token, decryption_key).unwrap();
+
+      assert_eq!(header.alg, settings.alg.name());
+      assert_eq!(header.enc, settings.enc.name());
+      assert_eq!(plaintext, CLAIMS.as_bytes(), "{settings}");
+    }
+  }
+
+  #[test]
+  fn test_encrypt_with_single_jwk() {
+    let ec = KeyPair::generate(KeyType::P256, Some("ec-1".into())).unwrap();
+    let oct = KeyPair::generate(KeyType::Hs256, None).unwrap();
+    let cases = [
+      (
+        "alg=ECDH-ES+A128KW enc=A128GCM",
+        ec.public_jwk(),
+        ec.private_jwk(),
+      ),
+      (
+        "alg=A256KW enc=A256GCM",
+        oct.private_jwk(),
+        oct.private_jwk(),
+      ),
+    ];
+    for (settings, encryption_key, decryption_key) in cases {
+      let settings: EncryptionSettings = settings.parse().unwrap();
+      let token = encrypt_jwe(CLAIMS.as_bytes(), &settings, &encryption_key.to_string()).unwrap();
+      let (_, plaintext) = decrypt_jwe(&token, &decryption_key.to_string()).unwrap();
+      assert_eq!(plaintext, CLAIMS.as_bytes(), "{settings}");
+    }
+  }
+
+  #[test]
+  fn test_encrypt_with_invalid_key() {
+    let settings: EncryptionSettings = "alg=dir enc=A256GCM".parse().unwrap();
+    assert_eq!(
+      encrypt_jwe(CLAIMS.as_bytes(), &settings, "").unwrap_err(),
+      JWTError::Internal("Provide a key to encrypt the JWE".into())
+    );
+    assert_eq!(
+      encrypt_jwe(CLAIMS.as_bytes(), &settings, "0123456789abcdef").unwrap_err(),
+      JWTError::Internal("Invalid key length for A256GCM, expected 32 bytes".into())
+    );
+  }
+}
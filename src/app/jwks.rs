@@ -0,0 +1,544 @@
+use std::{
+  collections::BTreeMap,
+  fs,
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+  },
+  thread,
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::utils::{JWTError, JWTResult};
+
+const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+/// used when the server doesn't send `Cache-Control: max-age`
+const DEFAULT_MAX_AGE: u64 = 300;
+/// the TUI decodes on every tick, so the cache is never revalidated more often than this
+const MIN_MAX_AGE: u64 = 60;
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// seconds before a failed fetch is retried, doubled on every failure
+const MIN_BACKOFF: u64 = 10;
+const MAX_BACKOFF: u64 = 300;
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+static BACKGROUND: AtomicBool = AtomicBool::new(false);
+/// fetches started by `request_jwks`, finished ones are only kept when they failed
+static FETCHES: Mutex<BTreeMap<String, Fetch>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone)]
+enum Fetch {
+  Pending,
+  Failed {
+    error: JWTError,
+    retry_at: u64,
+    backoff: u64,
+  },
+}
+
+/// only use cached JWKS, never fetch them
+pub fn set_offline(offline: bool) {
+  OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// fetch JWKS on a background thread with `request_jwks` instead of blocking, used by the TUI
+pub fn set_background(background: bool) {
+  BACKGROUND.store(background, Ordering::Relaxed);
+}
+
+/// whether the secret is an issuer, discovery document or `jwks_uri` URL
+pub fn is_jwks_url(secret_string: &str) -> bool {
+  secret_string.starts_with("https://") || secret_string.starts_with("http://")
+}
+
+/// fetch the JWKS of the URL, using the cache in the user cache directory.
+/// in background mode only the cache and the state of the fetch started by `request_jwks` are used
+pub fn fetch_jwks(url: &str) -> JWTResult<Vec<u8>> {
+  check_scheme(url)?;
+  let cache = default_cache()?;
+  match BACKGROUND.load(Ordering::Relaxed) && !cache.offline {
+    true => cache.lookup(url),
+    false => cache.fetch(url),
+  }
+}
+
+/// fetch the JWKS of the URL on a background thread, unless it's cached, being fetched, or failed recently
+pub fn request_jwks(url: &str) {
+  if !BACKGROUND.load(Ordering::Relaxed) || !is_jwks_url(url) || check_scheme(url).is_err() {
+    return;
+  }
+  if let Ok(cache) = default_cache() {
+    cache.request(url);
+  }
+}
+
+fn default_cache() -> JWTResult<JwksCache> {
+  let dir = dirs::cache_dir()
+    .map(|dir| dir.join("jwtui").join("jwks"))
+    .ok_or_else(|| JWTError::Internal("Unable to find the cache directory".into()))?;
+  Ok(JwksCache::new(dir, OFFLINE.load(Ordering::Relaxed)))
+}
+
+/// keys fetched over plain http can be replaced on the way, which is only acceptable for local issuers
+fn check_scheme(url: &str) -> JWTResult<()> {
+  if url.starts_with("https://") {
+    return Ok(());
+  }
+  let Some(rest) = url.strip_prefix("http://") else {
+    return Err(JWTError::Internal(format!(
+      "Refusing to fetch keys from {url}, use https"
+    )));
+  };
+  let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+  let host = match authority.strip_prefix('[') {
+    Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+    None => authority.split(':').next().unwrap_or_default(),
+  };
+  match host {
+    "localhost" | "127.0.0.1" | "::1" => Ok(()),
+    _ => Err(JWTError::Internal(format!(
+      "Refusing to fetch keys over plain http from {host}, use https"
+    ))),
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+  /// the resolved `jwks_uri`
+  jwks_uri: String,
+  etag: Option<String>,
+  /// unix timestamp until which the JWKS is used without revalidation
+  expires_at: u64,
+  jwks: Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct JwksCache {
+  dir: PathBuf,
+  offline: bool,
+}
+
+impl JwksCache {
+  pub fn new(dir: PathBuf, offline: bool) -> Self {
+    Self { dir, offline }
+  }
+
+  /// JWKS for an issuer, discovery document or `jwks_uri` URL
+  pub fn fetch(&self, url: &str) -> JWTResult<Vec<u8>> {
+    let cached = self.read(url);
+    let now = now();
+    let entry = match cached {
+      Some(entry) if self.offline || entry.expires_at > now => entry,
+      None if self.offline => {
+        return Err(JWTError::Internal(format!(
+          "No cached JWKS for {url}. Disable offline mode to fetch it"
+        )))
+      }
+      cached => match self.download(url, cached.as_ref()) {
+        Ok(entry) => {
+          self.write(url, &entry);
+          entry
+        }
+        // keys that are still cached are better than none when the issuer is unreachable
+        Err(_) if cached.is_some() => cached.unwrap(),
+        Err(e) => return Err(e),
+      },
+    };
+    Ok(serde_json::to_vec(&entry.jwks)?)
+  }
+
+  /// the cached JWKS, also when stale as it's revalidated by `request`, or the state of its fetch
+  fn lookup(&self, url: &str) -> JWTResult<Vec<u8>> {
+    if let Some(entry) = self.read(url) {
+      return Ok(serde_json::to_vec(&entry.jwks)?);
+    }
+    match FETCHES.lock().unwrap().get(url) {
+      Some(Fetch::Pending) => Err(JWTError::Internal(format!("Fetching the JWKS of {url}"))),
+      Some(Fetch::Failed { error, .. }) => Err(error.clone()),
+      None => Err(JWTError::Internal(format!(
+        "The JWKS of {url} is fetched once the secret is no longer edited"
+      ))),
+    }
+  }
+
+  /// fetch the JWKS on a background thread, failures are retried with an increasing backoff
+  fn request(&self, url: &str) {
+    if self.offline || self.read(url).is_some_and(|entry| entry.expires_at > now()) {
+      return;
+    }
+    let backoff = {
+      let mut fetches = FETCHES.lock().unwrap();
+      let backoff = match fetches.get(url) {
+        Some(Fetch::Pending) => return,
+        Some(Fetch::Failed { retry_at, .. }) if *retry_at > now() => return,
+        Some(Fetch::Failed { backoff, .. }) => (backoff * 2).min(MAX_BACKOFF),
+        None => MIN_BACKOFF,
+      };
+      fetches.insert(url.to_string(), Fetch::Pending);
+      backoff
+    };
+    let (cache, url) = (self.clone(), url.to_string());
+    thread::spawn(move || {
+      let result = cache.fetch(&url);
+      let mut fetches = FETCHES.lock().unwrap();
+      match result {
+        Ok(_) => fetches.remove(&url),
+        Err(error) => fetches.insert(
+          url,
+          Fetch::Failed {
+            error,
+            retry_at: now() + backoff,
+            backoff,
+          },
+        ),
+      };
+    });
+  }
+
+  fn download(&self, url: &str, cached: Option<&CacheEntry>) -> JWTResult<CacheEntry> {
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    let jwks_uri = match cached {
+      Some(entry) => entry.jwks_uri.clone(),
+      None => resolve_jwks_uri(&agent, url)?,
+    };
+    // the discovery document, or a tampered cache, could point at plain http
+    check_scheme(&jwks_uri)?;
+
+    let mut request = agent.get(&jwks_uri);
+    if let Some(etag) = cached.and_then(|entry| entry.etag.as_ref()) {
+      request = request.set("If-None-Match", etag);
+    }
+    let response = request.call().map_err(|e| fetch_error(&jwks_uri, e))?;
+    let max_age = max_age(response.header("Cache-Control"));
+    let etag = response.header("ETag").map(String::from);
+
+    let jwks = match (response.status(), cached) {
+      (304, Some(entry)) => entry.jwks.clone(),
+      _ => {
+        let jwks = read_json(response, &jwks_uri)?;
+        if jwks.get("keys").is_none() {
+          return Err(JWTError::Internal(format!(
+            "The document at {jwks_uri} isn't a JWKS"
+          )));
+        }
+        jwks
+      }
+    };
+    Ok(CacheEntry {
+      jwks_uri,
+      etag: etag.or_else(|| cached.and_then(|entry| entry.etag.clone())),
+      expires_at: now() + max_age,
+      jwks,
+    })
+  }
+
+  fn path(&self, url: &str) -> PathBuf {
+    let hash = Sha256::digest(url.as_bytes());
+    let name: String = hash.iter().map(|b| format!("{b:02x}")).collect();
+    self.dir.join(format!("{name}.json"))
+  }
+
+  fn read(&self, url: &str) -> Option<CacheEntry> {
+    let content = fs::read(self.path(url)).ok()?;
+    serde_json::from_slice(&content).ok()
+  }
+
+  /// the cache is best effort, failing to write it doesn't fail the decoding
+  fn write(&self, url: &str, entry: &CacheEntry) {
+    if fs::create_dir_all(&self.dir).is_ok() {
+      if let Ok(content) = serde_json::to_vec_pretty(entry) {
+        let _ = fs::write(self.path(url), content);
+      }
+    }
+  }
+}
+
+/// the URL is either the `jwks_uri`, the discovery document or the issuer
+fn resolve_jwks_uri(agent: &ureq::Agent, url: &str) -> JWTResult<String> {
+  let document = get_json(agent, url);
+  match document {
+    Ok(document) if document.get("keys").is_some() => Ok(url.to_string()),
+    Ok(document) if document.get("jwks_uri").is_some() => jwks_uri_of(&document, url),
+    _ if !url.ends_with(DISCOVERY_PATH) => {
+      let discovery_url = format!("{}{DISCOVERY_PATH}", url.trim_end_matches('/'));
+      jwks_uri_of(&get_json(agent, &discovery_url)?, &discovery_url)
+    }
+    Ok(_) => Err(JWTError::Internal(format!(
+      "The document at {url} has no `jwks_uri`"
+    ))),
+    Err(e) => Err(e),
+  }
+}
+
+fn jwks_uri_of(document: &Value, url: &str) -> JWTResult<String> {
+  document
+    .get("jwks_uri")
+    .and_then(Value::as_str)
+    .map(String::from)
+    .ok_or_else(|| JWTError::Internal(format!("The document at {url} has no `jwks_uri`")))
+}
+
+fn get_json(agent: &ureq::Agent, url: &str) -> JWTResult<Value> {
+  let response = agent.get(url).call().map_err(|e| fetch_error(url, e))?;
+  read_json(response, url)
+}
+
+fn read_json(response: ureq::Response, url: &str) -> JWTResult<Value> {
+  let body = response.into_string()?;
+  serde_json::from_str(&body)
+    .map_err(|e| JWTError::Internal(format!("Invalid JSON document at {url}: {e}")))
+}
+
+fn fetch_error(url: &str, err: ureq::Error) -> JWTError {
+  match err {
+    ureq::Error::Status(status, _) => {
+      JWTError::Internal(format!("Unable to fetch {url}: HTTP {status}"))
+    }
+    ureq::Error::Transport(e) => JWTError::Internal(format!("Unable to fetch {url}: {e}")),
+  }
+}
+
+/// seconds the response can be cached for
+fn max_age(cache_control: Option<&str>) -> u64 {
+  let directives = cache_control.unwrap_or_default().split(',').map(str::trim);
+  let mut max_age = DEFAULT_MAX_AGE;
+  for directive in directives {
+    match directive.split_once('=') {
+      Some(("max-age", value)) => max_age = value.parse().unwrap_or(DEFAULT_MAX_AGE),
+      None if directive == "no-cache" || directive == "no-store" => max_age = 0,
+      _ => {}
+    }
+  }
+  max_age.max(MIN_MAX_AGE)
+}
+
+fn now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+  };
+
+  use super::*;
+
+  const JWKS: &str = r#"{"keys":[{"k":"c2VjcmV0","kid":"k1","kty":"oct"}]}"#;
+
+  /// local stand-in for an issuer, records the request line and `If-None-Match` header of every request
+  fn serve(responses: Vec<&'static str>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(vec![]));
+    let recorded = requests.clone();
+    let jwks_uri = format!("{base}/jwks.json");
+    thread::spawn(move || {
+      for (stream, response) in listener.incoming().zip(responses) {
+        let mut stream = stream.unwrap();
+        let mut request = String::new();
+        for line in BufReader::new(&stream).lines() {
+          let line = line.unwrap();
+          if line.is_empty() {
+            break;
+          }
+          if request.is_empty() || line.to_lowercase().starts_with("if-none-match") {
+            request.push_str(&line);
+            request.push(' ');
+          }
+        }
+        recorded.lock().unwrap().push(request.trim().to_string());
+        let response = response.replace("{jwks_uri}", &jwks_uri);
+        let (head, body) = response.split_once("\n\n").unwrap();
+        let reply = format!(
+          "{head}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+          body.len()
+        );
+        stream.write_all(reply.as_bytes()).unwrap();
+      }
+    });
+    (base, requests)
+  }
+
+  fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("jwtui-test-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+  }
+
+  #[test]
+  fn test_is_jwks_url() {
+    assert!(is_jwks_url("https://issuer.example.com"));
+    assert!(is_jwks_url("http://localhost:8080/jwks.json"));
+    assert!(!is_jwks_url("@./jwks.json"));
+  }
+
+  #[test]
+  fn test_check_scheme() {
+    assert!(check_scheme("https://issuer.example.com").is_ok());
+    assert!(check_scheme("http://localhost:8080/jwks.json").is_ok());
+    assert!(check_scheme("http://127.0.0.1:1").is_ok());
+    assert!(check_scheme("http://[::1]:8080/").is_ok());
+    assert_eq!(
+      check_scheme("http://issuer.example.com/jwks.json").unwrap_err(),
+      JWTError::Internal(
+        "Refusing to fetch keys over plain http from issuer.example.com, use https".into()
+      )
+    );
+    assert!(check_scheme("http://localhost.evil.com/").is_err());
+    assert_eq!(
+      check_scheme("file:///etc/jwks.json").unwrap_err(),
+      JWTError::Internal("Refusing to fetch keys from file:///etc/jwks.json, use https".into())
+    );
+  }
+
+  #[test]
+  fn test_max_age() {
+    assert_eq!(max_age(None), DEFAULT_MAX_AGE);
+    assert_eq!(max_age(Some("public, max-age=3600")), 3600);
+    assert_eq!(max_age(Some("no-cache")), MIN_MAX_AGE);
+  }
+
+  #[test]
+  fn test_fetch_jwks_with_discovery_and_cache() {
+    let discovery = r#"HTTP/1.1 200 OK
+
+{"issuer":"test","jwks_uri":"{jwks_uri}"}"#;
+    let jwks = r#"HTTP/1.1 200 OK
+ETag: "v1"
+Cache-Control: max-age=0
+
+{"keys":[{"kty":"oct","kid":"k1","k":"c2VjcmV0"}]}"#;
+    let not_modified = "HTTP/1.1 304 Not Modified\n\n";
+    let home_page = "HTTP/1.1 200 OK\n\n<html></html>";
+    let (base, requests) = serve(vec![home_page, discovery, jwks, not_modified]);
+    let dir = cache_dir("discovery");
+
+    // the issuer isn't a JSON document, so the discovery document is used
+    let cache = JwksCache::new(dir.clone(), false);
+    assert_eq!(cache.fetch(&base).unwrap(), JWKS.as_bytes());
+
+    // expire the cached entry to revalidate it with the ETag
+    let mut entry = cache.read(&base).unwrap();
+    assert_eq!(entry.etag.as_deref(), Some("\"v1\""));
+    entry.expires_at = 0;
+    cache.write(&base, &entry);
+    assert_eq!(cache.fetch(&base).unwrap(), JWKS.as_bytes());
+
+    assert_eq!(
+      *requests.lock().unwrap(),
+      vec![
+        "GET / HTTP/1.1",
+        "GET /.well-known/openid-configuration HTTP/1.1",
+        "GET /jwks.json HTTP/1.1",
+        "GET /jwks.json HTTP/1.1 If-None-Match: \"v1\"",
+      ]
+    );
+
+    // the offline mode only uses the cache
+    let offline = JwksCache::new(dir.clone(), true);
+    assert_eq!(offline.fetch(&base).unwrap(), JWKS.as_bytes());
+    assert_eq!(
+      offline.fetch("http://127.0.0.1:1/jwks.json").unwrap_err(),
+      JWTError::Internal(
+        "No cached JWKS for http://127.0.0.1:1/jwks.json. Disable offline mode to fetch it".into()
+      )
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  /// wait for the background fetch of the URL to finish
+  fn wait_for_fetch(url: &str) {
+    for _ in 0..100 {
+      if !matches!(FETCHES.lock().unwrap().get(url), Some(Fetch::Pending)) {
+        return;
+      }
+      thread::sleep(Duration::from_millis(50));
+    }
+    panic!("{url} is still being fetched");
+  }
+
+  #[test]
+  fn test_request_jwks_in_background() {
+    let jwks = r#"HTTP/1.1 200 OK
+
+{"keys":[{"kty":"oct","kid":"k1","k":"c2VjcmV0"}]}"#;
+    // the URL is resolved before the JWKS is downloaded
+    let (base, requests) = serve(vec![jwks, jwks]);
+    let dir = cache_dir("background");
+    let cache = JwksCache::new(dir.clone(), false);
+    let url = format!("{base}/jwks.json");
+
+    assert_eq!(
+      cache.lookup(&url).unwrap_err(),
+      JWTError::Internal(format!(
+        "The JWKS of {url} is fetched once the secret is no longer edited"
+      ))
+    );
+    cache.request(&url);
+    wait_for_fetch(&url);
+    assert_eq!(cache.lookup(&url).unwrap(), JWKS.as_bytes());
+    // cached, so it isn't fetched again
+    cache.request(&url);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+
+    // failures are kept until the backoff has passed
+    let unreachable = "http://127.0.0.1:1/jwks.json";
+    cache.request(unreachable);
+    wait_for_fetch(unreachable);
+    let error = cache.lookup(unreachable).unwrap_err().to_string();
+    assert!(error.starts_with("Unable to fetch http://127.0.0.1:1/jwks.json"));
+    cache.request(unreachable);
+    assert!(matches!(
+      FETCHES.lock().unwrap().get(unreachable),
+      Some(Fetch::Failed {
+        backoff: MIN_BACKOFF,
+        ..
+      })
+    ));
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn test_fetch_jwks_with_http_jwks_uri() {
+    let discovery = r#"HTTP/1.1 200 OK
+
+{"issuer":"test","jwks_uri":"http://keys.example.com/jwks.json"}"#;
+    let (base, requests) = serve(vec![discovery]);
+    let dir = cache_dir("http-jwks-uri");
+    let url = format!("{base}/.well-known/openid-configuration");
+
+    assert_eq!(
+      JwksCache::new(dir, false).fetch(&url).unwrap_err(),
+      JWTError::Internal(
+        "Refusing to fetch keys over plain http from keys.example.com, use https".into()
+      )
+    );
+    assert_eq!(requests.lock().unwrap().len(), 1);
+  }
+
+  #[test]
+  fn test_fetch_jwks_with_http_error() {
+    let (base, _) = serve(vec!["HTTP/1.1 404 Not Found\n\n"]);
+    let dir = cache_dir("error");
+    let url = format!("{base}/.well-known/openid-configuration");
+
+    assert_eq!(
+      JwksCache::new(dir, false).fetch(&url).unwrap_err(),
+      JWTError::Internal(format!("Unable to fetch {url}: HTTP 404"))
+    );
+  }
+}
@@ -1,10 +1,22 @@
-use jsonwebtoken::{errors::Error, Algorithm, EncodingKey, Header};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
+use ed25519_dalek::SigningKey;
+use jsonwebtoken::{decode_header, errors::Error, Algorithm, EncodingKey, Header};
+use rsa::{pkcs1::EncodeRsaPrivateKey, pkcs8::EncodePrivateKey};
+use serde_json::{Map, Value};
 
 use super::{
-  jwt_decoder::Payload,
+  algorithms::check_header,
+  jwe::{encrypt_jwe, jwk_param, rsa_key_from_jwk, EncryptionSettings},
+  jwt_decoder::{decode_unsigned_header, Payload, TokenSegment},
   models::{BlockState, ScrollableTxt},
-  utils::{get_secret_from_file_or_input, JWTError, JWTResult, SecretType},
-  ActiveBlock, App, Route, RouteId, TextAreaInput, TextInput,
+  schema::{SchemaViolation, Schemas},
+  templates::{expand_placeholders, has_expressions, has_placeholders, resolve_expressions},
+  utils::{
+    get_secret_from_file_or_input, slurp_file, strip_leading_symbol, JWTError, JWTResult,
+    SecretType,
+  },
+  ActiveBlock, App, InputMode, Route, RouteId, TextAreaInput, TextInput,
 };
 
 #[derive(Default)]
@@ -13,8 +25,29 @@ pub struct Encoder<'a> {
   pub header: TextAreaInput<'a>,
   pub payload: TextAreaInput<'a>,
   pub secret: TextInput,
+  /// encryption settings, the token is only signed when empty
+  pub encryption: TextInput,
+  pub encryption_key: TextInput,
   pub signature_verified: bool,
+  /// `alg: none` tokens are only created once confirmed, as anyone can forge them
+  pub allow_unsigned: bool,
+  /// whether the last encoded token has `alg: none`
+  pub unsigned: bool,
+  /// segment of the token clicked on, its JSON block is highlighted
+  pub selected_segment: Option<TokenSegment>,
   pub blocks: BlockState,
+  /// re-encode the token on every tick, so that dynamic claims like `$now` never go stale
+  pub refresh_claims: bool,
+  /// the payload with its placeholders and dynamic claims resolved, empty when it has none
+  pub resolved_payload: String,
+  /// problems of the header, checked as it's typed, see `algorithms::check_header`
+  pub header_problems: Vec<String>,
+  /// violations of the JSON Schema of the payload, see `schema::Schemas`
+  pub schema_violations: Vec<SchemaViolation>,
+  /// input of the last randomized token, see `EncodeArgs::is_randomized`
+  randomized_args: Option<EncodeArgs>,
+  /// secret the header was last adapted to, see `header_for_jwk_secret`
+  jwk_secret: Option<String>,
 }
 
 impl Encoder<'_> {
@@ -42,6 +75,14 @@ impl Encoder<'_> {
           id: RouteId::Encoder,
           active_block: ActiveBlock::EncoderSecret,
         },
+        Route {
+          id: RouteId::Encoder,
+          active_block: ActiveBlock::EncoderEncryption,
+        },
+        Route {
+          id: RouteId::Encoder,
+          active_block: ActiveBlock::EncoderEncryptionKey,
+        },
         Route {
           id: RouteId::Encoder,
           active_block: ActiveBlock::EncoderToken,
@@ -50,29 +91,110 @@ impl Encoder<'_> {
       ..Encoder::default()
     }
   }
+
+  /// segment highlighted in the token and in its JSON block, by selecting either of them
+  pub fn highlighted_segment(&self) -> Option<TokenSegment> {
+    match self.blocks.get_active_block() {
+      ActiveBlock::EncoderToken => self.selected_segment,
+      ActiveBlock::EncoderHeader => Some(TokenSegment::Header),
+      ActiveBlock::EncoderPayload => Some(TokenSegment::Payload),
+      _ => None,
+    }
+  }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 struct EncodeArgs {
   pub header: String,
   /// claims
   pub payload: String,
   /// The secret to sign the JWT with.
   pub secret: String,
+  /// encrypt the token after signing
+  pub encryption: Option<EncryptionSettings>,
+  /// The recipient key to encrypt the JWE with.
+  pub encryption_key: String,
+  /// confirmation to create a token with `alg: none`
+  pub allow_unsigned: bool,
+}
+
+impl EncodeArgs {
+  /// encryption and dynamic claims give a different token every time
+  fn is_randomized(&self) -> bool {
+    self.encryption.is_some() || self.is_dynamic()
+  }
+
+  /// whether the header or payload has placeholders like `{{uuid}}` or claims like `$now+15m`
+  fn is_dynamic(&self) -> bool {
+    has_placeholders(&self.header)
+      || has_placeholders(&self.payload)
+      || has_expressions(&self.payload)
+  }
+
+  /// the args with the placeholders and dynamic claims resolved
+  fn expanded(&self, now: i64) -> JWTResult<EncodeArgs> {
+    let payload = expand_placeholders(&self.payload, now)?;
+    Ok(EncodeArgs {
+      header: expand_placeholders(&self.header, now)?,
+      payload: resolve_expressions(&payload, now).unwrap_or(payload),
+      ..self.clone()
+    })
+  }
 }
 
 pub fn encode_jwt_token(app: &mut App) {
-  let out = encode_token(&EncodeArgs {
-    header: app.data.encoder.header.input.lines().join("\n"),
-    payload: app.data.encoder.payload.input.lines().join("\n"),
-    secret: app.data.encoder.secret.input.value().to_string(),
+  // only adapt the header when the secret changes, so that it can be edited afterwards, and not
+  // while it's being edited
+  let secret = app.data.encoder.secret.input.value();
+  if app.data.encoder.header.input_mode == InputMode::Normal
+    && app.data.encoder.jwk_secret.as_deref() != Some(secret)
+  {
+    let header = app.data.encoder.header.input.lines().join("\n");
+    if let Some(header) = header_for_jwk_secret(&header, secret) {
+      app.data.encoder.header = TextAreaInput::new(header.lines().map(String::from).collect());
+    }
+    app.data.encoder.jwk_secret = Some(secret.to_string());
+  }
+  let header = app.data.encoder.header.input.lines().join("\n");
+  // placeholders aren't valid JSON until they are expanded
+  let header = expand_placeholders(&header, Utc::now().timestamp()).unwrap_or(header);
+  app.data.encoder.header_problems = check_header(&header, app.data.encoder.secret.input.value());
+
+  let out = parse_encryption(app.data.encoder.encryption.input.value()).and_then(|encryption| {
+    let args = EncodeArgs {
+      header: app.data.encoder.header.input.lines().join("\n"),
+      payload: app.data.encoder.payload.input.lines().join("\n"),
+      secret: app.data.encoder.secret.input.value().to_string(),
+      encryption,
+      encryption_key: app.data.encoder.encryption_key.input.value().to_string(),
+      allow_unsigned: app.data.encoder.allow_unsigned,
+    };
+    // keep the token stable until the input changes, unless dynamic claims are refreshed
+    let refresh = app.data.encoder.refresh_claims && args.is_dynamic();
+    if args.is_randomized() && !refresh && app.data.encoder.randomized_args.as_ref() == Some(&args)
+    {
+      return Ok(app.data.encoder.encoded.get_txt());
+    }
+    let expanded = args.expanded(Utc::now().timestamp())?;
+    app.data.encoder.resolved_payload = match expanded.payload != args.payload {
+      true => expanded.payload.clone(),
+      false => String::new(),
+    };
+    app.data.encoder.schema_violations = match &app.data.schemas {
+      Some(schemas) => check_schema(schemas, &expanded),
+      None => vec![],
+    };
+    let token = encode_token(&expanded)?;
+    app.data.encoder.randomized_args = args.is_randomized().then_some(args);
+    Ok(token)
   });
 
   match out {
     Ok(token) => {
       if token != app.data.encoder.encoded.get_txt() {
+        app.data.encoder.unsigned = decode_unsigned_header(&token).is_some();
         app.data.encoder.encoded = ScrollableTxt::new(token);
-        app.data.encoder.signature_verified = true;
+        app.data.encoder.signature_verified = !app.data.encoder.unsigned;
       }
       app.data.error = String::new();
     }
@@ -82,6 +204,47 @@ pub fn encode_jwt_token(app: &mut App) {
   }
 }
 
+/// verify the encoded token in the decoder, with the secret it was signed with when it's symmetric
+pub fn send_to_decoder(app: &mut App) {
+  let token = app.data.encoder.encoded.get_txt();
+  if token.is_empty() {
+    app.handle_error(JWTError::Internal(
+      "There is no encoded token to send to the decoder".into(),
+    ));
+    return;
+  }
+  let symmetric = decode_header(&token).is_ok_and(|header| {
+    matches!(
+      header.alg,
+      Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    )
+  });
+  if symmetric {
+    app.data.decoder.secret = TextInput::new(app.data.encoder.secret.input.value().into());
+  }
+  app.data.decoder.encoded = TextInput::new(token);
+  app.route_decoder();
+}
+
+/// the payload as signed, checked against the schema of its `typ` header and `iss` claim
+fn check_schema(schemas: &Schemas, args: &EncodeArgs) -> Vec<SchemaViolation> {
+  let Ok(claims) = serde_json::from_str::<Value>(&args.payload) else {
+    return vec![];
+  };
+  let header = serde_json::from_str::<Value>(&args.header).unwrap_or_default();
+  let typ = header.get("typ").and_then(Value::as_str);
+  schemas
+    .find(typ, &claims)
+    .map_or(vec![], |entry| entry.validate(&claims))
+}
+
+fn parse_encryption(settings: &str) -> JWTResult<Option<EncryptionSettings>> {
+  match settings.trim() {
+    "" => Ok(None),
+    settings => settings.parse().map(Some),
+  }
+}
+
 fn encode_token(args: &EncodeArgs) -> JWTResult<String> {
   if args.header.is_empty() {
     return Err(String::from("Header should not be empty").into());
@@ -89,17 +252,29 @@ fn encode_token(args: &EncodeArgs) -> JWTResult<String> {
   if args.payload.is_empty() {
     return Err(String::from("Payload should not be empty").into());
   }
+  if let Some(header) = unsigned_header(&args.header) {
+    return encode_unsigned_token(args, &header);
+  }
   let header: Result<Header, serde_json::Error> = serde_json::from_str(&args.header);
   match header {
     Ok(header) => {
-      let alg = header.alg;
-
       let payload: Result<Payload, serde_json::Error> = serde_json::from_str(&args.payload);
       match payload {
-        Ok(payload) => {
-          let encoding_key = encoding_key_from_secret(&alg, &args.secret)?;
-          Ok(jsonwebtoken::encode(&header, &payload, &encoding_key)?)
-        }
+        Ok(payload) => match &args.encryption {
+          Some(encryption) if !encryption.nested => encrypt_jwe(
+            serde_json::to_string(&payload)?.as_bytes(),
+            encryption,
+            &args.encryption_key,
+          ),
+          encryption => {
+            let encoding_key = encoding_key_from_secret(&header, &args.secret)?;
+            let token = jsonwebtoken::encode(&header, &payload, &encoding_key)?;
+            match encryption {
+              Some(encryption) => encrypt_jwe(token.as_bytes(), encryption, &args.encryption_key),
+              None => Ok(token),
+            }
+          }
+        },
         Err(e) => Err(format!("Error parsing payload: {:}", e).into()),
       }
     }
@@ -107,10 +282,50 @@ fn encode_token(args: &EncodeArgs) -> JWTResult<String> {
   }
 }
 
-pub fn encoding_key_from_secret(alg: &Algorithm, secret_string: &str) -> JWTResult<EncodingKey> {
+/// the header when its `alg` is `none` in any case, which `Header` can't parse
+fn unsigned_header(header: &str) -> Option<Map<String, Value>> {
+  let header: Map<String, Value> = serde_json::from_str(header).ok()?;
+  let alg = header.get("alg")?.as_str()?;
+  alg.eq_ignore_ascii_case("none").then_some(header)
+}
+
+/// an unsecured JWT with an empty signature, for testing that verifiers reject it
+fn encode_unsigned_token(args: &EncodeArgs, header: &Map<String, Value>) -> JWTResult<String> {
+  let payload: Payload = serde_json::from_str(&args.payload)
+    .map_err(|e| JWTError::Internal(format!("Error parsing payload: {e}")))?;
+  match &args.encryption {
+    Some(encryption) if !encryption.nested => encrypt_jwe(
+      serde_json::to_string(&payload)?.as_bytes(),
+      encryption,
+      &args.encryption_key,
+    ),
+    _ if !args.allow_unsigned => Err(JWTError::Internal(
+      "`alg: none` creates an UNSIGNED token that anyone can forge. Press <n> to allow it".into(),
+    )),
+    encryption => {
+      let token = format!(
+        "{}.{}.",
+        URL_SAFE_NO_PAD.encode(serde_json::to_string(header)?),
+        URL_SAFE_NO_PAD.encode(serde_json::to_string(&payload)?)
+      );
+      match encryption {
+        Some(encryption) => encrypt_jwe(token.as_bytes(), encryption, &args.encryption_key),
+        None => Ok(token),
+      }
+    }
+  }
+}
+
+pub fn encoding_key_from_secret(header: &Header, secret_string: &str) -> JWTResult<EncodingKey> {
+  let alg = &header.alg;
   let (secret, file_type) = get_secret_from_file_or_input(alg, secret_string);
   let secret = secret?;
 
+  if file_type == SecretType::Jwks {
+    let jwk = signing_jwk(&secret, header.kid.as_deref())?;
+    return encoding_key_from_jwk(alg, &jwk);
+  }
+
   match alg {
     Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => match file_type {
       SecretType::Plain => Ok(EncodingKey::from_secret(&secret)),
@@ -150,12 +365,160 @@ pub fn encoding_key_from_secret(alg: &Algorithm, secret_string: &str) -> JWTResu
   }
 }
 
+/// the signing key from a private JWK, or from a JWKS by the header's `kid`
+pub(super) fn signing_jwk(secret: &[u8], kid: Option<&str>) -> JWTResult<Value> {
+  let jwks: Value = serde_json::from_slice(secret)
+    .map_err(|_| JWTError::Internal("Invalid jwks secret format".into()))?;
+  match (jwks.get("keys").and_then(Value::as_array), kid) {
+    (Some(keys), Some(kid)) => keys
+      .iter()
+      .find(|k| k.get("kid").and_then(Value::as_str) == Some(kid))
+      .cloned()
+      .ok_or_else(|| JWTError::Internal(format!("No jwk found for 'kid' {kid:?}"))),
+    (Some(keys), None) if keys.len() == 1 => Ok(keys[0].clone()),
+    (Some(_), None) => Err(JWTError::Internal(
+      "Missing 'kid' from jwt header. Required to pick the signing key from the jwks".into(),
+    )),
+    (None, _) if jwks.get("kty").is_some() => Ok(jwks),
+    (None, _) => Err(JWTError::Internal("Invalid jwks secret format".into())),
+  }
+}
+
+fn encoding_key_from_jwk(alg: &Algorithm, jwk: &Value) -> JWTResult<EncodingKey> {
+  let kty = jwk.get("kty").and_then(Value::as_str).unwrap_or_default();
+  let crv = jwk.get("crv").and_then(Value::as_str).unwrap_or_default();
+  let supported = match kty {
+    "oct" => matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512),
+    "RSA" => matches!(
+      alg,
+      Algorithm::RS256
+        | Algorithm::RS384
+        | Algorithm::RS512
+        | Algorithm::PS256
+        | Algorithm::PS384
+        | Algorithm::PS512
+    ),
+    "EC" => matches!(
+      (alg, crv),
+      (Algorithm::ES256, "P-256") | (Algorithm::ES384, "P-384")
+    ),
+    "OKP" => *alg == Algorithm::EdDSA && crv == "Ed25519",
+    _ => false,
+  };
+  if !supported {
+    return Err(JWTError::Internal(format!(
+      "The {kty} {crv} jwk can't sign {alg:?} tokens"
+    )));
+  }
+  if kty == "oct" {
+    return Ok(EncodingKey::from_secret(&jwk_param(jwk, "k")?));
+  }
+
+  // jsonwebtoken only reads private keys from PEM or DER
+  let d = jwk_param(jwk, "d")
+    .map_err(|_| JWTError::Internal("The jwk has no private key. Provide a private jwk".into()))?;
+  let invalid_key = || JWTError::Internal(format!("Invalid {kty} private jwk"));
+  match (kty, crv) {
+    ("RSA", _) => {
+      let key = rsa_key_from_jwk(jwk).ok_or_else(invalid_key)?;
+      let der = key.to_pkcs1_der().map_err(|_| invalid_key())?;
+      Ok(EncodingKey::from_rsa_der(der.as_bytes()))
+    }
+    ("EC", "P-256") => {
+      let key = p256::SecretKey::from_slice(&d).map_err(|_| invalid_key())?;
+      let der = key.to_pkcs8_der().map_err(|_| invalid_key())?;
+      Ok(EncodingKey::from_ec_der(der.as_bytes()))
+    }
+    ("EC", _) => {
+      let key = p384::SecretKey::from_slice(&d).map_err(|_| invalid_key())?;
+      let der = key.to_pkcs8_der().map_err(|_| invalid_key())?;
+      Ok(EncodingKey::from_ec_der(der.as_bytes()))
+    }
+    _ => {
+      let d: [u8; 32] = d.try_into().map_err(|_| invalid_key())?;
+      let der = SigningKey::from_bytes(&d)
+        .to_pkcs8_der()
+        .map_err(|_| invalid_key())?;
+      Ok(EncodingKey::from_ed_der(der.as_bytes()))
+    }
+  }
+}
+
+/// header with the `alg` and `kid` of a single JWK given as secret, `None` when there is nothing to change
+fn header_for_jwk_secret(header: &str, secret_string: &str) -> Option<String> {
+  let secret = if secret_string.starts_with('@')
+    && (secret_string.ends_with(".json") || secret_string.ends_with(".jwk"))
+  {
+    slurp_file(strip_leading_symbol(secret_string)).ok()?
+  } else if secret_string.trim_start().starts_with('{') {
+    secret_string.as_bytes().to_vec()
+  } else {
+    return None;
+  };
+  let jwk: Value = serde_json::from_slice(&secret).ok()?;
+  let jwk = match jwk.get("keys").and_then(Value::as_array) {
+    Some(keys) if keys.len() == 1 => keys[0].clone(),
+    Some(_) => return None,
+    None => jwk,
+  };
+
+  let mut header: Map<String, Value> = serde_json::from_str(header).ok()?;
+  let current: Option<Algorithm> = header
+    .get("alg")
+    .and_then(|alg| serde_json::from_value(alg.clone()).ok());
+  let alg = match jwk.get("alg").and_then(Value::as_str) {
+    Some(alg) => alg.to_string(),
+    None => jwk_algorithm(&jwk, current)?,
+  };
+
+  let mut changed = header.get("alg").and_then(Value::as_str) != Some(alg.as_str());
+  header.insert("alg".into(), alg.into());
+  if let Some(kid) = jwk.get("kid").filter(|kid| kid.is_string()) {
+    changed |= header.get("kid") != Some(kid);
+    header.insert("kid".into(), kid.clone());
+  }
+  changed.then(|| serde_json::to_string_pretty(&header).unwrap_or_default())
+}
+
+/// algorithm for a JWK without `alg`, keeping the current one when it fits the key
+fn jwk_algorithm(jwk: &Value, current: Option<Algorithm>) -> Option<String> {
+  let kty = jwk.get("kty").and_then(Value::as_str)?;
+  let crv = jwk.get("crv").and_then(Value::as_str);
+  let alg = match (kty, crv, current) {
+    ("oct", _, Some(alg @ (Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512))) => alg,
+    ("oct", _, _) => Algorithm::HS256,
+    (
+      "RSA",
+      _,
+      Some(
+        alg @ (Algorithm::RS256
+        | Algorithm::RS384
+        | Algorithm::RS512
+        | Algorithm::PS256
+        | Algorithm::PS384
+        | Algorithm::PS512),
+      ),
+    ) => alg,
+    ("RSA", _, _) => Algorithm::RS256,
+    ("EC", Some("P-256"), _) => Algorithm::ES256,
+    ("EC", Some("P-384"), _) => Algorithm::ES384,
+    ("OKP", Some("Ed25519"), _) => Algorithm::EdDSA,
+    _ => return None,
+  };
+  Some(format!("{alg:?}"))
+}
+
 #[cfg(test)]
 mod tests {
   use tui_textarea::TextArea;
 
   use super::*;
-  use crate::app::jwt_decoder::{decode_token, DecodeArgs};
+  use crate::app::{
+    jwe::UNSIGNED_JWE,
+    jwt_decoder::{decode_or_decrypt_token, decode_token, DecodeArgs},
+    keygen::{KeyPair, KeyType},
+    validation::ValidationSettings,
+  };
 
   #[test]
   fn test_encode_hmac_jwt_token_with_valid_payload_and_defaults() {
@@ -184,6 +547,7 @@ mod tests {
       secret: String::from("secrets"),
       time_format_utc: false,
       ignore_exp: true,
+      validation: ValidationSettings::default(),
     };
 
     let decoded = decode_token(&args).1;
@@ -191,6 +555,72 @@ mod tests {
     assert!(decoded.is_ok())
   }
 
+  #[test]
+  fn test_encode_dynamic_claims() {
+    let mut app = App::new(None, "secrets".into());
+    app.data.encoder.payload.input = vec![
+      "{",
+      r#"  "iat": "$now","#,
+      r#"  "exp": "$now+15m","#,
+      r#"  "jti": "$uuid""#,
+      "}",
+    ]
+    .into();
+
+    encode_jwt_token(&mut app);
+    let token = app.data.encoder.encoded.get_txt();
+    let args = DecodeArgs {
+      jwt: token.clone(),
+      secret: String::from("secrets"),
+      time_format_utc: false,
+      ignore_exp: false,
+      validation: ValidationSettings::default(),
+    };
+    let claims = decode_token(&args).1.unwrap().claims;
+    let iat = claims.timestamp("iat").unwrap();
+    assert_eq!(claims.timestamp("exp"), Some(iat + 900));
+    assert_eq!(claims.0["jti"].as_str().unwrap().len(), 36);
+    let resolved: Payload = serde_json::from_str(&app.data.encoder.resolved_payload).unwrap();
+    assert_eq!(resolved, claims);
+
+    // the token is kept until the input changes
+    encode_jwt_token(&mut app);
+    assert_eq!(app.data.encoder.encoded.get_txt(), token);
+
+    app.data.encoder.refresh_claims = true;
+    encode_jwt_token(&mut app);
+    assert_ne!(app.data.encoder.encoded.get_txt(), token);
+  }
+
+  #[test]
+  fn test_encode_jwt_token_with_schema() {
+    let mut app = App::new(None, "secrets".into());
+    app.data.schemas = Some(
+      Schemas::from_file(std::path::Path::new("./test_data/test_claims_schema.json")).unwrap(),
+    );
+    app.data.encoder.payload.input = vec!["{", r#"  "sub": 1234567890"#, "}"].into();
+
+    encode_jwt_token(&mut app);
+    assert_eq!(
+      app
+        .data
+        .encoder
+        .schema_violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>(),
+      vec!["/exp is required", r#"/sub value is not of type "string""#]
+    );
+    // the token is still encoded
+    assert!(!app.data.encoder.encoded.get_txt().is_empty());
+
+    // dynamic claims are checked as resolved
+    app.data.encoder.payload.input =
+      vec!["{", r#"  "sub": "user-1","#, r#"  "exp": "$now+15m""#, "}"].into();
+    encode_jwt_token(&mut app);
+    assert!(app.data.encoder.schema_violations.is_empty());
+  }
+
   #[test]
   fn test_encode_rsa_jwt_token_with_valid_payload_and_header() {
     let mut app = App::new(None, "".into());
@@ -224,6 +654,7 @@ mod tests {
       secret: String::from("@./test_data/test_rsa_public_key.pem"),
       time_format_utc: false,
       ignore_exp: true,
+      validation: ValidationSettings::default(),
     };
 
     let decoded = decode_token(&args).1.unwrap();
@@ -267,6 +698,7 @@ mod tests {
       secret: String::from("@./test_data/test_rsa_public_key.der"),
       time_format_utc: false,
       ignore_exp: true,
+      validation: ValidationSettings::default(),
     };
 
     let decoded = decode_token(&args).1.unwrap();
@@ -310,6 +742,7 @@ mod tests {
       secret: String::from("@./test_data/test_ecdsa_public_key.pk8"),
       time_format_utc: false,
       ignore_exp: true,
+      validation: ValidationSettings::default(),
     };
 
     let decoded = decode_token(&args).1.unwrap();
@@ -353,6 +786,7 @@ mod tests {
       secret: String::from("@./test_data/test_eddsa_public_key.pem"),
       time_format_utc: false,
       ignore_exp: true,
+      validation: ValidationSettings::default(),
     };
 
     let decoded = decode_token(&args).1.unwrap();
@@ -404,4 +838,227 @@ mod tests {
       "Error parsing header: missing field `alg` at line 3 column 1"
     );
   }
+
+  #[test]
+  fn test_encode_unsigned_token_after_confirmation() {
+    let mut app = App::new(None, "".into());
+
+    app.data.encoder.header.input = vec!["{", r#"  "alg": "none""#, "}"].into();
+    app.data.encoder.payload.input = vec!["{", r#"  "sub": "1234567890""#, "}"].into();
+
+    encode_jwt_token(&mut app);
+
+    assert!(app
+      .data
+      .error
+      .starts_with("`alg: none` creates an UNSIGNED token"));
+    assert_eq!(app.data.encoder.encoded.get_txt(), "");
+
+    app.data.encoder.allow_unsigned = true;
+    encode_jwt_token(&mut app);
+
+    assert_eq!(app.data.error, "");
+    assert_eq!(
+      app.data.encoder.encoded.get_txt(),
+      "eyJhbGciOiJub25lIn0.eyJzdWIiOiIxMjM0NTY3ODkwIn0."
+    );
+    assert!(app.data.encoder.unsigned);
+    assert!(!app.data.encoder.signature_verified);
+
+    let args = DecodeArgs {
+      jwt: app.data.encoder.encoded.get_txt(),
+      secret: String::new(),
+      time_format_utc: false,
+      ignore_exp: true,
+      validation: ValidationSettings::default(),
+    };
+    let (decoded, verified) = decode_or_decrypt_token(&args);
+    assert!(decoded.unwrap().is_unsigned());
+    assert!(verified.is_err());
+  }
+
+  #[test]
+  fn test_encode_jwe_token_with_claims() {
+    let mut app = App::new(None, "".into());
+
+    app.data.encoder.payload.input = vec!["{", r#"  "sub": "1234567890""#, "}"].into();
+    app.data.encoder.encryption.input = "alg=RSA-OAEP-256 enc=A256GCM".into();
+    app.data.encoder.encryption_key.input = "@./test_data/test_rsa_public_key.pem".into();
+
+    encode_jwt_token(&mut app);
+    assert_eq!(app.data.error, "");
+    let token = app.data.encoder.encoded.get_txt();
+    assert_eq!(token.split('.').count(), 5);
+
+    // encryption is randomized, the token only changes with the input
+    encode_jwt_token(&mut app);
+    assert_eq!(app.data.encoder.encoded.get_txt(), token);
+
+    let args = DecodeArgs {
+      jwt: token,
+      secret: String::from("@./test_data/test_rsa_private_key.pem"),
+      time_format_utc: false,
+      ignore_exp: true,
+      validation: ValidationSettings::default(),
+    };
+
+    let (decoded, verified) = decode_or_decrypt_token(&args);
+    let decoded = decoded.unwrap();
+
+    // anyone can encrypt to the public key
+    assert_eq!(
+      verified.unwrap_err(),
+      JWTError::Internal(UNSIGNED_JWE.into())
+    );
+    assert!(decoded.header.is_none());
+    assert_eq!(decoded.jwe_header.unwrap().enc, "A256GCM");
+    assert_eq!(
+      decoded.claims,
+      serde_json::from_str(r#"{"sub": "1234567890"}"#).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_encode_nested_jwe_token() {
+    let mut app = App::new(None, "secrets".into());
+
+    app.data.encoder.payload.input = vec!["{", r#"  "sub": "1234567890""#, "}"].into();
+    app.data.encoder.encryption.input = "alg=dir enc=A128CBC-HS256 nested".into();
+    app.data.encoder.encryption_key.input =
+      "b64:AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=".into();
+
+    encode_jwt_token(&mut app);
+    assert_eq!(app.data.error, "");
+
+    // the key decrypts the token, the inner signature needs the signing secret
+    let args = DecodeArgs {
+      jwt: app.data.encoder.encoded.get_txt(),
+      secret: String::from("b64:AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8="),
+      time_format_utc: false,
+      ignore_exp: true,
+      validation: ValidationSettings::default(),
+    };
+
+    let (decoded, verified) = decode_or_decrypt_token(&args);
+    let decoded = decoded.unwrap();
+
+    assert_eq!(decoded.jwe_header.unwrap().cty.as_deref(), Some("JWT"));
+    assert_eq!(decoded.header.unwrap().alg, Algorithm::HS256);
+    assert!(verified.is_err());
+  }
+
+  #[test]
+  fn test_encode_jwe_token_with_invalid_settings() {
+    let mut app = App::new(None, "".into());
+
+    app.data.encoder.encryption.input = "alg=RSA-OAEP-256".into();
+
+    encode_jwt_token(&mut app);
+
+    assert_eq!(app.data.error, "Missing `enc=` encryption setting");
+  }
+
+  #[test]
+  fn test_encode_token_with_private_jwk() {
+    let pair = KeyPair::generate(KeyType::P256, Some("ec-1".into())).unwrap();
+    let path = std::env::temp_dir().join(format!("jwtui-encoder-{}.jwk", std::process::id()));
+    std::fs::write(&path, pair.private_jwk().to_string()).unwrap();
+
+    let mut app = App::new(None, format!("@{}", path.display()));
+    app.data.encoder.payload.input = vec![r#"{"sub": "1234567890"}"#].into();
+
+    encode_jwt_token(&mut app);
+    assert_eq!(app.data.error, "");
+
+    // alg and kid are taken from the jwk
+    let header: Header =
+      serde_json::from_str(&app.data.encoder.header.input.lines().join("\n")).unwrap();
+    assert_eq!(header.alg, Algorithm::ES256);
+    assert_eq!(header.kid.as_deref(), Some("ec-1"));
+
+    let args = DecodeArgs {
+      jwt: app.data.encoder.encoded.get_txt(),
+      secret: serde_json::json!({ "keys": [pair.public_jwk()] }).to_string(),
+      time_format_utc: false,
+      ignore_exp: true,
+      validation: ValidationSettings::default(),
+    };
+    assert!(decode_token(&args).1.is_ok());
+
+    // a header edited afterwards is kept
+    app.data.encoder.header.input = vec![r#"{"alg": "ES256"}"#].into();
+    encode_jwt_token(&mut app);
+    assert_eq!(app.data.error, "");
+    assert_eq!(
+      app.data.encoder.header.input.lines().join("\n"),
+      r#"{"alg": "ES256"}"#
+    );
+
+    // until the secret changes
+    app.data.encoder.secret = TextInput::new(pair.private_jwk().to_string());
+    encode_jwt_token(&mut app);
+    let header: Header =
+      serde_json::from_str(&app.data.encoder.header.input.lines().join("\n")).unwrap();
+    assert_eq!(header.kid.as_deref(), Some("ec-1"));
+    std::fs::remove_file(path).unwrap();
+  }
+
+  #[test]
+  fn test_encode_token_with_jwks_by_kid() {
+    let ed = KeyPair::generate(KeyType::Ed25519, Some("ed".into())).unwrap();
+    let hs = KeyPair::generate(KeyType::Hs256, Some("hs".into())).unwrap();
+    let jwks = serde_json::json!({ "keys": [ed.private_jwk(), hs.private_jwk()] }).to_string();
+
+    let mut app = App::new(None, jwks.clone());
+    app.data.encoder.payload.input = vec![r#"{"sub": "1234567890"}"#].into();
+
+    // the header isn't changed for a jwks with several keys
+    encode_jwt_token(&mut app);
+    assert_eq!(
+      app.data.error,
+      "Missing 'kid' from jwt header. Required to pick the signing key from the jwks"
+    );
+
+    app.data.encoder.header.input = vec![r#"{"alg": "EdDSA", "kid": "ed", "typ": "JWT"}"#].into();
+    encode_jwt_token(&mut app);
+    assert_eq!(app.data.error, "");
+
+    app.data.encoder.header.input = vec![r#"{"alg": "HS256", "kid": "ed"}"#].into();
+    encode_jwt_token(&mut app);
+    assert_eq!(
+      app.data.error,
+      "The OKP Ed25519 jwk can't sign HS256 tokens"
+    );
+
+    app.data.encoder.header.input = vec![r#"{"alg": "HS256", "kid": "hs"}"#].into();
+    encode_jwt_token(&mut app);
+    assert_eq!(app.data.error, "");
+
+    let args = DecodeArgs {
+      jwt: app.data.encoder.encoded.get_txt(),
+      secret: jwks,
+      time_format_utc: false,
+      ignore_exp: true,
+      validation: ValidationSettings::default(),
+    };
+    assert!(decode_token(&args).1.is_ok());
+  }
+
+  #[test]
+  fn test_header_for_jwk_secret() {
+    let rsa = r#"{"kty": "RSA", "kid": "r1", "n": "AQAB", "e": "AQAB", "d": "AQAB"}"#;
+    assert_eq!(
+      header_for_jwk_secret(r#"{"alg": "PS384"}"#, rsa).as_deref(),
+      Some("{\n  \"alg\": \"PS384\",\n  \"kid\": \"r1\"\n}")
+    );
+    assert_eq!(
+      header_for_jwk_secret(r#"{"alg": "HS256"}"#, rsa).as_deref(),
+      Some("{\n  \"alg\": \"RS256\",\n  \"kid\": \"r1\"\n}")
+    );
+    assert_eq!(
+      header_for_jwk_secret(r#"{"alg": "RS256", "kid": "r1"}"#, rsa),
+      None
+    );
+    assert_eq!(header_for_jwk_secret(r#"{"alg": "HS256"}"#, "secret"), None);
+  }
 }
@@ -28,6 +28,9 @@ generate_keybindings! {
   cycle_main_views,
   jump_to_decoder,
   jump_to_encoder,
+  jump_to_history,
+  jump_to_compare,
+  generate_keys,
   copy_to_clipboard,
   pg_up,
   pg_down,
@@ -37,6 +40,17 @@ generate_keybindings! {
   right,
   toggle_utc_dates,
   toggle_ignore_exp,
+  toggle_annotations,
+  search_keys,
+  send_to_encoder,
+  toggle_unsigned,
+  open_templates,
+  toggle_refresh_claims,
+  pick_algorithm,
+  fill_key_headers,
+  send_to_decoder,
+  toggle_pin,
+  delete_history_entry,
   toggle_input_edit,
   clear_input,
   delete_prev_char,
@@ -56,7 +70,8 @@ pub enum HContext {
   General,
   Editable,
   Decoder,
-  //   Encoder,
+  Encoder,
+  History,
 }
 
 impl fmt::Display for HContext {
@@ -116,6 +131,24 @@ pub const DEFAULT_KEYBINDING: KeyBindings = KeyBindings {
     desc: "Switch to encoder view",
     context: HContext::General,
   },
+  jump_to_history: KeyBinding {
+    key: Key::Char('H'),
+    alt: None,
+    desc: "Switch to history view",
+    context: HContext::General,
+  },
+  jump_to_compare: KeyBinding {
+    key: Key::Char('C'),
+    alt: None,
+    desc: "Switch to compare view",
+    context: HContext::General,
+  },
+  generate_keys: KeyBinding {
+    key: Key::Char('g'),
+    alt: None,
+    desc: "Generate keys",
+    context: HContext::General,
+  },
   cycle_main_views: KeyBinding {
     key: Key::Tab,
     alt: None,
@@ -176,6 +209,72 @@ pub const DEFAULT_KEYBINDING: KeyBindings = KeyBindings {
     desc: "Toggle ignoring exp claim from validation",
     context: HContext::Decoder,
   },
+  toggle_annotations: KeyBinding {
+    key: Key::Char('a'),
+    alt: None,
+    desc: "Toggle claim annotations",
+    context: HContext::Decoder,
+  },
+  search_keys: KeyBinding {
+    key: Key::Char('/'),
+    alt: None,
+    desc: "Search the keys of the header/payload",
+    context: HContext::Decoder,
+  },
+  send_to_encoder: KeyBinding {
+    key: Key::Char('s'),
+    alt: None,
+    desc: "Open the header and claims in the encoder to re-sign them",
+    context: HContext::Decoder,
+  },
+  toggle_unsigned: KeyBinding {
+    key: Key::Char('n'),
+    alt: None,
+    desc: "Allow/disallow creating unsigned (alg: none) tokens",
+    context: HContext::Encoder,
+  },
+  open_templates: KeyBinding {
+    key: Key::Char('o'),
+    alt: None,
+    desc: "Open a claims template",
+    context: HContext::Encoder,
+  },
+  toggle_refresh_claims: KeyBinding {
+    key: Key::Char('f'),
+    alt: None,
+    desc: "Refresh dynamic claims like $now on every tick",
+    context: HContext::Encoder,
+  },
+  pick_algorithm: KeyBinding {
+    key: Key::Char('a'),
+    alt: None,
+    desc: "Pick a signing algorithm that fits the secret",
+    context: HContext::Encoder,
+  },
+  fill_key_headers: KeyBinding {
+    key: Key::Char('k'),
+    alt: None,
+    desc: "Fill the kid and jwk or x5c/x5t#S256 headers from the key",
+    context: HContext::Encoder,
+  },
+  send_to_decoder: KeyBinding {
+    key: Key::Char('v'),
+    alt: None,
+    desc: "Verify the encoded token in the decoder",
+    context: HContext::Encoder,
+  },
+  toggle_pin: KeyBinding {
+    key: Key::Char('p'),
+    alt: None,
+    desc: "Pin/unpin history entry",
+    context: HContext::History,
+  },
+  delete_history_entry: KeyBinding {
+    key: Key::Delete,
+    alt: Some(Key::Char('x')),
+    desc: "Delete history entry",
+    context: HContext::History,
+  },
   toggle_input_edit: KeyBinding {
     key: Key::Enter,
     alt: Some(Key::Char('e')),
@@ -258,8 +357,8 @@ pub fn get_help_docs() -> Vec<Vec<String>> {
 
 fn help_row(item: &KeyBinding) -> Vec<String> {
   vec![
-    if item.alt.is_some() {
-      format!("{} | {}", item.key, item.alt.unwrap())
+    if let Some(alt) = item.alt {
+      format!("{} | {}", item.key, alt)
     } else {
       item.key.to_string()
     },
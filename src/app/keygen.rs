@@ -0,0 +1,615 @@
+use std::{fmt, fs, path::PathBuf, str::FromStr};
+
+use base64::{
+  engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+  Engine,
+};
+use ed25519_dalek::SigningKey;
+use jsonwebtoken::Algorithm;
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use rand::{rngs::OsRng, RngCore};
+use rsa::{
+  pkcs1::DecodeRsaPrivateKey,
+  pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey},
+  traits::{PrivateKeyParts, PublicKeyParts},
+  RsaPrivateKey,
+};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use super::{
+  algorithms::{HMAC_ALGORITHMS, RSA_ALGORITHMS},
+  models::{BlockState, StatefulTable},
+  utils::{write_private_file, JWTError, JWTResult},
+  ActiveBlock, App, Route, RouteId, TextAreaInput, TextInput,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+  Hs256,
+  Hs384,
+  Hs512,
+  Rsa2048,
+  Rsa3072,
+  Rsa4096,
+  P256,
+  P384,
+  Ed25519,
+}
+
+impl KeyType {
+  pub const ALL: [KeyType; 9] = [
+    KeyType::Hs256,
+    KeyType::Hs384,
+    KeyType::Hs512,
+    KeyType::Rsa2048,
+    KeyType::Rsa3072,
+    KeyType::Rsa4096,
+    KeyType::P256,
+    KeyType::P384,
+    KeyType::Ed25519,
+  ];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      KeyType::Hs256 => "HS256",
+      KeyType::Hs384 => "HS384",
+      KeyType::Hs512 => "HS512",
+      KeyType::Rsa2048 => "RSA-2048",
+      KeyType::Rsa3072 => "RSA-3072",
+      KeyType::Rsa4096 => "RSA-4096",
+      KeyType::P256 => "P-256",
+      KeyType::P384 => "P-384",
+      KeyType::Ed25519 => "Ed25519",
+    }
+  }
+
+  /// signing algorithm of the key, set in the `alg` of the JWK and the encoder header
+  pub fn alg(&self) -> &'static str {
+    match self {
+      KeyType::Hs256 => "HS256",
+      KeyType::Hs384 => "HS384",
+      KeyType::Hs512 => "HS512",
+      KeyType::Rsa2048 | KeyType::Rsa3072 | KeyType::Rsa4096 => "RS256",
+      KeyType::P256 => "ES256",
+      KeyType::P384 => "ES384",
+      KeyType::Ed25519 => "EdDSA",
+    }
+  }
+
+  pub fn is_symmetric(&self) -> bool {
+    matches!(self, KeyType::Hs256 | KeyType::Hs384 | KeyType::Hs512)
+  }
+}
+
+impl fmt::Display for KeyType {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+impl FromStr for KeyType {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let key_type = match s.to_uppercase().as_str() {
+      "ES256" => KeyType::P256,
+      "ES384" => KeyType::P384,
+      "EDDSA" => KeyType::Ed25519,
+      upper => KeyType::ALL
+        .into_iter()
+        .find(|k| k.name().to_uppercase() == upper)
+        .ok_or_else(|| {
+          let names: Vec<_> = KeyType::ALL.iter().map(KeyType::name).collect();
+          format!(
+            "Unsupported key type {s:?}. Supported types: {}",
+            names.join(", ")
+          )
+        })?,
+    };
+    Ok(key_type)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormat {
+  /// PKCS8 private key and SPKI public key
+  Pem,
+  Der,
+  Jwk,
+  /// public keys only
+  Jwks,
+}
+
+impl KeyFormat {
+  pub const ALL: [KeyFormat; 4] = [
+    KeyFormat::Pem,
+    KeyFormat::Der,
+    KeyFormat::Jwk,
+    KeyFormat::Jwks,
+  ];
+}
+
+impl fmt::Display for KeyFormat {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let name = match self {
+      KeyFormat::Pem => "pem",
+      KeyFormat::Der => "der",
+      KeyFormat::Jwk => "jwk",
+      KeyFormat::Jwks => "jwks",
+    };
+    write!(f, "{name}")
+  }
+}
+
+impl FromStr for KeyFormat {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    KeyFormat::ALL
+      .into_iter()
+      .find(|f| f.to_string() == s.to_lowercase())
+      .ok_or_else(|| {
+        format!("Unsupported key format {s:?}. Supported formats: pem, der, jwk, jwks")
+      })
+  }
+}
+
+enum KeyMaterial {
+  Symmetric(Vec<u8>),
+  Rsa(Box<RsaPrivateKey>),
+  P256(p256::SecretKey),
+  P384(p384::SecretKey),
+  Ed25519(SigningKey),
+}
+
+pub struct KeyPair {
+  pub key_type: KeyType,
+  pub kid: String,
+  key: KeyMaterial,
+}
+
+impl KeyPair {
+  /// generate a key, the `kid` defaults to the RFC 7638 thumbprint
+  pub fn generate(key_type: KeyType, kid: Option<String>) -> JWTResult<Self> {
+    let key = match key_type {
+      KeyType::Hs256 | KeyType::Hs384 | KeyType::Hs512 => {
+        // as long as the hash output, see RFC 7518 section 3.2
+        let len = match key_type {
+          KeyType::Hs256 => 32,
+          KeyType::Hs384 => 48,
+          _ => 64,
+        };
+        let mut secret = vec![0u8; len];
+        OsRng.fill_bytes(&mut secret);
+        KeyMaterial::Symmetric(secret)
+      }
+      KeyType::Rsa2048 | KeyType::Rsa3072 | KeyType::Rsa4096 => {
+        let bits = match key_type {
+          KeyType::Rsa2048 => 2048,
+          KeyType::Rsa3072 => 3072,
+          _ => 4096,
+        };
+        let key = RsaPrivateKey::new(&mut OsRng, bits)
+          .map_err(|e| JWTError::Internal(format!("Unable to generate the RSA key: {e}")))?;
+        KeyMaterial::Rsa(Box::new(key))
+      }
+      KeyType::P256 => KeyMaterial::P256(p256::SecretKey::random(&mut OsRng)),
+      KeyType::P384 => KeyMaterial::P384(p384::SecretKey::random(&mut OsRng)),
+      KeyType::Ed25519 => KeyMaterial::Ed25519(SigningKey::generate(&mut OsRng)),
+    };
+    let mut pair = KeyPair {
+      key_type,
+      kid: String::new(),
+      key,
+    };
+    pair.kid = kid.unwrap_or_else(|| thumbprint(&pair.required_members()));
+    Ok(pair)
+  }
+
+  /// read a PKCS8, PKCS1 (RSA) or SEC1 (EC) private key, the `kid` is its RFC 7638 thumbprint
+  pub fn from_private_der(der: &[u8]) -> Option<Self> {
+    let key = if let Ok(key) =
+      RsaPrivateKey::from_pkcs8_der(der).or_else(|_| RsaPrivateKey::from_pkcs1_der(der))
+    {
+      KeyMaterial::Rsa(Box::new(key))
+    } else if let Ok(key) =
+      p256::SecretKey::from_pkcs8_der(der).or_else(|_| p256::SecretKey::from_sec1_der(der))
+    {
+      KeyMaterial::P256(key)
+    } else if let Ok(key) =
+      p384::SecretKey::from_pkcs8_der(der).or_else(|_| p384::SecretKey::from_sec1_der(der))
+    {
+      KeyMaterial::P384(key)
+    } else {
+      KeyMaterial::Ed25519(SigningKey::from_pkcs8_der(der).ok()?)
+    };
+    let key_type = match &key {
+      KeyMaterial::Rsa(key) if key.size() > 384 => KeyType::Rsa4096,
+      KeyMaterial::Rsa(key) if key.size() > 256 => KeyType::Rsa3072,
+      KeyMaterial::Rsa(_) => KeyType::Rsa2048,
+      KeyMaterial::P256(_) => KeyType::P256,
+      KeyMaterial::P384(_) => KeyType::P384,
+      _ => KeyType::Ed25519,
+    };
+    let mut pair = KeyPair {
+      key_type,
+      kid: String::new(),
+      key,
+    };
+    pair.kid = thumbprint(&pair.required_members());
+    Some(pair)
+  }
+
+  /// e.g. `P-256`, RSA keys with their actual size
+  pub fn name(&self) -> String {
+    match &self.key {
+      KeyMaterial::Rsa(key) => format!("RSA-{}", key.size() * 8),
+      _ => self.key_type.name().into(),
+    }
+  }
+
+  /// the algorithms the key can sign with
+  pub fn algorithms(&self) -> Vec<Algorithm> {
+    match &self.key {
+      KeyMaterial::Symmetric(_) => HMAC_ALGORITHMS.to_vec(),
+      KeyMaterial::Rsa(_) => RSA_ALGORITHMS.to_vec(),
+      KeyMaterial::P256(_) => vec![Algorithm::ES256],
+      KeyMaterial::P384(_) => vec![Algorithm::ES384],
+      KeyMaterial::Ed25519(_) => vec![Algorithm::EdDSA],
+    }
+  }
+
+  /// members of the public JWK used for the thumbprint
+  pub fn required_members(&self) -> Value {
+    match &self.key {
+      KeyMaterial::Symmetric(secret) => json!({ "kty": "oct", "k": b64(secret) }),
+      KeyMaterial::Rsa(key) => json!({
+        "kty": "RSA",
+        "n": b64(&key.n().to_bytes_be()),
+        "e": b64(&key.e().to_bytes_be()),
+      }),
+      KeyMaterial::P256(key) => {
+        ec_members("P-256", key.public_key().to_encoded_point(false).as_bytes())
+      }
+      KeyMaterial::P384(key) => {
+        ec_members("P-384", key.public_key().to_encoded_point(false).as_bytes())
+      }
+      KeyMaterial::Ed25519(key) => json!({
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "x": b64(key.verifying_key().as_bytes()),
+      }),
+    }
+  }
+
+  /// the JWK to verify tokens, for HMAC this is the secret itself
+  pub fn public_jwk(&self) -> Value {
+    let mut jwk = self.required_members();
+    jwk["kid"] = self.kid.clone().into();
+    jwk["alg"] = self.key_type.alg().into();
+    jwk["use"] = "sig".into();
+    jwk
+  }
+
+  pub fn private_jwk(&self) -> Value {
+    let mut jwk = self.public_jwk();
+    match &self.key {
+      KeyMaterial::Symmetric(_) => {}
+      KeyMaterial::Rsa(key) => {
+        jwk["d"] = b64(&key.d().to_bytes_be()).into();
+        if let [p, q] = key.primes() {
+          jwk["p"] = b64(&p.to_bytes_be()).into();
+          jwk["q"] = b64(&q.to_bytes_be()).into();
+        }
+        if let (Some(dp), Some(dq), Some(qi)) = (key.dp(), key.dq(), key.crt_coefficient()) {
+          jwk["dp"] = b64(&dp.to_bytes_be()).into();
+          jwk["dq"] = b64(&dq.to_bytes_be()).into();
+          jwk["qi"] = b64(&qi.to_bytes_be()).into();
+        }
+      }
+      KeyMaterial::P256(key) => jwk["d"] = b64(&key.to_bytes()).into(),
+      KeyMaterial::P384(key) => jwk["d"] = b64(&key.to_bytes()).into(),
+      KeyMaterial::Ed25519(key) => jwk["d"] = b64(&key.to_bytes()).into(),
+    }
+    jwk
+  }
+
+  /// PKCS8 private key and SPKI public key as DER, `None` for HMAC secrets
+  fn der_documents(&self) -> JWTResult<Option<(Vec<u8>, Vec<u8>)>> {
+    let (private, public) = match &self.key {
+      KeyMaterial::Symmetric(_) => return Ok(None),
+      KeyMaterial::Rsa(key) => (key.to_pkcs8_der(), key.to_public_key().to_public_key_der()),
+      KeyMaterial::P256(key) => (key.to_pkcs8_der(), key.public_key().to_public_key_der()),
+      KeyMaterial::P384(key) => (key.to_pkcs8_der(), key.public_key().to_public_key_der()),
+      KeyMaterial::Ed25519(key) => (key.to_pkcs8_der(), key.verifying_key().to_public_key_der()),
+    };
+    let encoding_failed = |e: String| JWTError::Internal(format!("Unable to encode the key: {e}"));
+    Ok(Some((
+      private
+        .map_err(|e| encoding_failed(e.to_string()))?
+        .as_bytes()
+        .to_vec(),
+      public
+        .map_err(|e| encoding_failed(e.to_string()))?
+        .as_bytes()
+        .to_vec(),
+    )))
+  }
+
+  /// write the key files with the path prefix `out`, existing files are only replaced with `force`
+  pub fn write(&self, out: &str, formats: &[KeyFormat], force: bool) -> JWTResult<Vec<PathBuf>> {
+    let json = |value: Value| serde_json::to_vec_pretty(&value).unwrap_or_default();
+    let documents = self.der_documents()?;
+    // private files are only readable by the user
+    let mut files: Vec<(String, Vec<u8>, bool)> = vec![];
+    for format in formats {
+      match (format, &documents) {
+        (KeyFormat::Pem, Some((private, public))) => {
+          files.push((format!("{out}.pem"), pem("PRIVATE KEY", private), true));
+          files.push((format!("{out}.pub.pem"), pem("PUBLIC KEY", public), false));
+        }
+        (KeyFormat::Der, Some((private, public))) => {
+          files.push((format!("{out}.der"), private.clone(), true));
+          files.push((format!("{out}.pub.der"), public.clone(), false));
+        }
+        // HMAC secrets have no PEM or DER encoding
+        (KeyFormat::Pem | KeyFormat::Der, None) => {}
+        (KeyFormat::Jwk, _) => {
+          files.push((format!("{out}.jwk"), json(self.private_jwk()), true));
+          if !self.key_type.is_symmetric() {
+            files.push((format!("{out}.pub.jwk"), json(self.public_jwk()), false));
+          }
+        }
+        (KeyFormat::Jwks, _) => {
+          let jwks = json!({ "keys": [self.public_jwk()] });
+          // the JWK of an HMAC secret is the secret
+          let private = self.key_type.is_symmetric();
+          files.push((format!("{out}.jwks.json"), json(jwks), private));
+        }
+      }
+    }
+
+    if !force {
+      if let Some((path, ..)) = files.iter().find(|(path, ..)| fs::metadata(path).is_ok()) {
+        return Err(JWTError::Internal(format!(
+          "The file {path} already exists. Choose another output path"
+        )));
+      }
+    }
+    files
+      .into_iter()
+      .map(|(path, content, private)| {
+        match private {
+          true => write_private_file(&path, &content),
+          false => fs::write(&path, content),
+        }
+        .map(|_| PathBuf::from(&path))
+        .map_err(|e| JWTError::Internal(format!("Unable to write {path}: {e}")))
+      })
+      .collect()
+  }
+
+  /// secret inputs for the encoder and the decoder, the PEM files must be written with the same `out`
+  pub fn secrets(&self, out: &str) -> (String, String) {
+    match &self.key {
+      KeyMaterial::Symmetric(secret) => {
+        let secret = format!("b64:{}", STANDARD.encode(secret));
+        (secret.clone(), secret)
+      }
+      _ => (format!("@{out}.pem"), format!("@{out}.pub.pem")),
+    }
+  }
+}
+
+/// RFC 7638 JWK thumbprint. serde_json orders the members lexicographically and adds no whitespace
+pub(super) fn thumbprint(required_members: &Value) -> String {
+  URL_SAFE_NO_PAD.encode(Sha256::digest(required_members.to_string()))
+}
+
+fn ec_members(crv: &str, point: &[u8]) -> Value {
+  // uncompressed point: 0x04 || x || y
+  let (x, y) = point[1..].split_at((point.len() - 1) / 2);
+  json!({ "kty": "EC", "crv": crv, "x": b64(x), "y": b64(y) })
+}
+
+fn b64(bytes: &[u8]) -> String {
+  URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn pem(label: &str, der: &[u8]) -> Vec<u8> {
+  let body: Vec<String> = STANDARD
+    .encode(der)
+    .as_bytes()
+    .chunks(64)
+    .map(|line| String::from_utf8_lossy(line).into_owned())
+    .collect();
+  format!(
+    "-----BEGIN {label}-----\n{}\n-----END {label}-----\n",
+    body.join("\n")
+  )
+  .into_bytes()
+}
+
+/// key generation dialog
+pub struct Keygen {
+  pub key_types: StatefulTable<KeyType>,
+  /// path prefix of the key files
+  pub output: TextInput,
+  pub blocks: BlockState,
+  /// summary of the last generated key
+  pub message: String,
+}
+
+impl Default for Keygen {
+  fn default() -> Self {
+    Self {
+      key_types: StatefulTable::with_items(KeyType::ALL.to_vec()),
+      output: TextInput::new("./jwtui-key".into()),
+      blocks: BlockState::new(vec![
+        Route {
+          id: RouteId::Keygen,
+          active_block: ActiveBlock::KeygenTypes,
+        },
+        Route {
+          id: RouteId::Keygen,
+          active_block: ActiveBlock::KeygenOutput,
+        },
+      ]),
+      message: String::new(),
+    }
+  }
+}
+
+/// generate a key of the selected type, write all formats and load it into the encoder and decoder
+pub fn generate_keys(app: &mut App) {
+  let keygen = &app.data.keygen;
+  let Some(key_type) = keygen
+    .key_types
+    .state
+    .selected()
+    .and_then(|i| keygen.key_types.items.get(i).copied())
+  else {
+    return;
+  };
+  let out = keygen.output.input.value().trim().to_string();
+
+  let generated = KeyPair::generate(key_type, None).and_then(|pair| {
+    let files = pair.write(&out, &KeyFormat::ALL, false)?;
+    Ok((pair, files))
+  });
+  match generated {
+    Ok((pair, files)) => {
+      load_key_pair(app, &pair, &out);
+      let files: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+      app.data.keygen.message = format!(
+        "Generated {key_type} key with kid {}\n\n{}\n\nThe private key is loaded in the encoder and the public key in the decoder",
+        pair.kid,
+        files.join("\n")
+      );
+      app.data.error = String::new();
+    }
+    Err(e) => app.handle_error(e),
+  }
+}
+
+/// use the key as encoder and decoder secret, and its `alg` and `kid` in the encoder header
+fn load_key_pair(app: &mut App, pair: &KeyPair, out: &str) {
+  let (private, public) = pair.secrets(out);
+  app.data.encoder.secret = TextInput::new(private);
+  app.data.decoder.secret = TextInput::new(public);
+
+  let header = app.data.encoder.header.input.lines().join("\n");
+  let mut header: Map<String, Value> = serde_json::from_str(&header).unwrap_or_default();
+  header.insert("alg".into(), pair.key_type.alg().into());
+  header.insert("kid".into(), pair.kid.clone().into());
+  header.entry("typ").or_insert("JWT".into());
+  let header = serde_json::to_string_pretty(&header).unwrap_or_default();
+  app.data.encoder.header = TextAreaInput::new(header.lines().map(String::from).collect());
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::jwt_encoder::encode_jwt_token;
+
+  fn out_path(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("jwtui-keygen-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name).display().to_string()
+  }
+
+  #[test]
+  fn test_key_type_from_str() {
+    assert_eq!("p-256".parse::<KeyType>().unwrap(), KeyType::P256);
+    assert_eq!("EdDSA".parse::<KeyType>().unwrap(), KeyType::Ed25519);
+    assert_eq!(
+      "RSA-1024".parse::<KeyType>().unwrap_err(),
+      "Unsupported key type \"RSA-1024\". Supported types: HS256, HS384, HS512, RSA-2048, RSA-3072, RSA-4096, P-256, P-384, Ed25519"
+    );
+  }
+
+  #[test]
+  fn test_thumbprint_rfc7638_example() {
+    let jwk = json!({
+      "kty": "RSA",
+      "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+      "e": "AQAB",
+    });
+    assert_eq!(
+      thumbprint(&jwk),
+      "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+    );
+  }
+
+  #[test]
+  fn test_generate_and_write_keys() {
+    for key_type in [
+      KeyType::Hs384,
+      KeyType::P256,
+      KeyType::P384,
+      KeyType::Ed25519,
+    ] {
+      let out = out_path(key_type.name());
+      let pair = KeyPair::generate(key_type, None).unwrap();
+      assert_eq!(pair.kid, thumbprint(&pair.required_members()));
+      assert_eq!(pair.public_jwk()["alg"], key_type.alg());
+
+      let files = pair.write(&out, &KeyFormat::ALL, true).unwrap();
+      let expected = if key_type.is_symmetric() { 2 } else { 7 };
+      assert_eq!(files.len(), expected, "{key_type}");
+      assert!(pair.write(&out, &[KeyFormat::Jwks], false).is_err());
+      // private keys and HMAC secrets are only readable by the user
+      #[cfg(unix)]
+      for file in &files {
+        use std::os::unix::fs::PermissionsExt;
+
+        let name = file.to_string_lossy();
+        if !name.contains(".pub.") && (key_type.is_symmetric() || !name.ends_with(".jwks.json")) {
+          let mode = fs::metadata(file).unwrap().permissions().mode();
+          assert_eq!(mode & 0o077, 0, "{name}");
+        }
+      }
+
+      // sign with the loaded private key and verify with the public key
+      let mut app = App::new(None, "".into());
+      app.data.encoder.payload.input = vec![r#"{"sub": "1234567890"}"#].into();
+      load_key_pair(&mut app, &pair, &out);
+      encode_jwt_token(&mut app);
+      assert_eq!(app.data.error, "", "{key_type}");
+
+      app.data.decoder.encoded = TextInput::new(app.data.encoder.encoded.get_txt());
+      crate::app::jwt_decoder::decode_jwt_token(&mut app, false);
+      assert_eq!(app.data.error, "", "{key_type}");
+      assert!(app.data.decoder.signature_verified);
+    }
+  }
+
+  #[test]
+  fn test_generate_keys_in_dialog() {
+    let mut app = App::new(None, "".into());
+    app.data.keygen.key_types.state.select(Some(6));
+    app.data.keygen.output = TextInput::new(out_path("dialog"));
+    let _ = fs::remove_file(format!("{}.pem", out_path("dialog")));
+
+    generate_keys(&mut app);
+
+    assert_eq!(app.data.error, "");
+    assert!(app
+      .data
+      .keygen
+      .message
+      .starts_with("Generated P-256 key with kid "));
+    assert_eq!(
+      app.data.decoder.secret.input.value(),
+      format!("@{}.pub.pem", out_path("dialog"))
+    );
+    let header: Value =
+      serde_json::from_str(&app.data.encoder.header.input.lines().join("\n")).unwrap();
+    assert_eq!(header["alg"], "ES256");
+
+    // existing files are not replaced
+    generate_keys(&mut app);
+    assert!(app.data.error.contains("already exists"));
+  }
+}
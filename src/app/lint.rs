@@ -0,0 +1,547 @@
+use std::{cmp::Reverse, fmt};
+
+use base64::{
+  engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+  Engine,
+};
+use jsonwebtoken::Algorithm;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::{
+  jwks::is_jwks_url,
+  jwt_decoder::Payload,
+  trust_store::TrustStore,
+  utils::{
+    format_duration, get_secret_from_file_or_input, JWTError, SecretType, KEY_AS_HMAC_SECRET,
+  },
+};
+
+/// lifetimes above this are reported
+const MAX_LIFETIME: i64 = 24 * 60 * 60;
+
+/// single words up to this length are reported as guessable
+const MAX_WORD_LEN: usize = 16;
+
+/// HMAC secrets found in tutorials and default configs, and the most common passwords. Trailing
+/// digits and punctuation are ignored, so `secret123!` matches `secret`
+const COMMON_SECRETS: &[&str] = &[
+  "secret",
+  "password",
+  "passw0rd",
+  "changeme",
+  "changeit",
+  "your-256-bit-secret",
+  "your-384-bit-secret",
+  "your-512-bit-secret",
+  "secretkey",
+  "secret-key",
+  "secret_key",
+  "jwtsecret",
+  "jwt-secret",
+  "jwt_secret",
+  "mysecret",
+  "my-secret",
+  "supersecret",
+  "super-secret",
+  "123456",
+  "12345678",
+  "qwerty",
+  "admin",
+  "test",
+  "key",
+  "default",
+  "keyboard cat",
+  "topsecret",
+  "top-secret",
+  "private",
+  "privatekey",
+  "private-key",
+  "token",
+  "jwt",
+  "jwtkey",
+  "jwt-key",
+  "your_jwt_secret",
+  "shhhhh",
+  "letmein",
+  "welcome",
+  "iloveyou",
+  "abc",
+  "qwertyuiop",
+  "asdf",
+  "monkey",
+  "dragon",
+  "master",
+  "root",
+  "hello",
+  "1q2w3e4r",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+  Info,
+  Warning,
+  Error,
+}
+
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Severity::Info => write!(f, "info"),
+      Severity::Warning => write!(f, "warning"),
+      Severity::Error => write!(f, "error"),
+    }
+  }
+}
+
+/// a risk of the token, independent of its signature being valid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+  /// short name of the rule, e.g. `alg-none`
+  pub rule: &'static str,
+  pub severity: Severity,
+  pub message: String,
+}
+
+impl Finding {
+  fn new(rule: &'static str, severity: Severity, message: impl Into<String>) -> Self {
+    Self {
+      rule,
+      severity,
+      message: message.into(),
+    }
+  }
+}
+
+pub struct LintContext<'a> {
+  /// secret the token is verified with, checked when it's an HMAC secret
+  pub secret: &'a str,
+  /// hosts `jku` and `x5u` may point at, see `trusted_hosts`
+  pub trusted_hosts: Vec<String>,
+  pub leeway: u64,
+  pub now: i64,
+}
+
+/// hosts of the secret and of the trust store keys when they are URLs
+pub fn trusted_hosts(secret: &str, trust_store: Option<&TrustStore>) -> Vec<String> {
+  let keys = trust_store
+    .iter()
+    .flat_map(|store| store.entries.iter())
+    .filter_map(|entry| entry.key.as_deref());
+  std::iter::once(secret)
+    .chain(keys)
+    .filter_map(host_of)
+    .map(String::from)
+    .collect()
+}
+
+/// lint the raw header of a JWS or JWE and the claims, which are read from the token when not given.
+/// the raw header is used as `alg: none` and unknown fields like `crit` can't be parsed into a `Header`
+pub fn lint_token(token: &str, claims: Option<&Payload>, ctx: &LintContext<'_>) -> Vec<Finding> {
+  let mut parts = token.trim().split('.');
+  let Some(header) = parts.next().and_then(decode_part) else {
+    return vec![];
+  };
+  let claims = match claims {
+    Some(claims) => claims.clone(),
+    // claims of an unverifiable token, e.g. `alg: none`
+    None => parts
+      .next()
+      .and_then(decode_part)
+      .and_then(|claims| serde_json::from_value(Value::Object(claims)).ok())
+      .unwrap_or_else(|| Payload(Default::default())),
+  };
+  lint(&header, &claims, ctx)
+}
+
+/// print the findings as a table or as JSON, returns whether any of them is an error
+pub fn print_findings(findings: &[Finding], json: bool) -> bool {
+  match json {
+    true => println!("{}", serde_json::to_string_pretty(findings).unwrap()),
+    false if findings.is_empty() => println!("No findings"),
+    false => {
+      for f in findings {
+        println!("{:<8} {:<14} {}", f.severity.to_string(), f.rule, f.message);
+      }
+    }
+  }
+  findings.iter().any(|f| f.severity == Severity::Error)
+}
+
+fn decode_part(part: &str) -> Option<Map<String, Value>> {
+  serde_json::from_slice(&URL_SAFE_NO_PAD.decode(part).ok()?).ok()
+}
+
+/// every finding of the header and claims, errors first
+pub fn lint(header: &Map<String, Value>, claims: &Payload, ctx: &LintContext<'_>) -> Vec<Finding> {
+  let mut findings = vec![];
+  findings.extend(lint_alg(header));
+  findings.extend(lint_secret(header, ctx.secret));
+  findings.extend(lint_lifetime(claims, ctx));
+  for name in ["jku", "x5u"] {
+    findings.extend(lint_url_header(header, name, ctx));
+  }
+  if header.contains_key("jwk") {
+    findings.push(Finding::new(
+      "embedded-jwk",
+      Severity::Error,
+      "The header embeds a `jwk`. Never verify a token with a key it carries",
+    ));
+  }
+  findings.extend(lint_kid(header));
+  if let Some(crit) = header.get("crit") {
+    findings.push(Finding::new(
+      "crit",
+      Severity::Warning,
+      format!("The header marks {crit} as critical extensions, which aren't processed"),
+    ));
+  }
+  findings.sort_by_key(|f| Reverse(f.severity));
+  findings
+}
+
+fn lint_alg(header: &Map<String, Value>) -> Option<Finding> {
+  let alg = header.get("alg").and_then(Value::as_str)?;
+  alg.eq_ignore_ascii_case("none").then(|| {
+    Finding::new(
+      "alg-none",
+      Severity::Error,
+      "The token isn't signed (`alg: none`), anyone can forge it",
+    )
+  })
+}
+
+fn lint_secret(header: &Map<String, Value>, secret: &str) -> Option<Finding> {
+  let alg: Algorithm = serde_json::from_value(header.get("alg")?.clone()).ok()?;
+  let hash_size = match alg {
+    Algorithm::HS256 => 32,
+    Algorithm::HS384 => 48,
+    Algorithm::HS512 => 64,
+    _ => return None,
+  };
+  if secret.is_empty() {
+    return None;
+  }
+  let bytes = match get_secret_from_file_or_input(&alg, secret) {
+    (Ok(bytes), SecretType::Plain) => bytes,
+    (Ok(bytes), SecretType::B64) => STANDARD.decode(bytes).ok()?,
+    (Err(JWTError::Internal(e)), SecretType::Plain) if e == KEY_AS_HMAC_SECRET => {
+      return Some(Finding::new(
+        "key-confusion",
+        Severity::Error,
+        format!("The token uses {alg:?} with a PEM or DER key as secret, it can be forged with the public key"),
+      ))
+    }
+    // keys of a JWK or JWKS are usually generated
+    _ => return None,
+  };
+  let text = String::from_utf8_lossy(&bytes).trim().to_lowercase();
+  if let Some(kind) = guessable(&text) {
+    return Some(Finding::new(
+      "weak-secret",
+      Severity::Error,
+      format!("The HMAC secret is {kind} and can be guessed"),
+    ));
+  }
+  (bytes.len() < hash_size).then(|| {
+    Finding::new(
+      "weak-secret",
+      Severity::Warning,
+      format!(
+        "The HMAC secret has {} bytes, less than the {hash_size} bytes of the {alg:?} hash",
+        bytes.len()
+      ),
+    )
+  })
+}
+
+/// a common password, or a single word with optional trailing digits. This is a heuristic, not a
+/// full dictionary, words joined with separators or digits in between aren't caught
+fn guessable(text: &str) -> Option<&'static str> {
+  let stem = text.trim_end_matches(|c: char| c.is_ascii_digit() || c.is_ascii_punctuation());
+  if COMMON_SECRETS.contains(&text) || COMMON_SECRETS.contains(&stem) {
+    return Some("a common password");
+  }
+  (!stem.is_empty() && stem.len() <= MAX_WORD_LEN && stem.chars().all(char::is_alphabetic))
+    .then_some("a dictionary word")
+}
+
+fn lint_lifetime(claims: &Payload, ctx: &LintContext<'_>) -> Vec<Finding> {
+  let mut findings = vec![];
+  let (exp, iat) = (claims.timestamp("exp"), claims.timestamp("iat"));
+  match exp {
+    None => findings.push(Finding::new(
+      "missing-exp",
+      Severity::Warning,
+      "The token has no `exp` claim and never expires",
+    )),
+    Some(exp) => {
+      // crafted claims can be anywhere in the i64 range
+      let lifetime = exp.saturating_sub(iat.unwrap_or(ctx.now));
+      if lifetime > MAX_LIFETIME {
+        findings.push(Finding::new(
+          "long-lifetime",
+          Severity::Warning,
+          format!(
+            "The token is valid for {}, more than {}",
+            format_duration(lifetime as u64),
+            format_duration(MAX_LIFETIME as u64)
+          ),
+        ));
+      }
+    }
+  }
+  let latest = ctx
+    .now
+    .saturating_add(i64::try_from(ctx.leeway).unwrap_or(i64::MAX));
+  if let Some(iat) = iat.filter(|iat| *iat > latest) {
+    findings.push(Finding::new(
+      "iat-future",
+      Severity::Warning,
+      format!(
+        "The token was issued {} in the future",
+        format_duration(iat.abs_diff(ctx.now))
+      ),
+    ));
+  }
+  findings
+}
+
+/// keys must never be fetched from a URL of the token, unless it's a known host. The `iss` claim
+/// isn't trusted, it's set by whoever signed the token
+fn lint_url_header(
+  header: &Map<String, Value>,
+  name: &'static str,
+  ctx: &LintContext<'_>,
+) -> Option<Finding> {
+  let url = header.get(name)?.as_str().unwrap_or_default();
+  if !url.starts_with("https://") {
+    return Some(Finding::new(
+      name,
+      Severity::Error,
+      format!("`{name}` isn't an https URL: {url:?}"),
+    ));
+  }
+  let host = host_of(url).unwrap_or_default();
+  let trusted = ctx.trusted_hosts.iter().any(|h| h == host);
+  Some(match trusted {
+    true => Finding::new(
+      name,
+      Severity::Info,
+      format!("`{name}` points at the trusted host {host}"),
+    ),
+    false => Finding::new(
+      name,
+      Severity::Error,
+      format!("`{name}` points at the untrusted host {host}"),
+    ),
+  })
+}
+
+fn lint_kid(header: &Map<String, Value>) -> Option<Finding> {
+  let kid = header.get("kid")?.as_str()?;
+  let traversal = ["..", "/", "\\"].iter().any(|p| kid.contains(p));
+  let sql = ["'", "\"", ";", "--", "/*", "\0"]
+    .iter()
+    .any(|p| kid.contains(p));
+  (traversal || sql).then(|| {
+    Finding::new(
+      "kid-injection",
+      Severity::Error,
+      format!("`kid` contains path traversal or SQL characters: {kid:?}"),
+    )
+  })
+}
+
+/// host of an http(s) URL
+fn host_of(url: &str) -> Option<&str> {
+  if !is_jwks_url(url) {
+    return None;
+  }
+  let rest = url.split_once("://")?.1;
+  let authority = rest.split(['/', '?', '#']).next()?;
+  let host = authority.rsplit('@').next()?;
+  let host = host.split(':').next()?;
+  Some(host).filter(|h| !h.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+  use jsonwebtoken::{encode, EncodingKey, Header};
+  use serde_json::json;
+
+  use super::*;
+
+  const NOW: i64 = 1700000000;
+
+  fn ctx(secret: &str) -> LintContext<'_> {
+    LintContext {
+      secret,
+      trusted_hosts: vec!["keys.example.com".into()],
+      leeway: 60,
+      now: NOW,
+    }
+  }
+
+  fn rules(findings: &[Finding]) -> Vec<(&str, Severity)> {
+    findings.iter().map(|f| (f.rule, f.severity)).collect()
+  }
+
+  fn b64(value: Value) -> String {
+    URL_SAFE_NO_PAD.encode(value.to_string())
+  }
+
+  #[test]
+  fn test_lint_safe_token() {
+    let token = encode(
+      &Header::default(),
+      &json!({ "iat": NOW, "exp": NOW + 3600 }),
+      &EncodingKey::from_secret(&[7; 32]),
+    )
+    .unwrap();
+
+    assert_eq!(
+      lint_token(
+        &token,
+        None,
+        &ctx("b64:BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=")
+      ),
+      vec![]
+    );
+  }
+
+  #[test]
+  fn test_lint_risky_token() {
+    let header = json!({
+      "alg": "none",
+      "kid": "../../dev/null",
+      "jku": "https://attacker.example.org/jwks.json",
+      "x5u": "https://keys.example.com/cert.pem",
+      "jwk": { "kty": "oct", "k": "c2VjcmV0" },
+      "crit": ["exp"]
+    });
+    // the issuer is chosen by the attacker too
+    let claims =
+      json!({ "iss": "https://attacker.example.org", "sub": "admin", "iat": NOW + 3600 });
+    let token = format!("{}.{}.", b64(header), b64(claims));
+
+    let findings = lint_token(&token, None, &ctx(""));
+    assert_eq!(
+      rules(&findings),
+      vec![
+        ("alg-none", Severity::Error),
+        ("jku", Severity::Error),
+        ("embedded-jwk", Severity::Error),
+        ("kid-injection", Severity::Error),
+        ("missing-exp", Severity::Warning),
+        ("iat-future", Severity::Warning),
+        ("crit", Severity::Warning),
+        ("x5u", Severity::Info),
+      ]
+    );
+    assert_eq!(
+      findings[1].message,
+      "`jku` points at the untrusted host attacker.example.org"
+    );
+    assert_eq!(findings[5].message, "The token was issued 1h in the future");
+  }
+
+  #[test]
+  fn test_lint_weak_secret_and_lifetime() {
+    let header = json!({ "alg": "HS256", "kid": "key-1' OR '1'='1" });
+    let claims: Payload =
+      serde_json::from_value(json!({ "iat": NOW, "exp": NOW + 30 * 86400 })).unwrap();
+    let header = header.as_object().unwrap();
+
+    assert_eq!(
+      rules(&lint(header, &claims, &ctx("secret"))),
+      vec![
+        ("weak-secret", Severity::Error),
+        ("kid-injection", Severity::Error),
+        ("long-lifetime", Severity::Warning),
+      ]
+    );
+    let findings = lint(header, &claims, &ctx("a-longer-but-short-secret"));
+    assert_eq!(
+      findings[1].message,
+      "The HMAC secret has 25 bytes, less than the 32 bytes of the HS256 hash"
+    );
+    assert_eq!(
+      findings[2].message,
+      "The token is valid for 30d, more than 1d"
+    );
+    let findings = lint(
+      header,
+      &claims,
+      &ctx("@./test_data/test_rsa_public_key.pem"),
+    );
+    assert_eq!(findings[0].rule, "key-confusion");
+    assert_eq!(
+      findings[0].message,
+      "The token uses HS256 with a PEM or DER key as secret, it can be forged with the public key"
+    );
+
+    for (secret, message) in [
+      (
+        "Secret123!",
+        "The HMAC secret is a common password and can be guessed",
+      ),
+      (
+        "sunshine",
+        "The HMAC secret is a dictionary word and can be guessed",
+      ),
+      (
+        "Elephant2024",
+        "The HMAC secret is a dictionary word and can be guessed",
+      ),
+    ] {
+      let findings = lint(header, &claims, &ctx(secret));
+      assert_eq!(findings[0].rule, "weak-secret");
+      assert_eq!(findings[0].severity, Severity::Error);
+      assert_eq!(findings[0].message, message);
+    }
+  }
+
+  #[test]
+  fn test_lint_extreme_times() {
+    let header = json!({ "alg": "none" });
+    let header = header.as_object().unwrap();
+    let claims: Payload =
+      serde_json::from_value(json!({ "iat": i64::MIN, "exp": i64::MAX })).unwrap();
+    let findings = lint(header, &claims, &ctx(""));
+    assert_eq!(findings[1].rule, "long-lifetime");
+
+    let claims: Payload =
+      serde_json::from_value(json!({ "iat": i64::MAX, "exp": i64::MAX })).unwrap();
+    let findings = lint(
+      header,
+      &claims,
+      &LintContext {
+        leeway: u64::MAX,
+        now: 0,
+        ..ctx("")
+      },
+    );
+    assert_eq!(rules(&findings), vec![("alg-none", Severity::Error)]);
+  }
+
+  #[test]
+  fn test_trusted_hosts() {
+    let store: TrustStore = r#"
+      [[issuer]]
+      iss = "https://auth.example.com/"
+      key = "https://auth.example.com/.well-known/jwks.json"
+
+      [[issuer]]
+      iss = "dev-*"
+      key = "@./key.pem"
+    "#
+    .parse()
+    .unwrap();
+    assert_eq!(
+      trusted_hosts("https://user@idp.example.com:8443/path", Some(&store)),
+      vec!["idp.example.com", "auth.example.com"]
+    );
+  }
+}
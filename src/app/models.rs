@@ -62,6 +62,12 @@ impl<T> StatefulTable<T> {
   }
 }
 
+impl<T> Default for StatefulTable<T> {
+  fn default() -> Self {
+    StatefulTable::new()
+  }
+}
+
 impl<T> Scrollable for StatefulTable<T> {
   fn scroll_down(&mut self, increment: usize) {
     if let Some(i) = self.state.selected() {
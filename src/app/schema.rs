@@ -0,0 +1,455 @@
+use std::{
+  fmt, fs,
+  path::{Path, PathBuf},
+};
+
+use glob::Pattern;
+use jsonschema::{error::ValidationErrorKind, Validator};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::utils::{JWTError, JWTResult};
+
+/// maps token types and issuers to the JSON Schema of their claims, e.g.
+///
+/// ```toml
+/// [[schema]]
+/// typ = "at+jwt"
+/// path = "access-token.schema.json"
+///
+/// [[schema]]
+/// iss = "https://login.*.example.com/"
+/// path = "~/schemas/id-token.schema.json"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schemas {
+  #[serde(default, rename = "schema")]
+  pub entries: Vec<SchemaEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaEntry {
+  /// `typ` header of the token, case insensitive and with or without `application/`
+  pub typ: Option<String>,
+  /// glob pattern matched against the `iss` claim
+  pub iss: Option<String>,
+  /// schema file, relative to the config file
+  pub path: String,
+  /// content of the schema file, read when the config is loaded
+  #[serde(skip)]
+  pub schema: Value,
+  /// the schema compiled when the config is loaded
+  #[serde(skip)]
+  validator: Option<Validator>,
+}
+
+impl PartialEq for SchemaEntry {
+  fn eq(&self, other: &Self) -> bool {
+    (&self.typ, &self.iss, &self.path, &self.schema)
+      == (&other.typ, &other.iss, &other.path, &other.schema)
+  }
+}
+
+/// a value of the claims that doesn't match the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+  /// JSON pointer of the value, e.g. `/address/country`
+  pub pointer: String,
+  pub message: String,
+}
+
+impl Schemas {
+  /// `~/.config/jwtui/schemas.toml` or the platform equivalent
+  pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("jwtui").join("schemas.toml"))
+  }
+
+  /// load the given schemas config, or the one at the default path if it exists
+  pub fn load(path: Option<&Path>) -> JWTResult<Option<Schemas>> {
+    let path = match (path, Self::default_path()) {
+      (Some(path), _) => path.to_path_buf(),
+      (None, Some(path)) if path.exists() => path,
+      _ => return Ok(None),
+    };
+    let content = fs::read_to_string(&path).map_err(|e| {
+      JWTError::Internal(format!(
+        "Unable to read schemas config {}: {e}",
+        path.display()
+      ))
+    })?;
+    let mut schemas = content
+      .parse::<Schemas>()
+      .map_err(|e| JWTError::Internal(format!("Invalid schemas config {}: {e}", path.display())))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    for entry in &mut schemas.entries {
+      (entry.schema, entry.validator) = read_schema(&dir.join(expand_home(&entry.path)))?;
+    }
+    Ok(Some(schemas))
+  }
+
+  /// a single schema used for every token
+  pub fn from_file(path: &Path) -> JWTResult<Schemas> {
+    let (schema, validator) = read_schema(path)?;
+    Ok(Schemas {
+      entries: vec![SchemaEntry {
+        typ: None,
+        iss: None,
+        path: path.display().to_string(),
+        schema,
+        validator,
+      }],
+    })
+  }
+
+  /// first entry matching the `typ` header and the `iss` claim of a token
+  pub fn find(&self, typ: Option<&str>, claims: &Value) -> Option<&SchemaEntry> {
+    let iss = claims.get("iss").and_then(Value::as_str);
+    self.entries.iter().find(|entry| entry.matches(typ, iss))
+  }
+}
+
+impl std::str::FromStr for Schemas {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let schemas: Schemas = toml::from_str(s).map_err(|e| e.message().to_string())?;
+    for iss in schemas
+      .entries
+      .iter()
+      .filter_map(|entry| entry.iss.as_ref())
+    {
+      Pattern::new(iss).map_err(|e| format!("invalid `iss` pattern {iss:?}: {e}"))?;
+    }
+    Ok(schemas)
+  }
+}
+
+impl SchemaEntry {
+  /// entries without `typ` and `iss` match every token
+  fn matches(&self, typ: Option<&str>, iss: Option<&str>) -> bool {
+    let typ_matches = match (&self.typ, typ) {
+      (None, _) => true,
+      (Some(expected), Some(typ)) => media_type(expected).eq_ignore_ascii_case(media_type(typ)),
+      (Some(_), None) => false,
+    };
+    let iss_matches = match (&self.iss, iss) {
+      (None, _) => true,
+      (Some(pattern), Some(iss)) => Pattern::new(pattern).is_ok_and(|p| p.matches(iss)),
+      (Some(_), None) => false,
+    };
+    typ_matches && iss_matches
+  }
+
+  /// violations of the claims, empty when they match the schema
+  pub fn validate(&self, claims: &Value) -> Vec<SchemaViolation> {
+    match &self.validator {
+      Some(validator) => violations(validator, claims),
+      None => validate(&self.schema, claims),
+    }
+  }
+
+  /// file name of the schema
+  pub fn name(&self) -> String {
+    Path::new(&self.path)
+      .file_name()
+      .map_or(self.path.clone(), |name| name.to_string_lossy().into())
+  }
+}
+
+impl fmt::Display for SchemaViolation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.pointer.as_str() {
+      "" => write!(f, "(root) {}", self.message),
+      pointer => write!(f, "{pointer} {}", self.message),
+    }
+  }
+}
+
+/// `application/at+jwt` and `at+jwt` are the same type, see RFC 7515 section 4.1.9
+fn media_type(typ: &str) -> &str {
+  match typ.get(..12) {
+    Some(prefix) if prefix.eq_ignore_ascii_case("application/") => &typ[12..],
+    _ => typ,
+  }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+  match (path.strip_prefix("~/"), dirs::home_dir()) {
+    (Some(path), Some(home)) => home.join(path),
+    _ => PathBuf::from(path),
+  }
+}
+
+/// the schema and its compiled validator
+fn read_schema(path: &Path) -> JWTResult<(Value, Option<Validator>)> {
+  let content = fs::read_to_string(path)
+    .map_err(|e| JWTError::Internal(format!("Unable to read schema {}: {e}", path.display())))?;
+  let invalid = |e: String| JWTError::Internal(format!("Invalid schema {}: {e}", path.display()));
+  let schema: Value = serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+  match schema {
+    Value::Object(_) | Value::Bool(_) => {
+      let validator = compile(&schema).map_err(invalid)?;
+      Ok((schema, Some(validator)))
+    }
+    _ => Err(invalid("expected an object".into())),
+  }
+}
+
+/// a JSON Schema compiled with the `jsonschema` crate. `format` is an annotation, as in draft
+/// 2020-12, and only `$ref`s within the schema are resolved
+fn compile(schema: &Value) -> Result<Validator, String> {
+  jsonschema::options()
+    .should_validate_formats(false)
+    .build(schema)
+    .map_err(|e| e.to_string())
+}
+
+/// validate a value against a JSON Schema
+pub fn validate(schema: &Value, value: &Value) -> Vec<SchemaViolation> {
+  match compile(schema) {
+    Ok(validator) => violations(&validator, value),
+    Err(e) => vec![SchemaViolation {
+      pointer: String::new(),
+      message: format!("can't be checked, the schema is invalid: {e}"),
+    }],
+  }
+}
+
+fn violations(validator: &Validator, value: &Value) -> Vec<SchemaViolation> {
+  validator
+    .iter_errors(value)
+    .map(|e| {
+      let pointer = e.instance_path().to_string();
+      match e.kind() {
+        // reported at the pointer of the missing property
+        ValidationErrorKind::Required {
+          property: Value::String(name),
+        } => SchemaViolation {
+          pointer: child_pointer(&pointer, name),
+          message: "is required".into(),
+        },
+        // the value isn't repeated, claims can be long
+        _ => SchemaViolation {
+          pointer,
+          message: e.masked().to_string(),
+        },
+      }
+    })
+    .collect()
+}
+
+/// escape `~` and `/` in a property name, see RFC 6901
+fn child_pointer(pointer: &str, name: &str) -> String {
+  format!("{pointer}/{}", name.replace('~', "~0").replace('/', "~1"))
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  fn messages(schema: Value, value: Value) -> Vec<String> {
+    validate(&schema, &value)
+      .iter()
+      .map(ToString::to_string)
+      .collect()
+  }
+
+  #[test]
+  fn test_validate_claims() {
+    let schema = json!({
+      "type": "object",
+      "required": ["sub", "exp", "scope"],
+      "properties": {
+        "iss": { "const": "https://login.example.com/" },
+        "sub": { "type": "string" },
+        "scope": { "type": "string" },
+        "exp": { "type": "integer" },
+        "aud": { "type": ["string", "array"], "items": { "type": "string" } },
+        "roles": { "type": "array", "uniqueItems": true, "maxItems": 2 },
+        "tenant": { "$ref": "#/$defs/tenant" }
+      },
+      "additionalProperties": false,
+      "$defs": {
+        "tenant": {
+          "type": "object",
+          "properties": { "id": { "type": "string", "minLength": 3 } },
+          "required": ["id"]
+        }
+      }
+    });
+
+    assert!(messages(
+      schema.clone(),
+      json!({
+        "iss": "https://login.example.com/",
+        "sub": "user-1",
+        "exp": 1700000000,
+        "scope": "read",
+        "aud": ["api"],
+        "tenant": { "id": "acme" }
+      })
+    )
+    .is_empty());
+
+    assert_eq!(
+      messages(
+        schema,
+        json!({
+          "iss": "https://other.com/",
+          "exp": "2024-01-18T14:32:00Z",
+          "aud": ["api", 1],
+          "roles": ["admin", "admin", "user"],
+          "tenant": { "id": "a", "name~/x": "b" },
+          "foo": true
+        })
+      ),
+      vec![
+        "/sub is required",
+        "/scope is required",
+        r#"/aud/1 value is not of type "string""#,
+        r#"/exp value is not of type "integer""#,
+        r#"/iss "https://login.example.com/" was expected"#,
+        "/roles value has more than 2 items",
+        "/roles value has non-unique elements",
+        "/tenant/id value is shorter than 3 characters",
+        "(root) Additional properties are not allowed ('foo' was unexpected)",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_validate_combinators() {
+    let schema = json!({
+      "anyOf": [{ "required": ["email"] }, { "required": ["phone"] }],
+      "oneOf": [{ "required": ["a"] }, { "required": ["b"] }],
+      "not": { "required": ["password"] },
+      "if": { "properties": { "typ": { "const": "admin" } }, "required": ["typ"] },
+      "then": { "required": ["mfa"] },
+      "dependentRequired": { "email": ["email_verified"] },
+      "properties": {
+        "level": { "enum": [1, 2, 3] },
+        "ratio": { "type": "number", "exclusiveMinimum": 0, "maximum": 1, "multipleOf": 0.25 }
+      }
+    });
+
+    assert_eq!(
+      messages(
+        schema.clone(),
+        json!({ "a": 1, "b": 2, "password": "x", "typ": "admin", "level": 4, "ratio": 0.3 })
+      ),
+      vec![
+        "/level value is not one of 1, 2 or 3",
+        "/ratio value is not a multiple of 0.25",
+        "(root) value is not valid under any of the schemas listed in the 'anyOf' keyword",
+        "(root) value is valid under more than one of the schemas listed in the 'oneOf' keyword",
+        r#"(root) {"required":["password"]} is not allowed for value"#,
+        "/mfa is required",
+      ]
+    );
+    assert_eq!(
+      messages(schema, json!({ "email": "a@b.c", "a": 1, "ratio": 0 })),
+      vec![
+        "/email_verified is required",
+        "/ratio value is less than or equal to the minimum of 0",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_validate_patterns_and_formats() {
+    let schema = json!({
+      "properties": {
+        "iss": { "type": "string", "format": "uri" },
+        "sub": { "pattern": "^user-" }
+      },
+      "patternProperties": { "^x-": { "type": "string" } },
+      "propertyNames": { "maxLength": 7 },
+      "dependentSchemas": { "email": { "required": ["email_verified"] } }
+    });
+
+    // `format` is an annotation
+    assert!(messages(
+      schema.clone(),
+      json!({ "iss": "not a uri", "sub": "user-1" })
+    )
+    .is_empty());
+    assert_eq!(
+      messages(
+        schema,
+        json!({ "sub": "admin", "x-a": 1, "email": "a", "too-long": 1 })
+      ),
+      vec![
+        r#"/sub value does not match "^user-""#,
+        r#"/x-a value is not of type "string""#,
+        r#"(root) "too-long" is longer than 7 characters"#,
+        "/email_verified is required",
+      ]
+    );
+    assert!(messages(
+      json!({ "$ref": "https://example.com/schema.json" }),
+      json!({})
+    )[0]
+      .starts_with("(root) can't be checked, the schema is invalid: Resource 'https://example.com/schema.json'"));
+  }
+
+  #[test]
+  fn test_find_schema() {
+    let schemas: Schemas = r#"
+[[schema]]
+typ = "at+jwt"
+path = "access.json"
+
+[[schema]]
+iss = "https://login.*.example.com/"
+path = "id.json"
+
+[[schema]]
+path = "default.json"
+"#
+    .parse()
+    .unwrap();
+
+    let find = |typ, claims| schemas.find(typ, &claims).unwrap().path.as_str();
+    assert_eq!(find(Some("application/AT+JWT"), json!({})), "access.json");
+    assert_eq!(
+      find(
+        Some("JWT"),
+        json!({ "iss": "https://login.staging.example.com/" })
+      ),
+      "id.json"
+    );
+    assert_eq!(find(None, json!({ "iss": "other" })), "default.json");
+
+    assert_eq!(
+      "[[schema]]\niss = \"[a\"\npath = \"a.json\""
+        .parse::<Schemas>()
+        .unwrap_err(),
+      r#"invalid `iss` pattern "[a": Pattern syntax error near position 0: invalid range pattern"#
+    );
+    assert!("[[schema]]\ntyp = \"JWT\"".parse::<Schemas>().is_err());
+  }
+
+  #[test]
+  fn test_load_schema() {
+    let schemas = Schemas::from_file(Path::new("./test_data/test_claims_schema.json")).unwrap();
+    assert_eq!(schemas.entries[0].name(), "test_claims_schema.json");
+    assert_eq!(
+      schemas.entries[0].validate(&json!({ "sub": 1, "exp": 1700000000 })),
+      vec![SchemaViolation {
+        pointer: "/sub".into(),
+        message: r#"value is not of type "string""#.into(),
+      }]
+    );
+    assert_eq!(
+      Schemas::from_file(Path::new("./test_data/test_rsa_public_key.pem")).unwrap_err(),
+      JWTError::Internal(
+        "Invalid schema ./test_data/test_rsa_public_key.pem: invalid number at line 1 column 2"
+          .into()
+      )
+    );
+  }
+}
@@ -0,0 +1,481 @@
+use std::{env, fs, path::PathBuf, str::FromStr};
+
+use rand::{rngs::OsRng, RngCore};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::{
+  models::StatefulTable,
+  utils::{JWTError, JWTResult},
+  App, RouteId, TextAreaInput,
+};
+
+/// a header and payload to start a token from, with placeholders expanded when encoding
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  /// the `alg` and `kid` of the encoder are kept when it has none
+  #[serde(default = "default_header")]
+  pub header: String,
+  pub payload: String,
+}
+
+impl Template {
+  fn new(name: &str, description: &str, header: &str, payload: &str) -> Self {
+    Self {
+      name: name.into(),
+      description: description.into(),
+      header: header.into(),
+      payload: payload.into(),
+    }
+  }
+}
+
+fn default_header() -> String {
+  "{\n  \"typ\": \"JWT\"\n}".into()
+}
+
+/// built-in templates, listed before the user's
+fn presets() -> Vec<Template> {
+  vec![
+    Template::new(
+      "OIDC ID token",
+      "OpenID Connect ID token of a signed-in user",
+      &default_header(),
+      r#"{
+  "iss": "https://issuer.example.com/",
+  "sub": "248289761001",
+  "aud": "client-id",
+  "exp": {{now+1h}},
+  "iat": {{now}},
+  "auth_time": {{now}},
+  "nonce": "{{uuid}}",
+  "name": "Jane Doe",
+  "email": "jane.doe@example.com",
+  "email_verified": true
+}"#,
+    ),
+    Template::new(
+      "OAuth access token",
+      "JWT access token of RFC 9068, with the `at+jwt` type",
+      "{\n  \"typ\": \"at+jwt\"\n}",
+      r#"{
+  "iss": "https://issuer.example.com/",
+  "sub": "248289761001",
+  "aud": "https://api.example.com/",
+  "client_id": "client-id",
+  "exp": {{now+1h}},
+  "iat": {{now}},
+  "jti": "{{uuid}}",
+  "scope": "openid profile"
+}"#,
+    ),
+    Template::new(
+      "Service token",
+      "Short-lived token a service calls another service with",
+      &default_header(),
+      r#"{
+  "iss": "service-a",
+  "sub": "service-a",
+  "aud": "service-b",
+  "exp": {{now+5m}},
+  "iat": {{now}},
+  "jti": "{{uuid}}"
+}"#,
+    ),
+    Template::new(
+      "Refresh token",
+      "Long-lived token to get new access tokens with",
+      &default_header(),
+      r#"{
+  "iss": "https://issuer.example.com/",
+  "sub": "248289761001",
+  "aud": "https://issuer.example.com/",
+  "typ": "Refresh",
+  "exp": {{now+30d}},
+  "iat": {{now}},
+  "jti": "{{uuid}}",
+  "scope": "offline_access"
+}"#,
+    ),
+  ]
+}
+
+/// user templates, e.g.
+///
+/// ```toml
+/// [[template]]
+/// name = "staging admin"
+/// header = '{ "alg": "RS256", "kid": "staging" }'
+/// payload = '''
+/// { "sub": "{{env:USER}}", "roles": ["admin"], "exp": {{now+8h}} }
+/// '''
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateFile {
+  #[serde(default, rename = "template")]
+  templates: Vec<Template>,
+}
+
+impl FromStr for TemplateFile {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    toml::from_str(s).map_err(|e| e.message().to_string())
+  }
+}
+
+/// template picker of the encoder
+pub struct Templates {
+  pub items: StatefulTable<Template>,
+}
+
+impl Default for Templates {
+  fn default() -> Self {
+    Self {
+      items: StatefulTable::with_items(presets()),
+    }
+  }
+}
+
+impl Templates {
+  /// `~/.config/jwtui/templates.toml` or the platform equivalent
+  pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("jwtui").join("templates.toml"))
+  }
+
+  /// the presets and the templates at the default path if it exists
+  pub fn load() -> JWTResult<Templates> {
+    let mut templates = Templates::default();
+    let Some(path) = Self::default_path().filter(|path| path.exists()) else {
+      return Ok(templates);
+    };
+    let file = fs::read_to_string(&path)
+      .map_err(|e| e.to_string())
+      .and_then(|content| content.parse::<TemplateFile>())
+      .map_err(|e| JWTError::Internal(format!("Invalid templates {}: {e}", path.display())))?;
+    templates.items.items.extend(file.templates);
+    Ok(templates)
+  }
+
+  pub fn selected(&self) -> Option<&Template> {
+    self.items.items.get(self.items.state.selected()?)
+  }
+}
+
+/// replace the encoder header and payload with the selected template and go back to the encoder
+pub fn apply_selected(app: &mut App) {
+  let Some(template) = app.data.templates.selected() else {
+    return;
+  };
+  let current = app.data.encoder.header.input.lines().join("\n");
+  let header = header_with_key(&template.header, &current);
+  let payload = template.payload.clone();
+
+  app.data.encoder.header = TextAreaInput::new(header.lines().map(String::from).collect());
+  app.data.encoder.payload = TextAreaInput::new(payload.lines().map(String::from).collect());
+  app.data.error = String::new();
+  while app.get_current_route().id == RouteId::Templates && app.pop_navigation_stack().is_some() {}
+}
+
+/// the template header with the `alg` and `kid` of the current header, so the secret still fits
+fn header_with_key(template: &str, current: &str) -> String {
+  let Ok(mut header) = serde_json::from_str::<Map<String, Value>>(template) else {
+    // a header with placeholders is used as is
+    return template.to_string();
+  };
+  let current: Map<String, Value> = serde_json::from_str(current).unwrap_or_default();
+  let mut merged = Map::new();
+  for key in ["alg", "kid"] {
+    let value = header.remove(key).or_else(|| current.get(key).cloned());
+    if let Some(value) = value {
+      merged.insert(key.into(), value);
+    }
+  }
+  merged.entry("alg").or_insert("HS256".into());
+  merged.extend(header);
+  serde_json::to_string_pretty(&merged).unwrap_or_default()
+}
+
+/// whether the text has a `{{...}}` placeholder
+pub fn has_placeholders(text: &str) -> bool {
+  text
+    .find("{{")
+    .is_some_and(|start| text[start..].contains("}}"))
+}
+
+/// expand `{{now}}`, `{{now+1h}}`, `{{now-30m}}`, `{{uuid}}` and `{{env:NAME}}`.
+/// Times are unix seconds and environment variables are escaped to be used in a JSON string
+pub fn expand_placeholders(text: &str, now: i64) -> JWTResult<String> {
+  let mut out = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(start) = rest.find("{{") {
+    let Some(len) = rest[start..].find("}}") else {
+      break;
+    };
+    out.push_str(&rest[..start]);
+    out.push_str(&expand(rest[start + 2..start + len].trim(), now)?);
+    rest = &rest[start + len + 2..];
+  }
+  out.push_str(rest);
+  Ok(out)
+}
+
+fn expand(placeholder: &str, now: i64) -> JWTResult<String> {
+  if placeholder == "uuid" {
+    return Ok(uuid_v4());
+  }
+  if let Some(name) = placeholder.strip_prefix("env:") {
+    let value = env::var(name).map_err(|_| {
+      JWTError::Internal(format!(
+        "Environment variable {name} of the placeholder {{{{{placeholder}}}}} isn't set"
+      ))
+    })?;
+    let quoted = Value::String(value).to_string();
+    return Ok(quoted[1..quoted.len() - 1].to_string());
+  }
+  if let Some(offset) = placeholder.strip_prefix("now") {
+    if let Some(time) = offset_time(now, offset) {
+      return Ok(time.to_string());
+    }
+  }
+  Err(JWTError::Internal(format!(
+    "Unknown placeholder {{{{{placeholder}}}}}. Supported placeholders: {{{{now}}}}, {{{{now+1h}}}}, {{{{now-30m}}}}, {{{{uuid}}}}, {{{{env:NAME}}}}"
+  )))
+}
+
+/// resolve claims like `"exp": "$now+15m"` to unix time and `"jti": "$uuid"` to a random UUID,
+/// `None` when the JSON has no such claims
+pub fn resolve_expressions(json: &str, now: i64) -> Option<String> {
+  let mut value: Value = serde_json::from_str(json).ok()?;
+  resolve_value(&mut value, now).then(|| serde_json::to_string_pretty(&value).ok())?
+}
+
+/// whether the JSON has claims like `"$now+15m"`
+pub fn has_expressions(json: &str) -> bool {
+  fn has(value: &Value) -> bool {
+    match value {
+      Value::String(s) => is_expression(s),
+      Value::Array(items) => items.iter().any(has),
+      Value::Object(map) => map.values().any(has),
+      _ => false,
+    }
+  }
+  serde_json::from_str(json).is_ok_and(|value: Value| has(&value))
+}
+
+/// resolve every expression of the value, returns whether there was one
+fn resolve_value(value: &mut Value, now: i64) -> bool {
+  let children: Vec<&mut Value> = match value {
+    Value::String(s) if is_expression(s) => {
+      *value = match s.as_str() {
+        "$uuid" => uuid_v4().into(),
+        s => match offset_time(now, &s[4..]) {
+          Some(time) => time.into(),
+          // keep offsets too large for a timestamp
+          None => return false,
+        },
+      };
+      return true;
+    }
+    Value::Array(items) => items.iter_mut().collect(),
+    Value::Object(map) => map.values_mut().collect(),
+    _ => return false,
+  };
+  let mut found = false;
+  for child in children {
+    found |= resolve_value(child, now);
+  }
+  found
+}
+
+/// `$uuid`, `$now` or `$now` with an offset like `$now-30s`
+fn is_expression(s: &str) -> bool {
+  s == "$uuid"
+    || s
+      .strip_prefix("$now")
+      .is_some_and(|offset| parse_offset(offset).is_some())
+}
+
+/// `""`, or a signed duration in seconds, minutes, hours, days or weeks like `+1h` or `-30m`.
+/// `None` when it's invalid or doesn't fit in an `i64`
+fn parse_offset(offset: &str) -> Option<i64> {
+  if offset.is_empty() {
+    return Some(0);
+  }
+  let (sign, duration) = match (offset.strip_prefix('+'), offset.strip_prefix('-')) {
+    (Some(duration), _) => (1, duration),
+    (_, Some(duration)) => (-1, duration),
+    _ => return None,
+  };
+  let unit = match duration.chars().last()? {
+    's' => 1,
+    'm' => 60,
+    'h' => 3600,
+    'd' => 86400,
+    'w' => 7 * 86400,
+    _ => return None,
+  };
+  // the unit is a single byte
+  let amount: i64 = duration[..duration.len() - 1].parse().ok()?;
+  amount.checked_mul(unit)?.checked_mul(sign)
+}
+
+/// `now` shifted by the offset, `None` when the offset is invalid or the time overflows
+fn offset_time(now: i64, offset: &str) -> Option<i64> {
+  now.checked_add(parse_offset(offset)?)
+}
+
+/// random UUID of version 4
+fn uuid_v4() -> String {
+  let mut bytes = [0u8; 16];
+  OsRng.fill_bytes(&mut bytes);
+  bytes[6] = (bytes[6] & 0x0f) | 0x40;
+  bytes[8] = (bytes[8] & 0x3f) | 0x80;
+  let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+  format!(
+    "{}-{}-{}-{}-{}",
+    &hex[..8],
+    &hex[8..12],
+    &hex[12..16],
+    &hex[16..20],
+    &hex[20..]
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const NOW: i64 = 1_700_000_000;
+
+  #[test]
+  fn test_expand_placeholders() {
+    assert_eq!(
+      expand_placeholders(
+        r#"{"iat": {{now}}, "exp": {{ now+1h }}, "nbf": {{now-2d}}}"#,
+        NOW
+      )
+      .unwrap(),
+      r#"{"iat": 1700000000, "exp": 1700003600, "nbf": 1699827200}"#
+    );
+    let jti = expand_placeholders("{{uuid}}", NOW).unwrap();
+    assert_eq!(jti.len(), 36);
+    assert_eq!(&jti[14..15], "4");
+    assert_ne!(jti, expand_placeholders("{{uuid}}", NOW).unwrap());
+
+    env::set_var("JWTUI_TEMPLATE_TEST", "a \"quoted\" name");
+    assert_eq!(
+      expand_placeholders(r#""{{env:JWTUI_TEMPLATE_TEST}}""#, NOW).unwrap(),
+      r#""a \"quoted\" name""#
+    );
+    assert_eq!(expand_placeholders("{ {{ }", NOW).unwrap(), "{ {{ }");
+  }
+
+  #[test]
+  fn test_invalid_placeholders() {
+    assert!(expand_placeholders("{{now+1y}}", NOW)
+      .unwrap_err()
+      .to_string()
+      .starts_with("Unknown placeholder {{now+1y}}. Supported placeholders: {{now}}"));
+    assert_eq!(
+      expand_placeholders("{{env:JWTUI_TEMPLATE_UNSET}}", NOW)
+        .unwrap_err()
+        .to_string(),
+      "Environment variable JWTUI_TEMPLATE_UNSET of the placeholder {{env:JWTUI_TEMPLATE_UNSET}} isn't set"
+    );
+  }
+
+  #[test]
+  fn test_presets_expand_to_json() {
+    for template in presets() {
+      let payload = expand_placeholders(&template.payload, NOW).unwrap();
+      let payload: Map<String, Value> = serde_json::from_str(&payload).unwrap();
+      assert_eq!(payload["iat"], NOW, "{}", template.name);
+      assert!(has_placeholders(&template.payload));
+    }
+  }
+
+  #[test]
+  fn test_resolve_expressions() {
+    let payload = r#"{"iat": "$now", "exp": "$now+15m", "nbf": "$now-30s", "jti": "$uuid",
+      "scp": ["$now+1x", "$user"], "act": {"exp": "$now+1d"}}"#;
+    assert!(has_expressions(payload));
+    let resolved: Value =
+      serde_json::from_str(&resolve_expressions(payload, NOW).unwrap()).unwrap();
+    assert_eq!(resolved["iat"], NOW);
+    assert_eq!(resolved["exp"], NOW + 900);
+    assert_eq!(resolved["nbf"], NOW - 30);
+    assert_eq!(resolved["jti"].as_str().unwrap().len(), 36);
+    assert_eq!(resolved["act"]["exp"], NOW + 86400);
+    // other strings starting with `$` are kept
+    assert_eq!(resolved["scp"], serde_json::json!(["$now+1x", "$user"]));
+
+    assert!(!has_expressions(r#"{"sub": "$user"}"#));
+    assert_eq!(resolve_expressions(r#"{"sub": "$user"}"#, NOW), None);
+    assert_eq!(resolve_expressions("not json", NOW), None);
+  }
+
+  #[test]
+  fn test_invalid_offsets() {
+    for offset in [
+      "é",
+      "+1é",
+      "+é",
+      "+",
+      "+99999999999999w",
+      "-99999999999999w",
+    ] {
+      assert_eq!(parse_offset(offset), None, "{offset}");
+    }
+    assert_eq!(offset_time(NOW, &format!("+{}s", i64::MAX)), None);
+
+    let payload = r#"{"a": "$nowé", "b": "$now+99999999999999w"}"#;
+    assert!(!has_expressions(payload));
+    assert!(expand_placeholders("{{nowé}}", NOW).is_err());
+    assert!(expand_placeholders("{{now+99999999999999w}}", NOW).is_err());
+    // a valid offset that overflows the time is kept as it is
+    let payload = format!(r#"{{"exp": "$now+{}s"}}"#, i64::MAX);
+    assert_eq!(resolve_expressions(&payload, NOW), None);
+  }
+
+  #[test]
+  fn test_parse_template_file() {
+    let file: TemplateFile = r#"
+      [[template]]
+      name = "admin"
+      payload = '{ "sub": "{{env:USER}}" }'
+    "#
+    .parse()
+    .unwrap();
+    assert_eq!(
+      file.templates,
+      vec![Template::new(
+        "admin",
+        "",
+        &default_header(),
+        r#"{ "sub": "{{env:USER}}" }"#
+      )]
+    );
+    assert!("[[template]]\nname = \"a\""
+      .parse::<TemplateFile>()
+      .is_err());
+  }
+
+  #[test]
+  fn test_header_keeps_key() {
+    let current = r#"{ "alg": "ES256", "kid": "key-1", "typ": "JWT" }"#;
+    assert_eq!(
+      header_with_key("{\n  \"typ\": \"at+jwt\"\n}", current),
+      "{\n  \"alg\": \"ES256\",\n  \"kid\": \"key-1\",\n  \"typ\": \"at+jwt\"\n}"
+    );
+    assert_eq!(
+      header_with_key(r#"{ "alg": "RS256" }"#, "not json"),
+      "{\n  \"alg\": \"RS256\"\n}"
+    );
+  }
+}
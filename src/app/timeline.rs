@@ -0,0 +1,168 @@
+use super::{jwt_decoder::Payload, utils::format_duration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifetimeStatus {
+  NotYetValid,
+  Valid,
+  /// less than a fifth of the lifetime or a minute is left
+  ExpiringSoon,
+  Expired,
+}
+
+/// `nbf`, `iat`, now and `exp` of a token that expires, shown on one bar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timeline {
+  pub nbf: Option<i64>,
+  pub iat: Option<i64>,
+  pub exp: i64,
+  pub now: i64,
+  /// seconds `exp` and `nbf` are extended by, like the validation
+  pub leeway: i64,
+}
+
+impl Timeline {
+  pub fn new(claims: &Payload, now: i64, leeway: u64) -> Option<Timeline> {
+    Some(Timeline {
+      nbf: claims.timestamp("nbf"),
+      iat: claims.timestamp("iat"),
+      exp: claims.timestamp("exp")?,
+      now,
+      leeway: i64::try_from(leeway).unwrap_or(i64::MAX),
+    })
+  }
+
+  /// the first and last time of the bar
+  pub fn range(&self) -> (i64, i64) {
+    let times = [self.nbf, self.iat, Some(self.exp), Some(self.now)];
+    let times = times.iter().flatten();
+    (
+      *times.clone().min().unwrap_or(&self.now),
+      *times.max().unwrap_or(&self.exp),
+    )
+  }
+
+  /// column of a time on a bar `width` wide
+  pub fn position(&self, time: i64, width: u16) -> u16 {
+    let (start, end) = self.range();
+    if end <= start || width == 0 {
+      return 0;
+    }
+    // the claims can be anywhere in the i64 range, their differences can overflow
+    let ratio = (time as f64 - start as f64) / (end as f64 - start as f64);
+    (ratio * (width - 1) as f64).round() as u16
+  }
+
+  /// the times on the bar, now last as it's drawn over the others
+  pub fn markers(&self) -> Vec<(&'static str, i64)> {
+    let mut markers = vec![];
+    if let Some(nbf) = self.nbf {
+      markers.push(("nbf", nbf));
+    }
+    if let Some(iat) = self.iat {
+      markers.push(("iat", iat));
+    }
+    markers.push(("exp", self.exp));
+    markers.push(("now", self.now));
+    markers
+  }
+
+  pub fn status(&self) -> LifetimeStatus {
+    let start = self.nbf.or(self.iat).unwrap_or(self.now);
+    let remaining = self.exp.saturating_sub(self.now);
+    match self.nbf {
+      Some(nbf) if nbf > self.now.saturating_add(self.leeway) => LifetimeStatus::NotYetValid,
+      _ if self.exp < self.now.saturating_sub(self.leeway) => LifetimeStatus::Expired,
+      // the lifetime of crafted claims doesn't fit in an i64
+      _ if i128::from(remaining) * 5 <= i128::from(self.exp) - i128::from(start)
+        || remaining <= 60 =>
+      {
+        LifetimeStatus::ExpiringSoon
+      }
+      _ => LifetimeStatus::Valid,
+    }
+  }
+
+  /// e.g. `expires in 12m 5s` or `expired 3s ago`
+  pub fn countdown(&self) -> String {
+    let remaining = self.exp.saturating_sub(self.now);
+    let ago = format_duration(self.exp.abs_diff(self.now));
+    match self.status() {
+      LifetimeStatus::NotYetValid => format!(
+        "valid in {}",
+        format_duration(self.nbf.unwrap_or(self.now).abs_diff(self.now))
+      ),
+      LifetimeStatus::Expired => format!("expired {ago} ago"),
+      _ if remaining < 0 => format!("expired {ago} ago, within the {}s leeway", self.leeway),
+      _ => format!("expires in {}", format_duration(remaining as u64)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  const NOW: i64 = 1_700_000_000;
+
+  fn timeline(claims: serde_json::Value, now: i64) -> Option<Timeline> {
+    Timeline::new(&serde_json::from_value(claims).unwrap(), now, 0)
+  }
+
+  #[test]
+  fn test_timeline_without_exp() {
+    assert_eq!(timeline(json!({ "iat": NOW }), NOW), None);
+  }
+
+  #[test]
+  fn test_timeline_status_and_countdown() {
+    let claims = json!({ "iat": NOW - 1800, "exp": NOW + 600 });
+    let valid = timeline(claims.clone(), NOW).unwrap();
+    assert_eq!(valid.status(), LifetimeStatus::Valid);
+    assert_eq!(valid.countdown(), "expires in 10m");
+
+    let soon = timeline(claims.clone(), NOW + 545).unwrap();
+    assert_eq!(soon.status(), LifetimeStatus::ExpiringSoon);
+    assert_eq!(soon.countdown(), "expires in 55s");
+
+    let expired = timeline(claims.clone(), NOW + 601).unwrap();
+    assert_eq!(expired.status(), LifetimeStatus::Expired);
+    assert_eq!(expired.countdown(), "expired 1s ago");
+
+    let mut leeway = expired.clone();
+    leeway.leeway = 60;
+    assert_eq!(leeway.status(), LifetimeStatus::ExpiringSoon);
+    assert_eq!(leeway.countdown(), "expired 1s ago, within the 60s leeway");
+
+    let early = timeline(json!({ "nbf": NOW + 90, "exp": NOW + 600 }), NOW).unwrap();
+    assert_eq!(early.status(), LifetimeStatus::NotYetValid);
+    assert_eq!(early.countdown(), "valid in 1m 30s");
+  }
+
+  #[test]
+  fn test_timeline_extreme_times() {
+    let claims = json!({ "nbf": i64::MIN, "iat": i64::MIN, "exp": i64::MAX });
+    let lifetime = Timeline::new(&serde_json::from_value(claims).unwrap(), 0, u64::MAX).unwrap();
+    assert_eq!(lifetime.status(), LifetimeStatus::Valid);
+    assert_eq!(lifetime.position(0, 31), 15);
+    assert_eq!(lifetime.position(i64::MAX, 31), 30);
+
+    let expired = timeline(json!({ "exp": i64::MIN }), i64::MAX).unwrap();
+    assert_eq!(expired.status(), LifetimeStatus::Expired);
+    assert!(expired.countdown().starts_with("expired "));
+  }
+
+  #[test]
+  fn test_timeline_positions() {
+    let timeline = timeline(json!({ "iat": NOW - 1800, "exp": NOW + 600 }), NOW + 1200).unwrap();
+    assert_eq!(timeline.range(), (NOW - 1800, NOW + 1200));
+    assert_eq!(timeline.position(NOW - 1800, 31), 0);
+    assert_eq!(timeline.position(NOW + 600, 31), 24);
+    assert_eq!(timeline.position(NOW + 1200, 31), 30);
+    assert_eq!(
+      timeline.markers(),
+      vec![("iat", NOW - 1800), ("exp", NOW + 600), ("now", NOW + 1200)]
+    );
+  }
+}
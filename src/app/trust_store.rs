@@ -0,0 +1,207 @@
+use std::{
+  env, fmt, fs,
+  path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use glob::Pattern;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::utils::{JWTError, JWTResult};
+
+/// maps token issuers to the key that verifies their tokens, e.g.
+///
+/// ```toml
+/// [[issuer]]
+/// iss = "https://login.*.example.com/"
+/// name = "staging"
+/// key = "https://login.staging.example.com/"
+///
+/// [[issuer]]
+/// iss = "dev-*"
+/// key_env = "JWT_DEV_SECRET"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrustStore {
+  #[serde(default, rename = "issuer")]
+  pub entries: Vec<TrustEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrustEntry {
+  /// glob pattern matched against the `iss` claim
+  pub iss: String,
+  pub name: Option<String>,
+  /// secret in the same syntax as the secret input: `@` file path (PEM, DER, JWKS), issuer/JWKS URL or `b64:`
+  pub key: Option<String>,
+  /// environment variable holding the secret, e.g. an HMAC secret
+  pub key_env: Option<String>,
+}
+
+impl TrustStore {
+  /// `~/.config/jwtui/trust.toml` or the platform equivalent
+  pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("jwtui").join("trust.toml"))
+  }
+
+  /// load the given trust store, or the one at the default path if it exists
+  pub fn load(path: Option<&Path>) -> JWTResult<Option<TrustStore>> {
+    let path = match (path, Self::default_path()) {
+      (Some(path), _) => path.to_path_buf(),
+      (None, Some(path)) if path.exists() => path,
+      _ => return Ok(None),
+    };
+    let content = fs::read_to_string(&path).map_err(|e| {
+      JWTError::Internal(format!(
+        "Unable to read trust store {}: {e}",
+        path.display()
+      ))
+    })?;
+    content
+      .parse::<TrustStore>()
+      .map(Some)
+      .map_err(|e| JWTError::Internal(format!("Invalid trust store {}: {e}", path.display())))
+  }
+
+  /// first entry whose pattern matches the `iss` claim of the token
+  pub fn find_for_token(&self, token: &str) -> Option<&TrustEntry> {
+    let iss = issuer_of(token)?;
+    self.entries.iter().find(|entry| entry.matches(&iss))
+  }
+}
+
+impl std::str::FromStr for TrustStore {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let store: TrustStore = toml::from_str(s).map_err(|e| e.message().to_string())?;
+    for entry in &store.entries {
+      Pattern::new(&entry.iss)
+        .map_err(|e| format!("invalid `iss` pattern {:?}: {e}", entry.iss))?;
+      if entry.key.is_some() == entry.key_env.is_some() {
+        return Err(format!(
+          "entry for {:?} needs either `key` or `key_env`",
+          entry.iss
+        ));
+      }
+    }
+    Ok(store)
+  }
+}
+
+impl TrustEntry {
+  fn matches(&self, iss: &str) -> bool {
+    Pattern::new(&self.iss).is_ok_and(|pattern| pattern.matches(iss))
+  }
+
+  /// the secret in the syntax of the secret input
+  pub fn secret(&self) -> JWTResult<String> {
+    match (&self.key, &self.key_env) {
+      (Some(key), _) => Ok(expand_home(key)),
+      (None, Some(var)) => env::var(var).map_err(|_| {
+        JWTError::Internal(format!(
+          "Environment variable {var} of trust store entry {self} isn't set"
+        ))
+      }),
+      (None, None) => Err(JWTError::Internal(format!(
+        "Trust store entry {self} has no key"
+      ))),
+    }
+  }
+}
+
+impl fmt::Display for TrustEntry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match &self.name {
+      Some(name) => write!(f, "{name:?} ({})", self.iss),
+      None => write!(f, "{:?}", self.iss),
+    }
+  }
+}
+
+/// `@~/keys/key.pem` is read from the home directory
+fn expand_home(key: &str) -> String {
+  match (key.strip_prefix("@~/"), dirs::home_dir()) {
+    (Some(path), Some(home)) => format!("@{}", home.join(path).display()),
+    _ => key.to_string(),
+  }
+}
+
+/// the unverified `iss` claim of a JWS
+fn issuer_of(token: &str) -> Option<String> {
+  let payload = token.split('.').nth(1)?;
+  let claims: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+  claims.get("iss")?.as_str().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const STORE: &str = r#"
+[[issuer]]
+iss = "https://login.*.example.com/"
+name = "staging"
+key = "@./test_data/test_rsa_public_key.pem"
+
+[[issuer]]
+iss = "dev-*"
+key_env = "JWTUI_TEST_DEV_SECRET"
+"#;
+
+  fn token_with_issuer(iss: &str) -> String {
+    let claims = URL_SAFE_NO_PAD.encode(format!(r#"{{"iss":"{iss}"}}"#));
+    format!("eyJhbGciOiJIUzI1NiJ9.{claims}.sig")
+  }
+
+  #[test]
+  fn test_find_entry_for_token() {
+    let store: TrustStore = STORE.parse().unwrap();
+
+    let entry = store
+      .find_for_token(&token_with_issuer("https://login.staging.example.com/"))
+      .unwrap();
+    assert_eq!(
+      entry.to_string(),
+      r#""staging" (https://login.*.example.com/)"#
+    );
+    assert_eq!(
+      entry.secret().unwrap(),
+      "@./test_data/test_rsa_public_key.pem"
+    );
+
+    let entry = store.find_for_token(&token_with_issuer("dev-1")).unwrap();
+    assert_eq!(
+      entry.secret().unwrap_err(),
+      JWTError::Internal(
+        r#"Environment variable JWTUI_TEST_DEV_SECRET of trust store entry "dev-*" isn't set"#
+          .into()
+      )
+    );
+
+    assert!(store
+      .find_for_token(&token_with_issuer("https://other.com/"))
+      .is_none());
+    assert!(store.find_for_token("not a token").is_none());
+  }
+
+  #[test]
+  fn test_invalid_trust_store() {
+    assert_eq!(
+      "[[issuer]]\niss = \"a\"".parse::<TrustStore>().unwrap_err(),
+      r#"entry for "a" needs either `key` or `key_env`"#
+    );
+    assert_eq!(
+      "[[issuer]]\niss = \"[a\"\nkey = \"s\""
+        .parse::<TrustStore>()
+        .unwrap_err(),
+      r#"invalid `iss` pattern "[a": Pattern syntax error near position 0: invalid range pattern"#
+    );
+    assert!("[[issuer]]\niss = \"a\"\nsecret = \"s\""
+      .parse::<TrustStore>()
+      .is_err());
+  }
+}
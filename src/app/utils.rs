@@ -1,10 +1,20 @@
-use std::{fmt, fs, io, str::Utf8Error};
+use std::{
+  env, fmt, fs,
+  io::{self, Read, Write},
+  path::Path,
+  str::Utf8Error,
+};
 
 use jsonwebtoken::{
   errors::{Error, ErrorKind},
   jwk, Algorithm, DecodingKey, Header,
 };
 
+use super::{
+  jwe::KeyAlgorithm,
+  jwks::{fetch_jwks, is_jwks_url},
+};
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum JWTError {
   Internal(String),
@@ -53,6 +63,11 @@ impl fmt::Display for JWTError {
   }
 }
 
+/// error of a PEM or DER key used as HMAC secret
+pub const KEY_AS_HMAC_SECRET: &str =
+  "A PEM or DER key can't be used as an HMAC secret, anyone with its public key could sign tokens";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum SecretType {
   Pem,
   Der,
@@ -61,49 +76,170 @@ pub enum SecretType {
   Plain,
 }
 
+impl SecretType {
+  /// whether a secret of this type can be used with the given algorithm
+  pub fn supports(&self, alg: &Algorithm) -> bool {
+    match self {
+      SecretType::Plain | SecretType::B64 => is_hmac(alg),
+      SecretType::Pem | SecretType::Der => !is_hmac(alg),
+      SecretType::Jwks => true,
+    }
+  }
+
+  pub fn supported_algorithms(&self) -> &'static str {
+    match self {
+      SecretType::Plain | SecretType::B64 => "HS256, HS384, HS512",
+      SecretType::Pem | SecretType::Der => "RS*, PS*, ES256, ES384, EdDSA",
+      SecretType::Jwks => "algorithm of the JWK",
+    }
+  }
+}
+
+fn is_hmac(alg: &Algorithm) -> bool {
+  matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512)
+}
+
+/// infer the type of secret from the secret input without reading any files
+pub fn get_secret_type(alg: &Algorithm, secret_string: &str) -> SecretType {
+  secret_type(is_hmac(alg), secret_string)
+}
+
+/// infer the type of a JWE key, symmetric keys are handled like HMAC secrets
+pub fn get_jwe_secret_type(alg: &KeyAlgorithm, secret_string: &str) -> SecretType {
+  secret_type(alg.is_symmetric(), secret_string)
+}
+
+fn secret_type(symmetric: bool, secret_string: &str) -> SecretType {
+  if symmetric {
+    if secret_string.starts_with('@')
+      && (secret_string.ends_with(".json") || secret_string.ends_with(".jwk"))
+    {
+      SecretType::Jwks
+    } else if secret_string.trim_start().starts_with('{')
+      && serde_json::from_str::<serde_json::Value>(secret_string).is_ok()
+    {
+      // JWK or JWKS pasted as secret
+      SecretType::Jwks
+    } else if secret_string.starts_with("b64:") {
+      SecretType::B64
+    } else {
+      SecretType::Plain
+    }
+  } else if secret_string.starts_with('@') {
+    get_secret_file_type(secret_string)
+  } else {
+    // an issuer, discovery document or `jwks_uri` URL, or a JWKS pasted as argument
+    // (e.g. output of 'curl https://auth.domain.com/jwks.json')
+    SecretType::Jwks
+  }
+}
+
 pub fn get_secret_from_file_or_input(
   alg: &Algorithm,
   secret_string: &str,
 ) -> (JWTResult<Vec<u8>>, SecretType) {
-  match alg {
-    Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
-      if secret_string.starts_with('@') {
-        (
-          slurp_file(strip_leading_symbol(secret_string)).map_err(JWTError::from),
-          if secret_string.ends_with(".json") {
-            SecretType::Jwks
-          } else {
-            SecretType::Plain
-          },
-        )
-      } else if secret_string.starts_with("b64:") {
-        (
-          Ok(
-            secret_string
-              .chars()
-              .skip(4)
-              .collect::<String>()
-              .as_bytes()
-              .to_owned(),
-          ),
-          SecretType::B64,
-        )
-      } else {
-        (Ok(secret_string.as_bytes().to_owned()), SecretType::Plain)
-      }
-    }
-    _ => {
-      if secret_string.starts_with('@') {
-        (
-          slurp_file(strip_leading_symbol(secret_string)).map_err(JWTError::from),
-          get_secret_file_type(secret_string),
-        )
-      } else {
-        // allows to read JWKS from argument (e.g. output of 'curl https://auth.domain.com/jwks.json')
-        (Ok(secret_string.as_bytes().to_vec()), SecretType::Jwks)
-      }
+  let secret_type = get_secret_type(alg, secret_string);
+  (read_secret(secret_string, secret_type), secret_type)
+}
+
+pub fn get_jwe_secret_from_file_or_input(
+  alg: &KeyAlgorithm,
+  secret_string: &str,
+) -> (JWTResult<Vec<u8>>, SecretType) {
+  let secret_type = get_jwe_secret_type(alg, secret_string);
+  (read_secret(secret_string, secret_type), secret_type)
+}
+
+fn read_secret(secret_string: &str, secret_type: SecretType) -> JWTResult<Vec<u8>> {
+  let secret = read_secret_bytes(secret_string, secret_type)?;
+  if secret_type == SecretType::Plain && is_asymmetric_key(&secret) {
+    return Err(JWTError::Internal(KEY_AS_HMAC_SECRET.into()));
+  }
+  Ok(secret)
+}
+
+/// a PEM or DER encoded key, e.g. the public key of a trust store entry used for an `HS256` token
+pub fn is_asymmetric_key(secret: &[u8]) -> bool {
+  String::from_utf8_lossy(secret).contains("-----BEGIN ") || is_der_sequence(secret)
+}
+
+/// a DER `SEQUENCE` spanning all the bytes, which is how DER keys and certificates start
+fn is_der_sequence(bytes: &[u8]) -> bool {
+  let (length, header) = match bytes {
+    [0x30, 0x81, length, ..] => (*length as usize, 3),
+    [0x30, 0x82, high, low, ..] => (((*high as usize) << 8) | *low as usize, 4),
+    [0x30, length, ..] if *length < 0x80 => (*length as usize, 2),
+    _ => return false,
+  };
+  length > 0 && header + length == bytes.len()
+}
+
+fn read_secret_bytes(secret_string: &str, secret_type: SecretType) -> JWTResult<Vec<u8>> {
+  if secret_string.starts_with('@') {
+    slurp_file(strip_leading_symbol(secret_string)).map_err(JWTError::from)
+  } else if secret_type == SecretType::Jwks && is_jwks_url(secret_string) {
+    fetch_jwks(secret_string)
+  } else if secret_type == SecretType::B64 {
+    Ok(
+      secret_string
+        .chars()
+        .skip(4)
+        .collect::<String>()
+        .as_bytes()
+        .to_owned(),
+    )
+  } else {
+    Ok(secret_string.as_bytes().to_owned())
+  }
+}
+
+/// the token given on the command line: `-` for STDIN, `@` file path, `env:NAME` or the token itself.
+/// surrounding whitespace and a `Bearer ` prefix are removed
+pub fn read_token(input: &str) -> JWTResult<String> {
+  let token = if input == "-" {
+    let mut token = String::new();
+    io::stdin()
+      .read_to_string(&mut token)
+      .map_err(|e| JWTError::Internal(format!("Unable to read the token from STDIN: {e}")))?;
+    token
+  } else if input.starts_with('@') {
+    let path = strip_leading_symbol(input);
+    fs::read_to_string(&path)
+      .map_err(|e| JWTError::Internal(format!("Unable to read the token from {path}: {e}")))?
+  } else if let Some(name) = input.strip_prefix("env:") {
+    env::var(name).map_err(|_| {
+      JWTError::Internal(format!(
+        "Environment variable {name} with the token isn't set"
+      ))
+    })?
+  } else {
+    input.to_string()
+  };
+  Ok(strip_bearer(&token))
+}
+
+fn strip_bearer(token: &str) -> String {
+  let token = token.trim();
+  match token.get(..7) {
+    Some(prefix) if prefix.eq_ignore_ascii_case("bearer ") => token[7..].trim().to_string(),
+    _ => token.to_string(),
+  }
+}
+
+/// duration like `1d 2h 3m 4s`
+pub fn format_duration(seconds: u64) -> String {
+  let mut rest = seconds;
+  let mut parts = vec![];
+  for (unit, size) in [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)] {
+    if rest >= size {
+      parts.push(format!("{}{unit}", rest / size));
+      rest %= size;
     }
   }
+  match parts.is_empty() {
+    true => "0s".into(),
+    false => parts.join(" "),
+  }
 }
 
 pub fn strip_leading_symbol(secret_string: &str) -> String {
@@ -129,6 +265,23 @@ pub fn slurp_file(file_name: String) -> io::Result<Vec<u8>> {
   fs::read(file_name)
 }
 
+/// write a file only the user can read, e.g. a private key. The mode is set before anything is
+/// written, also when an existing file is replaced
+pub fn write_private_file(path: impl AsRef<Path>, content: &[u8]) -> io::Result<()> {
+  let mut options = fs::OpenOptions::new();
+  options.write(true).create(true).truncate(true);
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    options.mode(0o600);
+    let file = options.open(path)?;
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    (&file).write_all(content)
+  }
+  #[cfg(not(unix))]
+  options.open(path)?.write_all(content)
+}
+
 fn decoding_key_from_jwks(jwks: jwk::JwkSet, header: &Header) -> JWTResult<DecodingKey> {
   let kid = match &header.kid {
     Some(k) => k.to_owned(),
@@ -151,17 +304,18 @@ fn decoding_key_from_jwks(jwks: jwk::JwkSet, header: &Header) -> JWTResult<Decod
   DecodingKey::from_jwk(jwk).map_err(Error::into)
 }
 
+/// a JWKS, or a single JWK handled as a set of one key
 fn parse_jwks(secret: &[u8]) -> Option<jwk::JwkSet> {
-  match serde_json::from_slice(secret) {
-    Ok(jwks) => Some(jwks),
-    Err(_) => None,
-  }
+  serde_json::from_slice(secret).ok().or_else(|| {
+    let jwk: jwk::Jwk = serde_json::from_slice(secret).ok()?;
+    Some(jwk::JwkSet { keys: vec![jwk] })
+  })
 }
 
 fn get_secret_file_type(secret_string: &str) -> SecretType {
   if secret_string.ends_with(".pem") {
     SecretType::Pem
-  } else if secret_string.ends_with(".json") {
+  } else if secret_string.ends_with(".json") || secret_string.ends_with(".jwk") {
     SecretType::Jwks
   } else {
     SecretType::Der
@@ -185,7 +339,7 @@ fn map_external_error(ext_err: &Error) -> String {
         ErrorKind::MissingRequiredClaim(missing) => if missing.as_str() == "exp" {
           "`exp` is missing, but is required. This error can be ignored by pressing `i`.".to_string()
         } else {
-          format!("`{:?}` is missing, but is required", missing)
+          format!("`{}` is missing, but is required", missing)
         }
         ErrorKind::ExpiredSignature => {
           "The token has expired (or the `exp` claim is not set). This error can be ignored by pressing `i`.".to_string()
@@ -194,13 +348,13 @@ fn map_external_error(ext_err: &Error) -> String {
           "The token issuer is invalid".to_string()
         }
         ErrorKind::InvalidAudience => {
-          "The token audience doesn't match the subject".to_string()
+          "The token audience doesn't match any of the expected audiences".to_string()
         }
         ErrorKind::InvalidSubject => {
-          "The token subject doesn't match the audience".to_string()
+          "The token subject doesn't match the expected subject".to_string()
         }
         ErrorKind::ImmatureSignature => {
-          "The `nbf` claim is in the future which isn't allowed".to_string()
+          "The `nbf` claim, or the `iat` claim when validating at a given time, is in the future which isn't allowed".to_string()
         }
         ErrorKind::InvalidAlgorithm => "The JWT provided has a different signing algorithm than the one you provided".to_string(),
         _ => format!("The JWT provided is invalid because {:?}", ext_err),
@@ -228,6 +382,73 @@ mod tests {
     std::fs::remove_file(file_name).unwrap();
   }
 
+  #[test]
+  #[cfg(unix)]
+  fn test_write_private_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let file_name = "test_private.txt";
+    std::fs::write(file_name, "world readable").unwrap();
+    write_private_file(file_name, b"secret").unwrap();
+    let metadata = std::fs::metadata(file_name).unwrap();
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+    assert_eq!(std::fs::read(file_name).unwrap(), b"secret");
+    std::fs::remove_file(file_name).unwrap();
+  }
+
+  #[test]
+  fn test_read_token() {
+    let file_name = "test_token.txt";
+    std::fs::write(file_name, "Bearer eyJ.eyJ.sig\n").unwrap();
+    assert_eq!(read_token("@test_token.txt").unwrap(), "eyJ.eyJ.sig");
+    std::fs::remove_file(file_name).unwrap();
+
+    env::set_var("JWTUI_TEST_TOKEN", "  bearer eyJ.eyJ.env ");
+    assert_eq!(read_token("env:JWTUI_TEST_TOKEN").unwrap(), "eyJ.eyJ.env");
+    assert_eq!(
+      read_token("env:JWTUI_TEST_MISSING_TOKEN").unwrap_err(),
+      JWTError::Internal(
+        "Environment variable JWTUI_TEST_MISSING_TOKEN with the token isn't set".into()
+      )
+    );
+
+    assert_eq!(read_token(" eyJ.eyJ.sig\n").unwrap(), "eyJ.eyJ.sig");
+    assert_eq!(read_token("Bearer").unwrap(), "Bearer");
+  }
+
+  #[test]
+  fn test_reject_key_as_hmac_secret() {
+    for key in [
+      "@./test_data/test_rsa_public_key.pem",
+      "@./test_data/test_rsa_public_key.der",
+      "@./test_data/test_ecdsa_private_key.pk8",
+    ] {
+      let (secret, secret_type) = get_secret_from_file_or_input(&Algorithm::HS256, key);
+      assert_eq!(secret_type, SecretType::Plain);
+      assert_eq!(
+        secret.unwrap_err(),
+        JWTError::Internal(
+          "A PEM or DER key can't be used as an HMAC secret, anyone with its public key could sign tokens"
+            .into()
+        )
+      );
+    }
+    let pem = std::fs::read_to_string("./test_data/test_rsa_public_key.pem").unwrap();
+    assert!(get_secret_from_file_or_input(&Algorithm::HS256, &pem)
+      .0
+      .is_err());
+
+    assert!(!is_asymmetric_key(b"0123456789abcdef"));
+    assert!(!is_asymmetric_key(b"0\x05abc"));
+    // the same keys still verify asymmetric algorithms
+    assert!(get_secret_from_file_or_input(
+      &Algorithm::RS256,
+      "@./test_data/test_rsa_public_key.pem"
+    )
+    .0
+    .is_ok());
+  }
+
   #[test]
   #[should_panic(expected = "The system cannot find the file specified.")]
   #[cfg(target_os = "windows")]
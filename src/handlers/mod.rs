@@ -1,12 +1,13 @@
 use crossterm::event::{Event, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
-use ratatui::layout::Rect;
+use ratatui::layout::{Margin, Position, Rect};
 use tui_input::{backend::crossterm::EventHandler, Input};
 use tui_textarea::TextArea;
 
 use crate::{
   app::{
-    key_binding::DEFAULT_KEYBINDING, models::Scrollable, ActiveBlock, App, InputMode, RouteId,
-    TextAreaInput, TextInput,
+    algorithms, history, json_tree::JsonTree, jwt_decoder, jwt_decoder::segment_at, jwt_encoder,
+    key_binding::DEFAULT_KEYBINDING, keygen, models::Scrollable, templates, ActiveBlock, App,
+    InputMode, RouteId, TextAreaInput, TextInput,
   },
   event::Key,
 };
@@ -19,6 +20,26 @@ pub fn handle_key_events(key: Key, key_event: KeyEvent, app: &mut App) {
       _ if key == DEFAULT_KEYBINDING.esc.key && app.get_current_route().id == RouteId::Help => {
         app.pop_navigation_stack();
       }
+      _ if key == DEFAULT_KEYBINDING.esc.key && app.get_current_route().id == RouteId::Keygen => {
+        // a route is pushed for every focused block of the dialog
+        while app.get_current_route().id == RouteId::Keygen && app.pop_navigation_stack().is_some()
+        {
+        }
+      }
+      _ if key == DEFAULT_KEYBINDING.esc.key
+        && app.get_current_route().id == RouteId::Templates =>
+      {
+        while app.get_current_route().id == RouteId::Templates
+          && app.pop_navigation_stack().is_some()
+        {}
+      }
+      _ if key == DEFAULT_KEYBINDING.esc.key
+        && app.get_current_route().id == RouteId::Algorithms =>
+      {
+        while app.get_current_route().id == RouteId::Algorithms
+          && app.pop_navigation_stack().is_some()
+        {}
+      }
       _ if key == DEFAULT_KEYBINDING.quit.key || key == DEFAULT_KEYBINDING.quit.alt.unwrap() => {
         app.should_quit = true;
       }
@@ -59,6 +80,21 @@ pub fn handle_key_events(key: Key, key_event: KeyEvent, app: &mut App) {
       {
         app.route_encoder();
       }
+      _ if key == DEFAULT_KEYBINDING.jump_to_history.key
+        && app.get_current_route().id != RouteId::History =>
+      {
+        app.route_history();
+      }
+      _ if key == DEFAULT_KEYBINDING.jump_to_compare.key
+        && app.get_current_route().id != RouteId::Compare =>
+      {
+        app.route_compare();
+      }
+      _ if key == DEFAULT_KEYBINDING.generate_keys.key
+        && app.get_current_route().id != RouteId::Keygen =>
+      {
+        app.push_navigation_route(*app.data.keygen.blocks.get_active_item());
+      }
       _ if key == DEFAULT_KEYBINDING.cycle_main_views.key => app.cycle_main_routes(),
 
       _ if key == DEFAULT_KEYBINDING.toggle_input_edit.key
@@ -88,9 +124,25 @@ fn handle_edit_event(app: &mut App) {
   match app.get_current_route().active_block {
     ActiveBlock::DecoderToken => app.data.decoder.encoded.input_mode = InputMode::Editing,
     ActiveBlock::DecoderSecret => app.data.decoder.secret.input_mode = InputMode::Editing,
+    ActiveBlock::DecoderValidation => app.data.decoder.validation.input_mode = InputMode::Editing,
+    ActiveBlock::DecoderHeader => app.data.decoder.header.toggle_selected(),
+    ActiveBlock::DecoderPayload => app.data.decoder.payload.toggle_selected(),
     ActiveBlock::EncoderHeader => app.data.encoder.header.input_mode = InputMode::Editing,
     ActiveBlock::EncoderPayload => app.data.encoder.payload.input_mode = InputMode::Editing,
     ActiveBlock::EncoderSecret => app.data.encoder.secret.input_mode = InputMode::Editing,
+    ActiveBlock::EncoderEncryption => app.data.encoder.encryption.input_mode = InputMode::Editing,
+    ActiveBlock::EncoderEncryptionKey => {
+      app.data.encoder.encryption_key.input_mode = InputMode::Editing
+    }
+    ActiveBlock::KeygenTypes => keygen::generate_keys(app),
+    ActiveBlock::KeygenOutput => app.data.keygen.output.input_mode = InputMode::Editing,
+    ActiveBlock::TemplateList => templates::apply_selected(app),
+    ActiveBlock::AlgorithmList => algorithms::apply_selected(app),
+    ActiveBlock::HistoryEntries => history::reopen_selected(app),
+    ActiveBlock::HistorySearch => app.data.history.search.input_mode = InputMode::Editing,
+    ActiveBlock::CompareLeft => app.data.compare.left.input_mode = InputMode::Editing,
+    ActiveBlock::CompareRight => app.data.compare.right.input_mode = InputMode::Editing,
+    ActiveBlock::CompareSecret => app.data.compare.secret.input_mode = InputMode::Editing,
     _ => { /* do nothing */ }
   }
 }
@@ -101,14 +153,30 @@ fn handle_copy_event(app: &mut App) {
       copy_to_clipboard(app.data.decoder.encoded.input.value().into(), app);
     }
     ActiveBlock::DecoderHeader => {
-      copy_to_clipboard(app.data.decoder.header.get_txt(), app);
+      if let Some(value) = app.data.decoder.header.selected_value() {
+        copy_to_clipboard(value, app);
+      }
     }
     ActiveBlock::DecoderPayload => {
-      copy_to_clipboard(app.data.decoder.payload.get_txt(), app);
+      if let Some(value) = app.data.decoder.payload.selected_value() {
+        copy_to_clipboard(value, app);
+      }
     }
     ActiveBlock::DecoderSecret => {
       copy_to_clipboard(app.data.decoder.secret.input.value().into(), app);
     }
+    ActiveBlock::DecoderValidation => {
+      copy_to_clipboard(app.data.decoder.validation.input.value().into(), app);
+    }
+    ActiveBlock::DecoderReport => {
+      let report = serde_json::to_string_pretty(&app.data.decoder.get_report()).unwrap_or_default();
+      copy_to_clipboard(report, app);
+    }
+    ActiveBlock::DecoderFindings => {
+      let findings =
+        serde_json::to_string_pretty(&app.data.decoder.findings.items).unwrap_or_default();
+      copy_to_clipboard(findings, app);
+    }
     ActiveBlock::EncoderToken => {
       copy_to_clipboard(app.data.encoder.encoded.get_txt(), app);
     }
@@ -121,6 +189,32 @@ fn handle_copy_event(app: &mut App) {
     ActiveBlock::EncoderSecret => {
       copy_to_clipboard(app.data.encoder.secret.input.value().into(), app);
     }
+    ActiveBlock::EncoderEncryption => {
+      copy_to_clipboard(app.data.encoder.encryption.input.value().into(), app);
+    }
+    ActiveBlock::EncoderEncryptionKey => {
+      copy_to_clipboard(app.data.encoder.encryption_key.input.value().into(), app);
+    }
+    ActiveBlock::KeygenOutput => {
+      copy_to_clipboard(app.data.keygen.output.input.value().into(), app);
+    }
+    ActiveBlock::HistoryEntries => {
+      if let Some(entry) = app.data.history.selected() {
+        copy_to_clipboard(entry.token.clone(), app);
+      }
+    }
+    ActiveBlock::HistorySearch => {
+      copy_to_clipboard(app.data.history.search.input.value().into(), app);
+    }
+    ActiveBlock::CompareLeft => {
+      copy_to_clipboard(app.data.compare.left.input.value().into(), app);
+    }
+    ActiveBlock::CompareRight => {
+      copy_to_clipboard(app.data.compare.right.input.value().into(), app);
+    }
+    ActiveBlock::CompareSecret => {
+      copy_to_clipboard(app.data.compare.secret.input.value().into(), app);
+    }
     _ => { /* Do nothing */ }
   }
 }
@@ -129,6 +223,11 @@ fn is_any_text_editing(app: &mut App, key: Key, key_event: KeyEvent) -> bool {
   match app.get_current_route().active_block {
     ActiveBlock::DecoderToken => is_text_editing(&mut app.data.decoder.encoded, key, key_event),
     ActiveBlock::DecoderSecret => is_text_editing(&mut app.data.decoder.secret, key, key_event),
+    ActiveBlock::DecoderValidation => {
+      is_text_editing(&mut app.data.decoder.validation, key, key_event)
+    }
+    ActiveBlock::DecoderHeader => is_tree_searching(&mut app.data.decoder.header, key, key_event),
+    ActiveBlock::DecoderPayload => is_tree_searching(&mut app.data.decoder.payload, key, key_event),
     ActiveBlock::EncoderHeader => {
       is_text_area_editing(&mut app.data.encoder.header, key, key_event)
     }
@@ -136,6 +235,17 @@ fn is_any_text_editing(app: &mut App, key: Key, key_event: KeyEvent) -> bool {
       is_text_area_editing(&mut app.data.encoder.payload, key, key_event)
     }
     ActiveBlock::EncoderSecret => is_text_editing(&mut app.data.encoder.secret, key, key_event),
+    ActiveBlock::EncoderEncryption => {
+      is_text_editing(&mut app.data.encoder.encryption, key, key_event)
+    }
+    ActiveBlock::EncoderEncryptionKey => {
+      is_text_editing(&mut app.data.encoder.encryption_key, key, key_event)
+    }
+    ActiveBlock::KeygenOutput => is_text_editing(&mut app.data.keygen.output, key, key_event),
+    ActiveBlock::HistorySearch => is_text_editing(&mut app.data.history.search, key, key_event),
+    ActiveBlock::CompareLeft => is_text_editing(&mut app.data.compare.left, key, key_event),
+    ActiveBlock::CompareRight => is_text_editing(&mut app.data.compare.right, key, key_event),
+    ActiveBlock::CompareSecret => is_text_editing(&mut app.data.compare.secret, key, key_event),
     _ => false,
   }
 }
@@ -157,6 +267,19 @@ fn is_text_editing(input: &mut TextInput, key: Key, key_event: KeyEvent) -> bool
   }
 }
 
+fn is_tree_searching(tree: &mut JsonTree, key: Key, key_event: KeyEvent) -> bool {
+  if tree.search.input_mode == InputMode::Editing && key == DEFAULT_KEYBINDING.toggle_input_edit.key
+  {
+    tree.next_match();
+    true
+  } else if is_text_editing(&mut tree.search, key, key_event) {
+    tree.search_keys();
+    true
+  } else {
+    false
+  }
+}
+
 fn is_text_area_editing(input: &mut TextAreaInput<'_>, key: Key, key_event: KeyEvent) -> bool {
   if input.input_mode == InputMode::Editing {
     if key == DEFAULT_KEYBINDING.esc.key {
@@ -187,11 +310,54 @@ fn handle_route_events(key: Key, app: &mut App) {
         _ if key == DEFAULT_KEYBINDING.toggle_ignore_exp.key => {
           app.data.decoder.ignore_exp = !app.data.decoder.ignore_exp;
         }
+        _ if key == DEFAULT_KEYBINDING.toggle_annotations.key => {
+          app.data.decoder.annotations = !app.data.decoder.annotations;
+        }
+        _ if key == DEFAULT_KEYBINDING.search_keys.key => {
+          match app.get_current_route().active_block {
+            ActiveBlock::DecoderHeader => {
+              app.data.decoder.header.search.input_mode = InputMode::Editing
+            }
+            ActiveBlock::DecoderPayload => {
+              app.data.decoder.payload.search.input_mode = InputMode::Editing
+            }
+            _ => { /* Do nothing */ }
+          }
+        }
+        _ if key == DEFAULT_KEYBINDING.send_to_encoder.key => jwt_decoder::send_to_encoder(app),
         _ => { /* Do nothing */ }
       };
     }
     RouteId::Encoder => {
-      //   nothing to handle
+      match key {
+        _ if key == DEFAULT_KEYBINDING.toggle_unsigned.key => {
+          app.data.encoder.allow_unsigned = !app.data.encoder.allow_unsigned;
+        }
+        _ if key == DEFAULT_KEYBINDING.toggle_refresh_claims.key => {
+          app.data.encoder.refresh_claims = !app.data.encoder.refresh_claims;
+        }
+        _ if key == DEFAULT_KEYBINDING.open_templates.key => {
+          app.push_navigation_stack(RouteId::Templates, ActiveBlock::TemplateList);
+        }
+        _ if key == DEFAULT_KEYBINDING.pick_algorithm.key => algorithms::open_picker(app),
+        _ if key == DEFAULT_KEYBINDING.fill_key_headers.key => algorithms::fill_key_headers(app),
+        _ if key == DEFAULT_KEYBINDING.send_to_decoder.key => jwt_encoder::send_to_decoder(app),
+        _ => { /* Do nothing */ }
+      };
+    }
+    RouteId::History => {
+      let result = match key {
+        _ if key == DEFAULT_KEYBINDING.toggle_pin.key => app.data.history.toggle_pin(),
+        _ if key == DEFAULT_KEYBINDING.delete_history_entry.key
+          || key == DEFAULT_KEYBINDING.delete_history_entry.alt.unwrap() =>
+        {
+          app.data.history.delete_selected()
+        }
+        _ => Ok(()),
+      };
+      if let Err(e) = result {
+        app.handle_error(e);
+      }
     }
     _ => { /* Do nothing */ }
   }
@@ -208,7 +374,19 @@ fn handle_left_key_events(app: &mut App) {
       app.data.encoder.blocks.previous();
       app.push_navigation_route(*app.data.encoder.blocks.get_active_item());
     }
-    RouteId::Help => { /* Do nothing */ }
+    RouteId::Keygen => {
+      app.data.keygen.blocks.previous();
+      app.push_navigation_route(*app.data.keygen.blocks.get_active_item());
+    }
+    RouteId::History => {
+      app.data.history.blocks.previous();
+      app.push_navigation_route(*app.data.history.blocks.get_active_item());
+    }
+    RouteId::Compare => {
+      app.data.compare.blocks.previous();
+      app.push_navigation_route(*app.data.compare.blocks.get_active_item());
+    }
+    RouteId::Help | RouteId::Templates | RouteId::Algorithms => { /* Do nothing */ }
   }
 }
 
@@ -223,7 +401,19 @@ fn handle_right_key_events(app: &mut App) {
       app.data.encoder.blocks.next();
       app.push_navigation_route(*app.data.encoder.blocks.get_active_item());
     }
-    RouteId::Help => { /* Do nothing */ }
+    RouteId::Keygen => {
+      app.data.keygen.blocks.next();
+      app.push_navigation_route(*app.data.keygen.blocks.get_active_item());
+    }
+    RouteId::History => {
+      app.data.history.blocks.next();
+      app.push_navigation_route(*app.data.history.blocks.get_active_item());
+    }
+    RouteId::Compare => {
+      app.data.compare.blocks.next();
+      app.push_navigation_route(*app.data.compare.blocks.get_active_item());
+    }
+    RouteId::Help | RouteId::Templates | RouteId::Algorithms => { /* Do nothing */ }
   }
 }
 
@@ -240,26 +430,88 @@ fn handle_mouse_btn_press(app: &mut App, mouse_event: MouseEvent) {
     .collect::<Vec<_>>()
     .first()
   {
-    let selected_route = *data.0;
+    let (selected_route, area) = (*data.0, *data.1);
 
     // route specific events
     match app.get_current_route().id {
       RouteId::Decoder => {
+        if selected_route.active_block == ActiveBlock::DecoderToken {
+          // the token is inside an input with a border, in a block with a margin
+          let token = app.data.decoder.encoded.input.value();
+          app.data.decoder.selected_segment =
+            char_index_at(area.inner(Margin::new(2, 2)), mouse_event, 0)
+              .and_then(|i| segment_at(token, i));
+        }
+        let tree = match selected_route.active_block {
+          ActiveBlock::DecoderHeader => Some(&mut app.data.decoder.header),
+          ActiveBlock::DecoderPayload => Some(&mut app.data.decoder.payload),
+          _ => None,
+        };
+        if let Some(tree) = tree {
+          select_tree_row(tree, area.inner(Margin::new(1, 1)), mouse_event);
+        }
         app.data.decoder.blocks.set_item(selected_route);
         app.push_navigation_route(*app.data.decoder.blocks.get_active_item());
       }
       RouteId::Encoder => {
+        if selected_route.active_block == ActiveBlock::EncoderToken {
+          let encoded = &app.data.encoder.encoded;
+          app.data.encoder.selected_segment =
+            char_index_at(area.inner(Margin::new(1, 1)), mouse_event, encoded.offset)
+              .and_then(|i| segment_at(&encoded.get_txt(), i));
+        }
         app.data.encoder.blocks.set_item(selected_route);
         app.push_navigation_route(*app.data.encoder.blocks.get_active_item());
       }
-      RouteId::Help => { /* Do nothing */ }
+      RouteId::Keygen => {
+        app.data.keygen.blocks.set_item(selected_route);
+        app.push_navigation_route(*app.data.keygen.blocks.get_active_item());
+      }
+      RouteId::History => {
+        app.data.history.blocks.set_item(selected_route);
+        app.push_navigation_route(*app.data.history.blocks.get_active_item());
+      }
+      RouteId::Compare => {
+        app.data.compare.blocks.set_item(selected_route);
+        app.push_navigation_route(*app.data.compare.blocks.get_active_item());
+      }
+      RouteId::Help | RouteId::Templates | RouteId::Algorithms => { /* Do nothing */ }
     }
   };
 }
 
+/// select the row of the tree clicked on
+fn select_tree_row(tree: &mut JsonTree, rows: Rect, mouse_event: MouseEvent) {
+  if !rows.contains(Position::new(mouse_event.column, mouse_event.row)) {
+    return;
+  }
+  let index = (mouse_event.row - rows.y) as usize + tree.rows.state.offset();
+  if index < tree.rows.items.len() {
+    tree.rows.state.select(Some(index));
+  }
+}
+
+/// index of the character clicked on in a wrapped text, scrolled by `offset` rows
+fn char_index_at(text: Rect, mouse_event: MouseEvent, offset: u16) -> Option<usize> {
+  if !text.contains(Position::new(mouse_event.column, mouse_event.row)) {
+    return None;
+  }
+  let row = (mouse_event.row - text.y + offset) as usize;
+  Some(row * text.width as usize + (mouse_event.column - text.x) as usize)
+}
+
 fn handle_block_scroll(app: &mut App, up: bool, is_mouse: bool, page: bool) {
   match app.get_current_route().active_block {
     ActiveBlock::Help => app.help_docs.handle_scroll(up, page),
+    ActiveBlock::KeygenTypes => app.data.keygen.key_types.handle_scroll(up, page),
+    ActiveBlock::TemplateList => app.data.templates.items.handle_scroll(up, page),
+    ActiveBlock::AlgorithmList => app.data.algorithms.items.handle_scroll(up, page),
+    ActiveBlock::HistoryEntries => app.data.history.table.handle_scroll(up, page),
+    ActiveBlock::CompareDiff => app
+      .data
+      .compare
+      .diff
+      .handle_scroll(inverse_dir(up, is_mouse), page),
     ActiveBlock::DecoderHeader => app
       .data
       .decoder
@@ -270,6 +522,16 @@ fn handle_block_scroll(app: &mut App, up: bool, is_mouse: bool, page: bool) {
       .decoder
       .payload
       .handle_scroll(inverse_dir(up, is_mouse), page),
+    ActiveBlock::DecoderReport => app
+      .data
+      .decoder
+      .report
+      .handle_scroll(inverse_dir(up, is_mouse), page),
+    ActiveBlock::DecoderFindings => app
+      .data
+      .decoder
+      .findings
+      .handle_scroll(inverse_dir(up, is_mouse), page),
     _ => {}
   }
 }
@@ -307,10 +569,11 @@ fn inverse_dir(up: bool, is_mouse: bool) -> bool {
 
 #[cfg(test)]
 mod tests {
+  use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
   use crossterm::event::{KeyCode, KeyModifiers};
 
   use super::*;
-  use crate::app::{models::ScrollableTxt, Route};
+  use crate::app::{history::HistoryKind, jwt_decoder::TokenSegment, Route};
 
   #[test]
   fn test_inverse_dir() {
@@ -421,34 +684,276 @@ mod tests {
     assert_eq!(app.help_docs.state.selected(), Some(1));
   }
 
+  #[test]
+  fn test_handle_key_events_for_keygen_dialog() {
+    let mut app = App::default();
+    app.route_encoder();
+
+    let key_evt = KeyEvent::from(KeyCode::Char('g'));
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(
+      app.get_current_route().active_block,
+      ActiveBlock::KeygenTypes
+    );
+
+    let key_evt = KeyEvent::from(KeyCode::Down);
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.data.keygen.key_types.state.selected(), Some(1));
+
+    let key_evt = KeyEvent::from(KeyCode::Right);
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(
+      app.get_current_route().active_block,
+      ActiveBlock::KeygenOutput
+    );
+
+    let key_evt = KeyEvent::from(KeyCode::Esc);
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.get_current_route().id, RouteId::Encoder);
+  }
+
+  #[test]
+  fn test_handle_key_events_for_templates() {
+    let mut app = App::new(None, "secret".into());
+    app.route_encoder();
+
+    let key_evt = KeyEvent::from(KeyCode::Char('o'));
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.get_current_route().id, RouteId::Templates);
+
+    let key_evt = KeyEvent::from(KeyCode::Down);
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.data.templates.items.state.selected(), Some(1));
+
+    let key_evt = KeyEvent::from(KeyCode::Enter);
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.get_current_route().id, RouteId::Encoder);
+    assert_eq!(
+      app.data.encoder.header.input.lines().join("\n"),
+      "{\n  \"alg\": \"HS256\",\n  \"typ\": \"at+jwt\"\n}"
+    );
+
+    app.on_tick();
+    assert_eq!(app.data.error, "");
+    let token = app.data.encoder.encoded.get_txt();
+    let payload = URL_SAFE_NO_PAD.decode(token.split('.').nth(1).unwrap());
+    let claims: serde_json::Value = serde_json::from_slice(&payload.unwrap()).unwrap();
+    assert_eq!(claims["exp"], claims["iat"].as_i64().unwrap() + 3600);
+    assert_eq!(claims["jti"].as_str().unwrap().len(), 36);
+  }
+
+  #[test]
+  fn test_handle_key_events_to_decoder_and_back() {
+    let mut app = App::new(None, "secret".into());
+    app.route_encoder();
+    app.data.encoder.payload.input =
+      TextArea::from(["{", r#"  "exp": 1700000000,"#, r#"  "sub": "alice""#, "}"]);
+    app.on_tick();
+
+    app.data.decoder.secret.input = "other".into();
+    let key_evt = KeyEvent::from(KeyCode::Char('v'));
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.get_current_route().id, RouteId::Decoder);
+    assert_eq!(
+      app.data.decoder.encoded.input.value(),
+      app.data.encoder.encoded.get_txt()
+    );
+    // the secret the token was signed with
+    assert_eq!(app.data.decoder.secret.input.value(), "secret");
+    app.data.decoder.utc_dates = true;
+    app.on_tick();
+    assert!(app.data.decoder.signature_verified);
+
+    app.data.encoder.payload.input = TextArea::default();
+    let key_evt = KeyEvent::from(KeyCode::Char('s'));
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.get_current_route().id, RouteId::Encoder);
+    assert_eq!(
+      app.data.encoder.header.input.lines().join("\n"),
+      "{\n  \"typ\": \"JWT\",\n  \"alg\": \"HS256\"\n}"
+    );
+    // timestamps shown as UTC dates are signed as numbers again
+    assert_eq!(
+      app.data.encoder.payload.input.lines().join("\n"),
+      "{\n  \"exp\": 1700000000,\n  \"sub\": \"alice\"\n}"
+    );
+  }
+
+  #[test]
+  fn test_send_to_encoder_without_token() {
+    let mut app = App::new(None, "secret".into());
+    let key_evt = KeyEvent::from(KeyCode::Char('s'));
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.get_current_route().id, RouteId::Decoder);
+    assert_eq!(
+      app.data.error,
+      "There is no decoded token to send to the encoder"
+    );
+  }
+
+  #[test]
+  fn test_handle_key_events_for_history() {
+    let mut app = App::default();
+    for token in ["a.b.c", "d.e.f"] {
+      app
+        .data
+        .history
+        .record(token, HistoryKind::Decoded, None, false)
+        .unwrap();
+    }
+
+    let key_evt = KeyEvent::from(KeyCode::Char('H'));
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.get_current_route().id, RouteId::History);
+
+    let key_evt = KeyEvent::from(KeyCode::Down);
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.data.history.selected().unwrap().token, "a.b.c");
+
+    let key_evt = KeyEvent::from(KeyCode::Char('p'));
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert!(app.data.history.entries[1].pinned);
+    assert_eq!(app.data.history.table.items, vec![1, 0]);
+
+    assert_eq!(app.data.history.selected().unwrap().token, "a.b.c");
+
+    let key_evt = KeyEvent::from(KeyCode::Char('x'));
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.data.history.entries.len(), 1);
+
+    let key_evt = KeyEvent::from(KeyCode::Enter);
+    handle_key_events(Key::from(key_evt), key_evt, &mut app);
+    assert_eq!(app.get_current_route().id, RouteId::Decoder);
+    assert_eq!(app.data.decoder.encoded.input.value(), "d.e.f");
+  }
+
   #[test]
   fn test_handle_block_scroll_with_decoder_header_block() {
     let mut app = App::default();
-    app.data.decoder.header = ScrollableTxt::new("test\n multiline\n string".into());
+    app.data.decoder.header = JsonTree::new("\"test\"".into());
     app.push_navigation_route(Route {
       id: RouteId::Decoder,
       active_block: ActiveBlock::DecoderHeader,
     });
 
     handle_block_scroll(&mut app, false, false, false);
-    assert_eq!(app.data.decoder.header.offset, 0);
+    assert_eq!(app.data.decoder.header.rows.state.selected(), Some(0));
 
-    app.data.decoder.header =
-      ScrollableTxt::new("te\nst\nm\n\n\n\n\n\n\n\n\nul\ntil\ni\nne\nstr\ni\nn\ng".into());
+    app.data.decoder.header = JsonTree::new(format!("{:?}", (0..20).collect::<Vec<_>>()));
 
     handle_block_scroll(&mut app, false, false, false);
-    assert_eq!(app.data.decoder.header.offset, 1);
+    assert_eq!(app.data.decoder.header.rows.state.selected(), Some(1));
 
     handle_block_scroll(&mut app, false, false, false);
-    assert_eq!(app.data.decoder.header.offset, 2);
+    assert_eq!(app.data.decoder.header.rows.state.selected(), Some(2));
 
     handle_block_scroll(&mut app, false, false, true);
-    assert_eq!(app.data.decoder.header.offset, 12);
+    assert_eq!(app.data.decoder.header.rows.state.selected(), Some(12));
 
     handle_block_scroll(&mut app, true, false, true);
-    assert_eq!(app.data.decoder.header.offset, 2);
+    assert_eq!(app.data.decoder.header.rows.state.selected(), Some(2));
 
     handle_block_scroll(&mut app, true, false, true);
-    assert_eq!(app.data.decoder.header.offset, 0);
+    assert_eq!(app.data.decoder.header.rows.state.selected(), Some(0));
+  }
+
+  #[test]
+  fn test_search_and_fold_decoder_payload() {
+    let mut app = App::new(None, "".into());
+    app.data.decoder.payload =
+      JsonTree::new(r#"{"realm_access":{"roles":["admin"]},"sub":"alice"}"#.into());
+    app.push_navigation_route(Route {
+      id: RouteId::Decoder,
+      active_block: ActiveBlock::DecoderPayload,
+    });
+    let press = |app: &mut App, code| {
+      let key_evt = KeyEvent::from(code);
+      handle_key_events(Key::from(key_evt), key_evt, app);
+    };
+
+    press(&mut app, KeyCode::Char('/'));
+    assert_eq!(
+      app.data.decoder.payload.search.input_mode,
+      InputMode::Editing
+    );
+    press(&mut app, KeyCode::Char('s'));
+    // `realm_access` is the first match
+    assert_eq!(app.data.decoder.payload.rows.state.selected(), Some(1));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.data.decoder.payload.rows.state.selected(), Some(2));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.data.decoder.payload.rows.state.selected(), Some(6));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.data.decoder.payload.rows.state.selected(), Some(1));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(
+      app.data.decoder.payload.search.input_mode,
+      InputMode::Normal
+    );
+
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(
+      app.data.decoder.payload.selected().unwrap().pointer,
+      "/realm_access"
+    );
+    assert_eq!(app.data.decoder.payload.rows.items.len(), 4);
+
+    app.update_block_map(
+      Route {
+        id: RouteId::Decoder,
+        active_block: ActiveBlock::DecoderPayload,
+      },
+      Rect::new(0, 0, 30, 10),
+    );
+    let click = MouseEvent {
+      kind: MouseEventKind::Down(MouseButton::Left),
+      column: 5,
+      row: 3,
+      modifiers: KeyModifiers::NONE,
+    };
+    handle_mouse_events(click, &mut app);
+    assert_eq!(
+      app.data.decoder.payload.selected_value(),
+      Some("alice".into())
+    );
+  }
+
+  #[test]
+  fn test_select_token_segment_with_mouse() {
+    let mut app = App::new(Some("aaaa.bbbbbbbbbb.cc".into()), "".into());
+    app.update_block_map(
+      Route {
+        id: RouteId::Decoder,
+        active_block: ActiveBlock::DecoderToken,
+      },
+      Rect::new(0, 0, 12, 8),
+    );
+    let click = |column, row| MouseEvent {
+      kind: MouseEventKind::Down(MouseButton::Left),
+      column,
+      row,
+      modifiers: KeyModifiers::NONE,
+    };
+
+    // the token wraps after 8 characters: "aaaa.bbb" and "bbbbbbb."
+    handle_mouse_events(click(8, 3), &mut app);
+    assert_eq!(
+      app.data.decoder.selected_segment,
+      Some(TokenSegment::Payload)
+    );
+    assert_eq!(
+      app.data.decoder.highlighted_segment(),
+      Some(TokenSegment::Payload)
+    );
+
+    handle_mouse_events(click(2, 2), &mut app);
+    assert_eq!(
+      app.data.decoder.selected_segment,
+      Some(TokenSegment::Header)
+    );
+
+    // on a dot
+    handle_mouse_events(click(6, 2), &mut app);
+    assert_eq!(app.data.decoder.selected_segment, None);
   }
 }
@@ -9,11 +9,26 @@ use std::{
   error::Error,
   io::{self, stdout, Stdout, Write},
   panic::{self, PanicHookInfo},
+  path::PathBuf,
 };
 
-use app::{jwt_decoder::print_decoded_token, App};
+use app::{
+  batch::{decode_batch, read_tokens, to_ndjson, to_summary, TokenStatus},
+  history::{History, HistoryStore, PASSPHRASE_ENV},
+  jwe::is_jwe,
+  jwks,
+  jwt_decoder::print_decoded_token,
+  keygen::{KeyFormat, KeyPair, KeyType},
+  lint::print_findings,
+  schema::{SchemaViolation, Schemas},
+  templates::Templates,
+  trust_store::TrustStore,
+  utils::read_token,
+  validation::{parse_instant, ValidationSettings, DEFAULT_LEEWAY},
+  App,
+};
 use banner::BANNER;
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use crossterm::{
   event::DisableMouseCapture,
   execute,
@@ -31,11 +46,11 @@ use crate::app::jwt_decoder::decode_jwt_token;
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None, before_help = BANNER)]
 pub struct Cli {
-  /// JWT token to decode [mandatory for stdout mode, optional for TUI mode].
+  /// JWT token to decode [mandatory for stdout mode, optional for TUI mode]. Can be the token, - for STDIN, a file path (beginning with @) or an environment variable (beginning with env:).
   #[clap(index = 1)]
   #[clap(value_parser)]
   pub token: Option<String>,
-  /// Secret for validating the JWT. Can be text, file path (beginning with @) or base64 encoded string (beginning with b64:).
+  /// Secret for validating the JWT. Can be text, file path (beginning with @), base64 encoded string (beginning with b64:) or an issuer/JWKS URL.
   #[arg(short = 'S', long, value_parser, default_value = "")]
   pub secret: String,
   /// Print to STDOUT instead of starting the CLI in TUI mode.
@@ -53,41 +68,276 @@ pub struct Cli {
   /// Disable mouse capture in order to copy individual text.
   #[arg(short, long, value_parser, default_value_t = false)]
   pub disable_mouse_capture: bool,
+  /// Expected issuer (`iss` claim). Can be repeated or comma separated to accept several issuers.
+  #[arg(long, value_parser, value_delimiter = ',')]
+  pub iss: Vec<String>,
+  /// Expected audience (`aud` claim). Can be repeated or comma separated, the token must match one of them.
+  #[arg(long, value_parser, value_delimiter = ',')]
+  pub aud: Vec<String>,
+  /// Expected subject (`sub` claim).
+  #[arg(long, value_parser)]
+  pub sub: Option<String>,
+  /// Leeway in seconds for validating the `exp` and `nbf` claims.
+  #[arg(long, value_parser, default_value_t = DEFAULT_LEEWAY)]
+  pub leeway: u64,
+  /// Claim that must be present in the JWT. Can be repeated or comma separated.
+  #[arg(long, value_parser, value_delimiter = ',')]
+  pub require_claim: Vec<String>,
+  /// Reject the JWT if its `nbf` claim is in the future.
+  #[arg(long, value_parser, default_value_t = false)]
+  pub validate_nbf: bool,
+  /// Validate `exp`, `nbf` and `iat` as of this time instead of now. Can be a RFC 3339 date or a unix timestamp.
+  #[arg(long, value_parser = parse_at)]
+  pub at: Option<i64>,
+  /// Only use cached JWKS when the secret is an issuer/JWKS URL, never fetch them.
+  #[arg(long, value_parser, default_value_t = false)]
+  pub offline: bool,
+  /// Trust store mapping issuers to keys, used when no secret is given [default: ~/.config/jwtui/trust.toml].
+  #[arg(long, value_parser)]
+  pub trust_store: Option<PathBuf>,
+  /// JSON Schema the claims of decoded and encoded tokens are validated against. Exits with 1 when printing to STDOUT or linting and the claims don't match.
+  #[arg(long, value_parser)]
+  pub schema: Option<PathBuf>,
+  /// Config mapping token types (`typ` header) and issuers to JSON Schemas, used without --schema [default: ~/.config/jwtui/schemas.toml].
+  #[arg(long, value_parser, conflicts_with = "schema")]
+  pub schemas: Option<PathBuf>,
+  /// Decode and verify the tokens of files, glob patterns or - for STDIN, one per line or in `Authorization: Bearer` lines. Prints a summary, or NDJSON with --json. Can be repeated.
+  #[arg(short, long, value_parser, conflicts_with = "token")]
+  pub batch: Vec<String>,
+  /// Do not save decoded and encoded tokens to the history file [default: ~/.local/share/jwtui/history.json]. The history is only saved when JWTUI_HISTORY_PASSPHRASE is set to encrypt it, or with --plain-history.
+  #[arg(long, value_parser, default_value_t = false)]
+  pub no_history: bool,
+  /// Save the history file unencrypted when JWTUI_HISTORY_PASSPHRASE isn't set. The tokens in it can be used by anyone who can read it.
+  #[arg(
+    long,
+    value_parser,
+    default_value_t = false,
+    conflicts_with = "no_history"
+  )]
+  pub plain_history: bool,
+  /// Print the security findings of the token, as JSON with --json. Exits with 1 when there are errors.
+  #[arg(long, value_parser, default_value_t = false, requires = "token")]
+  pub lint: bool,
+  #[command(subcommand)]
+  pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+  /// Generate an HMAC secret or a key pair to sign and verify JWTs.
+  Keygen(KeygenArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct KeygenArgs {
+  /// Key type: HS256, HS384, HS512, RSA-2048, RSA-3072, RSA-4096, P-256, P-384 or Ed25519.
+  #[arg(value_parser, default_value = "P-256")]
+  pub key_type: KeyType,
+  /// Path prefix of the written files, e.g. `key.pem` and `key.pub.pem` for `key`.
+  #[arg(short, long, value_parser, default_value = "./jwtui-key")]
+  pub out: String,
+  /// Output formats. Can be repeated or comma separated.
+  #[arg(
+    short,
+    long,
+    value_parser,
+    value_delimiter = ',',
+    default_value = "pem,der,jwk,jwks"
+  )]
+  pub format: Vec<KeyFormat>,
+  /// Key ID written in the JWKs [default: the RFC 7638 thumbprint of the key].
+  #[arg(long, value_parser)]
+  pub kid: Option<String>,
+  /// Overwrite existing files.
+  #[arg(long, value_parser, default_value_t = false)]
+  pub force: bool,
+}
+
+impl Cli {
+  fn validation_settings(&self) -> ValidationSettings {
+    ValidationSettings {
+      iss: self.iss.clone(),
+      aud: self.aud.clone(),
+      sub: self.sub.clone(),
+      leeway: self.leeway,
+      required_claims: self.require_claim.clone(),
+      validate_nbf: self.validate_nbf,
+      at: self.at,
+    }
+  }
+
+  fn new_app(&self, trust_store: Option<TrustStore>, schemas: Option<Schemas>) -> App {
+    let mut app = App::new(self.token.clone(), self.secret.clone());
+    app.data.schemas = schemas;
+    app
+      .data
+      .decoder
+      .set_validation_settings(&self.validation_settings());
+    app.data.decoder.trust_store = trust_store;
+    app
+  }
 }
 
 type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
+fn parse_at(value: &str) -> std::result::Result<i64, String> {
+  parse_instant(value).map_err(|e| e.to_string())
+}
+
 fn main() -> Result<()> {
   panic::set_hook(Box::new(|info| {
     panic_hook(info);
   }));
 
   // parse CLI arguments
-  let cli = Cli::parse();
+  let mut cli = Cli::parse();
 
   if cli.tick_rate >= 1000 {
     panic!("Tick rate must be below 1000");
   }
 
-  if (cli.stdout || cli.json) && cli.token.is_some() {
-    to_stdout(cli);
+  if let Some(Command::Keygen(args)) = &cli.command {
+    return keygen(args);
+  }
+
+  // keeps the token out of the shell history and process list
+  cli.token = cli
+    .token
+    .as_deref()
+    .map(read_token)
+    .transpose()
+    .map_err(|e| e.to_string())?;
+
+  jwks::set_offline(cli.offline);
+  let trust_store = TrustStore::load(cli.trust_store.as_deref()).map_err(|e| e.to_string())?;
+  let schemas = match &cli.schema {
+    Some(path) => Schemas::from_file(path).map(Some),
+    None => Schemas::load(cli.schemas.as_deref()),
+  }
+  .map_err(|e| e.to_string())?;
+
+  if !cli.batch.is_empty() {
+    batch(cli, trust_store, schemas)?;
+  } else if cli.lint {
+    lint(cli, trust_store, schemas);
+  } else if (cli.stdout || cli.json) && cli.token.is_some() {
+    to_stdout(cli, trust_store, schemas);
   } else {
     // The UI must run in the "main" thread
-    start_ui(cli)?;
+    start_ui(cli, trust_store, schemas)?;
   }
 
   Ok(())
 }
 
-fn to_stdout(cli: Cli) {
-  let mut app = App::new(cli.token.clone(), cli.secret.clone());
+fn to_stdout(cli: Cli, trust_store: Option<TrustStore>, schemas: Option<Schemas>) {
+  let mut app = cli.new_app(trust_store, schemas);
   // print decoded result to stdout
   decode_jwt_token(&mut app, cli.no_verify);
-  if app.data.error.is_empty() && app.data.decoder.is_decoded() {
-    print_decoded_token(app.data.decoder.get_decoded().as_ref().unwrap(), cli.json);
+  if let (Some(entry), false) = (&app.data.decoder.trust_entry, cli.json) {
+    println!("Using the key of trust store entry {entry}");
+  }
+  // JSON output includes the validation report, so print it even if validation failed.
+  // unsigned tokens never verify, so they are printed with the error
+  let unsigned = app.data.decoder.is_unsigned();
+  if app.data.decoder.is_decoded() && (cli.json || unsigned || app.data.error.is_empty()) {
+    print_decoded_token(
+      app.data.decoder.get_decoded().as_ref().unwrap(),
+      app.data.decoder.get_report(),
+      cli.json,
+    );
+    if unsigned && !cli.json && !app.data.error.is_empty() {
+      println!("\n{}", app.data.error);
+    }
+    // the violations are listed in the validation report of the JSON output
+    if !cli.json {
+      print_schema_violations(&app.data.decoder.schema_violations);
+    }
   } else {
+    // the protected header of a JWE, or the segments of a malformed token, are shown even when
+    // the token can't be decoded
+    let decoder = &app.data.decoder;
+    if !decoder.is_decoded() && !decoder.header.get_txt().is_empty() {
+      match decoder.problems.is_empty() && is_jwe(decoder.encoded.input.value()) {
+        true => println!("\nJWE header\n----------"),
+        false => println!("\nToken header\n------------"),
+      }
+      println!("{}\n", decoder.header.get_txt());
+    }
+    if !decoder.is_decoded() && !decoder.payload.get_txt().is_empty() {
+      println!("Token claims\n------------");
+      println!("{}\n", decoder.payload.get_txt());
+    }
+    println!("{}", app.data.error);
+    if decoder.problems.len() > 1 {
+      for problem in &decoder.problems {
+        println!("  × {}", problem.message);
+      }
+    }
+    // e.g. a token with a bad signature is still checked against its schema
+    print_schema_violations(&decoder.schema_violations);
+  }
+  if !app.data.decoder.schema_violations.is_empty() {
+    std::process::exit(1);
+  }
+}
+
+fn print_schema_violations(violations: &[SchemaViolation]) {
+  if !violations.is_empty() {
+    println!("\nSchema violations\n-----------------");
+    for violation in violations {
+      println!("  × {violation}");
+    }
+  }
+}
+
+fn lint(cli: Cli, trust_store: Option<TrustStore>, schemas: Option<Schemas>) {
+  let mut app = cli.new_app(trust_store, schemas);
+  decode_jwt_token(&mut app, true);
+  // a token without a readable header has nothing to lint
+  let findings = &app.data.decoder.findings.items;
+  if findings.is_empty() && !app.data.decoder.is_decoded() {
     println!("{}", app.data.error);
+    std::process::exit(1);
+  }
+  let errors = print_findings(findings, cli.json);
+  // the JSON output only has the findings
+  if !cli.json {
+    print_schema_violations(&app.data.decoder.schema_violations);
+  }
+  if errors || !app.data.decoder.schema_violations.is_empty() {
+    std::process::exit(1);
+  }
+}
+
+fn batch(cli: Cli, trust_store: Option<TrustStore>, schemas: Option<Schemas>) -> Result<()> {
+  let tokens = read_tokens(&cli.batch).map_err(|e| e.to_string())?;
+  let mut app = cli.new_app(trust_store, schemas);
+  let results = decode_batch(&mut app, tokens, cli.no_verify);
+  match cli.json {
+    true => println!("{}", to_ndjson(&results)),
+    false => println!("{}", to_summary(&results)),
+  }
+  if results.iter().any(|r| r.status != TokenStatus::Valid) {
+    std::process::exit(1);
   }
+  Ok(())
+}
+
+fn keygen(args: &KeygenArgs) -> Result<()> {
+  let pair = KeyPair::generate(args.key_type, args.kid.clone()).map_err(|e| e.to_string())?;
+  let files = pair
+    .write(&args.out, &args.format, args.force)
+    .map_err(|e| e.to_string())?;
+
+  println!("Generated {} key with kid {}", args.key_type, pair.kid);
+  if args.key_type.is_symmetric() {
+    println!("Secret: {}", pair.secrets(&args.out).0);
+  }
+  for file in files {
+    println!("{}", file.display());
+  }
+  Ok(())
 }
 
 /// Enable mouse capture, but don't enable capture of all the mouse movements, doing so will improve performance, and is part of the fix for the weird mouse event output bug
@@ -104,7 +354,25 @@ pub fn enable_mouse_capture() -> Result<()> {
   )
 }
 
-fn start_ui(cli: Cli) -> Result<()> {
+/// load the history before the UI starts, so that a file that can't be decrypted isn't overwritten
+fn load_history(cli: &Cli) -> Result<History> {
+  let path = match (cli.no_history, HistoryStore::default_path()) {
+    (false, Some(path)) => path,
+    _ => return Ok(History::default()),
+  };
+  let passphrase = std::env::var(PASSPHRASE_ENV).ok().filter(|p| !p.is_empty());
+  // the tokens are credentials, they are only written in plain text when asked for
+  if passphrase.is_none() && !cli.plain_history {
+    return Ok(History::default());
+  }
+  Ok(History::load(HistoryStore::new(path, passphrase)).map_err(|e| e.to_string())?)
+}
+
+fn start_ui(cli: Cli, trust_store: Option<TrustStore>, schemas: Option<Schemas>) -> Result<()> {
+  // JWKS are fetched on a background thread, so that the UI never waits for an issuer
+  jwks::set_background(true);
+  let history = load_history(&cli)?;
+  let templates = Templates::load().map_err(|e| e.to_string())?;
   // see https://docs.rs/crossterm/0.17.7/crossterm/terminal/#raw-mode
   enable_raw_mode()?;
   // Terminal initialization
@@ -122,7 +390,9 @@ fn start_ui(cli: Cli) -> Result<()> {
   // custom events
   let events = event::Events::new(cli.tick_rate);
 
-  let mut app = App::new(cli.token.clone(), cli.secret.clone());
+  let mut app = cli.new_app(trust_store, schemas);
+  app.data.history = history;
+  app.data.templates = templates;
   // main UI loop
   loop {
     // Get the size of the screen on each loop to account for resize event
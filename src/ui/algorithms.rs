@@ -0,0 +1,165 @@
+use ratatui::{
+  layout::{Constraint, Rect},
+  widgets::{Paragraph, Row, Table, Wrap},
+  Frame,
+};
+
+use super::{
+  utils::{
+    layout_block_with_line, style_default, style_highlight, style_primary, title_with_dual_style,
+    vertical_chunks,
+  },
+  HIGHLIGHT,
+};
+use crate::app::{algorithms::describe, ActiveBlock, App, Route, RouteId};
+
+pub fn draw_algorithms(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  let chunks = vertical_chunks(vec![Constraint::Length(3), Constraint::Min(0)], area);
+  draw_key_block(f, app, chunks[0]);
+  draw_algorithm_list_block(f, app, chunks[1]);
+}
+
+/// the key the algorithms were picked for
+fn draw_key_block(f: &mut Frame<'_>, app: &App, area: Rect) {
+  let block = layout_block_with_line(
+    title_with_dual_style(" Signing Secret ".into(), "".into()),
+    app.light_theme,
+    false,
+  );
+  let paragraph = Paragraph::new(app.data.algorithms.key.as_str())
+    .style(style_default(app.light_theme))
+    .block(block)
+    .wrap(Wrap { trim: true });
+  f.render_widget(paragraph, area);
+}
+
+fn draw_algorithm_list_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(
+    Route {
+      id: RouteId::Algorithms,
+      active_block: ActiveBlock::AlgorithmList,
+    },
+    area,
+  );
+
+  let block = layout_block_with_line(
+    title_with_dual_style(" Algorithm ".into(), "(<enter> use) ".into()),
+    app.light_theme,
+    true,
+  );
+
+  let rows = app.data.algorithms.items.items.iter().map(|alg| {
+    Row::new(vec![format!("{alg:?}"), describe(alg).into()]).style(style_primary(app.light_theme))
+  });
+
+  let table = Table::new(rows, [Constraint::Length(7), Constraint::Min(10)])
+    .block(block)
+    .row_highlight_style(style_highlight())
+    .highlight_symbol(HIGHLIGHT);
+
+  f.render_stateful_widget(table, area, &mut app.data.algorithms.items.state);
+}
+
+#[cfg(test)]
+mod tests {
+  use ratatui::{
+    backend::TestBackend,
+    layout::Position,
+    prelude::Buffer,
+    style::{Modifier, Style},
+    Terminal,
+  };
+
+  use super::*;
+  use crate::{
+    app::algorithms::open_picker,
+    ui::utils::{COLOR_CYAN, COLOR_WHITE, COLOR_YELLOW},
+  };
+
+  #[test]
+  fn test_draw_algorithms() {
+    let mut app = App::new(None, "secret".into());
+    app.route_encoder();
+    open_picker(&mut app);
+    app.data.algorithms.items.state.select(Some(1));
+
+    let backend = TestBackend::new(60, 9);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    terminal
+      .draw(|f| {
+        draw_algorithms(f, &mut app, f.area());
+      })
+      .unwrap();
+
+    let mut expected = Buffer::with_lines(vec![
+      r#"┌ Signing Secret ──────────────────────────────────────────┐"#,
+      r#"│Algorithms that fit the HMAC secret                       │"#,
+      r#"└──────────────────────────────────────────────────────────┘"#,
+      r#"┌ Algorithm (<enter> use) ─────────────────────────────────┐"#,
+      r#"│   HS256   HMAC using SHA-256                             │"#,
+      r#"│=> HS384   HMAC using SHA-384                             │"#,
+      r#"│   HS512   HMAC using SHA-512                             │"#,
+      r#"│                                                          │"#,
+      r#"└──────────────────────────────────────────────────────────┘"#,
+    ]);
+
+    // set expected row styles
+    for row in 0..=8 {
+      for col in 0..=59 {
+        match (col, row) {
+          (0 | 17..=59, 0) | (0..=59, 1..=2) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_WHITE));
+          }
+          (1..=58, 4 | 6) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_CYAN));
+          }
+          (1..=58, 5) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_CYAN)
+                  .add_modifier(Modifier::REVERSED),
+              );
+          }
+          (1..=16, 0) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_WHITE)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          (1..=11, 3) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_YELLOW)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          _ => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_YELLOW));
+          }
+        }
+      }
+    }
+
+    terminal.backend().assert_buffer(&expected);
+  }
+}
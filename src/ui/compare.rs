@@ -0,0 +1,337 @@
+use ratatui::{
+  layout::{Constraint, Rect},
+  text::Text,
+  widgets::{Block, Paragraph, Row, Table, Wrap},
+  Frame,
+};
+use serde_json::Value;
+
+use super::{
+  utils::{
+    get_selectable_block, horizontal_chunks, layout_block_with_line, render_input_widget,
+    style_default, style_failure, style_highlight, style_primary, style_secondary, style_success,
+    style_warning, title_with_dual_style, vertical_chunks, vertical_chunks_with_margin,
+  },
+  HIGHLIGHT,
+};
+use crate::app::{
+  compare::{DiffKind, DiffLine},
+  ActiveBlock, App, Route, RouteId, TextInput,
+};
+
+pub fn draw_compare(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  let chunks = vertical_chunks(
+    vec![
+      Constraint::Length(5),
+      Constraint::Length(6),
+      Constraint::Min(5),
+    ],
+    area,
+  );
+
+  let token_chunks = horizontal_chunks(
+    vec![Constraint::Percentage(50), Constraint::Percentage(50)],
+    chunks[0],
+  );
+  draw_token_block(f, app, token_chunks[0], ActiveBlock::CompareLeft);
+  draw_token_block(f, app, token_chunks[1], ActiveBlock::CompareRight);
+
+  let secret_chunks = horizontal_chunks(
+    vec![Constraint::Percentage(50), Constraint::Percentage(50)],
+    chunks[1],
+  );
+  draw_secret_block(f, app, secret_chunks[0]);
+  draw_verification_block(f, app, secret_chunks[1]);
+
+  draw_diff_block(f, app, chunks[2]);
+}
+
+fn draw_token_block(f: &mut Frame<'_>, app: &mut App, area: Rect, active_block: ActiveBlock) {
+  app.update_block_map(get_route(active_block), area);
+
+  let (title, input): (_, &TextInput) = match active_block {
+    ActiveBlock::CompareLeft => ("Left Token", &app.data.compare.left),
+    _ => ("Right Token", &app.data.compare.right),
+  };
+  let block = get_selectable_block(
+    title,
+    *app.data.compare.blocks.get_active_block() == active_block,
+    Some(&input.input_mode),
+    app.light_theme,
+  );
+
+  f.render_widget(block, area);
+
+  let chunks = vertical_chunks_with_margin(vec![Constraint::Min(2)], area, 1);
+  render_input_widget(f, chunks[0], input, app.light_theme);
+}
+
+fn draw_secret_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::CompareSecret), area);
+
+  let block = get_selectable_block(
+    "Secret",
+    *app.data.compare.blocks.get_active_block() == ActiveBlock::CompareSecret,
+    Some(&app.data.compare.secret.input_mode),
+    app.light_theme,
+  );
+
+  f.render_widget(block, area);
+
+  let chunks =
+    vertical_chunks_with_margin(vec![Constraint::Length(1), Constraint::Min(2)], area, 1);
+
+  let mut text = Text::from("Secret to verify both tokens with");
+  text = text.patch_style(style_default(app.light_theme));
+  let paragraph = Paragraph::new(text).block(Block::default());
+
+  f.render_widget(paragraph, chunks[0]);
+
+  render_input_widget(f, chunks[1], &app.data.compare.secret, app.light_theme);
+}
+
+fn draw_verification_block(f: &mut Frame<'_>, app: &App, area: Rect) {
+  let block = layout_block_with_line(
+    title_with_dual_style(" Verification ".into(), "".into()),
+    app.light_theme,
+    false,
+  );
+
+  let verification = &app.data.compare.verification;
+  let style = if verification.starts_with("Both") {
+    style_success(app.light_theme)
+  } else if verification.starts_with("Only") || verification.starts_with("Neither") {
+    style_failure(app.light_theme)
+  } else {
+    style_primary(app.light_theme)
+  };
+  let text = Text::from(verification.clone()).patch_style(style);
+
+  let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+  f.render_widget(paragraph, area);
+}
+
+fn draw_diff_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::CompareDiff), area);
+
+  let is_active = *app.data.compare.blocks.get_active_block() == ActiveBlock::CompareDiff;
+  let changes = app
+    .data
+    .compare
+    .diff
+    .items
+    .iter()
+    .filter(|line| line.kind != DiffKind::Unchanged)
+    .count();
+  let block = layout_block_with_line(
+    title_with_dual_style(" Diff ".into(), format!("({changes} changes) ")),
+    app.light_theme,
+    is_active,
+  );
+
+  let rows = app.data.compare.diff.items.iter().map(|line: &DiffLine| {
+    let (marker, style) = match line.kind {
+      DiffKind::Unchanged => (" ", style_primary(app.light_theme)),
+      DiffKind::Added => ("+", style_success(app.light_theme)),
+      DiffKind::Removed => ("-", style_failure(app.light_theme)),
+      DiffKind::Changed => ("~", style_warning(app.light_theme)),
+    };
+    Row::new(vec![
+      marker.to_string(),
+      line.path.clone(),
+      display_value(line.left.as_ref()),
+      display_value(line.right.as_ref()),
+      line.delta().unwrap_or_default(),
+    ])
+    .style(style)
+  });
+
+  let table = Table::new(
+    rows,
+    [
+      Constraint::Length(1),
+      Constraint::Percentage(25),
+      Constraint::Percentage(30),
+      Constraint::Percentage(30),
+      Constraint::Min(8),
+    ],
+  )
+  .header(
+    Row::new(vec!["", "Path", "Left", "Right", "Delta"]).style(style_secondary(app.light_theme)),
+  )
+  .block(block)
+  .row_highlight_style(style_highlight())
+  .highlight_symbol(HIGHLIGHT);
+
+  f.render_stateful_widget(table, area, &mut app.data.compare.diff.state);
+}
+
+fn display_value(value: Option<&Value>) -> String {
+  match value {
+    Some(Value::String(s)) => s.clone(),
+    Some(v) => v.to_string(),
+    None => String::new(),
+  }
+}
+
+fn get_route(active_block: ActiveBlock) -> Route {
+  Route {
+    id: RouteId::Compare,
+    active_block,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ratatui::{
+    backend::TestBackend,
+    layout::Position,
+    prelude::Buffer,
+    style::{Modifier, Style},
+    Terminal,
+  };
+  use serde_json::json;
+
+  use super::*;
+  use crate::ui::utils::{
+    COLOR_CYAN, COLOR_GREEN, COLOR_ORANGE, COLOR_RED, COLOR_WHITE, COLOR_YELLOW,
+  };
+
+  fn line(path: &str, kind: DiffKind, left: Option<Value>, right: Option<Value>) -> DiffLine {
+    DiffLine {
+      path: path.into(),
+      kind,
+      left,
+      right,
+    }
+  }
+
+  #[test]
+  fn test_draw_compare() {
+    let mut app = App::default();
+    app.route_compare();
+    app.data.compare.diff.set_items(vec![
+      line(
+        "header.alg",
+        DiffKind::Unchanged,
+        Some(json!("HS256")),
+        Some(json!("HS256")),
+      ),
+      line(
+        "claims.exp",
+        DiffKind::Changed,
+        Some(json!(1516239022)),
+        Some(json!(1516242622)),
+      ),
+      line(
+        "claims.roles[1]",
+        DiffKind::Removed,
+        Some(json!("user")),
+        None,
+      ),
+      line("claims.scope", DiffKind::Added, None, Some(json!("read"))),
+    ]);
+    app.data.compare.verification = "Both tokens verify with the secret".into();
+
+    let backend = TestBackend::new(100, 19);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    terminal
+      .draw(|f| {
+        draw_compare(f, &mut app, f.area());
+      })
+      .unwrap();
+
+    let mut expected = Buffer::with_lines(vec![
+      r#"┌ Left Token (<enter> edit | <c> copy) ──────────┐┌ Right Token ───────────────────────────────────┐"#,
+      r#"│┌──────────────────────────────────────────────┐││┌──────────────────────────────────────────────┐│"#,
+      r#"││                                              ││││                                              ││"#,
+      r#"│└──────────────────────────────────────────────┘││└──────────────────────────────────────────────┘│"#,
+      r#"└────────────────────────────────────────────────┘└────────────────────────────────────────────────┘"#,
+      r#"┌ Secret ────────────────────────────────────────┐┌ Verification ──────────────────────────────────┐"#,
+      r#"│Secret to verify both tokens with               ││Both tokens verify with the secret              │"#,
+      r#"│┌──────────────────────────────────────────────┐││                                                │"#,
+      r#"││                                              │││                                                │"#,
+      r#"│└──────────────────────────────────────────────┘││                                                │"#,
+      r#"└────────────────────────────────────────────────┘└────────────────────────────────────────────────┘"#,
+      r#"┌ Diff (3 changes) ────────────────────────────────────────────────────────────────────────────────┐"#,
+      r#"│     Path                     Left                         Right                         Delta    │"#,
+      r#"│=>   header.alg               HS256                        HS256                                  │"#,
+      r#"│   ~ claims.exp               1516239022                   1516242622                    +1h      │"#,
+      r#"│   - claims.roles[1]          user                                                                │"#,
+      r#"│   + claims.scope                                          read                                   │"#,
+      r#"│                                                                                                  │"#,
+      r#"└──────────────────────────────────────────────────────────────────────────────────────────────────┘"#,
+    ]);
+
+    // set expected row styles
+    for row in 0..=18 {
+      for col in 0..=99 {
+        match (col, row) {
+          (0 | 13..=49, 0) | (0 | 49, 1..=3) | (0..=49, 4) | (1..=98, 12) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_YELLOW));
+          }
+          (51..=84, 6) | (1..=98, 16) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_GREEN));
+          }
+          (1..=98, 13) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_CYAN)
+                  .add_modifier(Modifier::REVERSED),
+              );
+          }
+          (1..=98, 14) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_ORANGE));
+          }
+          (1..=98, 15) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_RED));
+          }
+          (51..=63, 0) | (1..=8 | 51..=64, 5) | (1..=6, 11) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_WHITE)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          (1..=12, 0) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_YELLOW)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          _ => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_WHITE));
+          }
+        }
+      }
+    }
+
+    terminal.backend().assert_buffer(&expected);
+  }
+}
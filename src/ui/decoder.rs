@@ -1,33 +1,166 @@
 use ratatui::{
-  layout::{Constraint, Rect},
-  text::Text,
-  widgets::{Block, Paragraph, Wrap},
+  layout::{Constraint, Position, Rect},
+  style::{Modifier, Style},
+  text::{Line, Span, Text},
+  widgets::{Block, Paragraph, Row, Table, Wrap},
   Frame,
 };
 
-use super::utils::{
-  get_selectable_block, horizontal_chunks, render_input_widget, style_default, style_primary,
-  vertical_chunks, vertical_chunks_with_margin,
+use super::{
+  utils::{
+    get_selectable_block, horizontal_chunks, layout_block_with_line, render_input_widget,
+    render_styled_input_widget, style_default, style_failure, style_help, style_highlight,
+    style_primary, style_secondary, style_segment, style_success, style_warning,
+    title_with_dual_style, token_styles, vertical_chunks, vertical_chunks_with_margin,
+  },
+  HIGHLIGHT,
+};
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::app::{
+  annotations,
+  json_tree::{JsonTree, RowKind, TreeRow},
+  jwt_decoder::TokenSegment,
+  lint::{Finding, Severity},
+  timeline::LifetimeStatus,
+  validation::{Check, CheckStatus},
+  ActiveBlock, App, InputMode, Route, RouteId,
 };
-use crate::app::{ActiveBlock, App, Route, RouteId};
 
 pub fn draw_decoder(f: &mut Frame<'_>, app: &mut App, area: Rect) {
-  let chunks = horizontal_chunks(
-    vec![Constraint::Percentage(50), Constraint::Percentage(50)],
+  // report rows, table header and borders
+  let report_height = match app.data.decoder.report.items.len() {
+    0 => 0,
+    len => len as u16 + 3,
+  };
+  let findings_height = match app.data.decoder.findings.items.len() {
+    0 => 0,
+    len => len as u16 + 3,
+  };
+  // labels, bar and borders
+  let timeline_height = match app.data.decoder.timeline {
+    Some(_) => 4,
+    None => 0,
+  };
+  let chunks = vertical_chunks(
+    vec![
+      Constraint::Min(10),
+      Constraint::Length(timeline_height),
+      Constraint::Length(report_height),
+      Constraint::Length(findings_height),
+    ],
     area,
   );
-  draw_left_side(f, app, chunks[0]);
-  draw_right_side(f, app, chunks[1]);
+
+  let main_chunks = horizontal_chunks(
+    vec![Constraint::Percentage(50), Constraint::Percentage(50)],
+    chunks[0],
+  );
+  draw_left_side(f, app, main_chunks[0]);
+  draw_right_side(f, app, main_chunks[1]);
+  draw_timeline_block(f, app, chunks[1]);
+  if report_height > 0 {
+    draw_report_block(f, app, chunks[2]);
+  }
+  if findings_height > 0 {
+    draw_findings_block(f, app, chunks[3]);
+  }
+}
+
+/// `nbf`, `iat`, now and `exp` on one bar, with a countdown to the expiry
+fn draw_timeline_block(f: &mut Frame<'_>, app: &App, area: Rect) {
+  let Some(timeline) = &app.data.decoder.timeline else {
+    return;
+  };
+  let light = app.light_theme;
+  let style = match timeline.status() {
+    LifetimeStatus::NotYetValid => style_primary(light),
+    LifetimeStatus::Valid => style_success(light),
+    LifetimeStatus::ExpiringSoon => style_warning(light),
+    LifetimeStatus::Expired => style_failure(light),
+  };
+  let block = layout_block_with_line(
+    Line::from(vec![
+      Span::styled(" Lifetime: ", Style::default().add_modifier(Modifier::BOLD)),
+      Span::styled(format!("{} ", timeline.countdown()), style),
+    ]),
+    light,
+    false,
+  );
+  let inner = block.inner(area);
+  f.render_widget(block, area);
+
+  let width = inner.width as usize;
+  let now = timeline.position(timeline.now, inner.width) as usize;
+  let mut bar: Vec<Span<'_>> = (0..width)
+    .map(|i| match i <= now {
+      true => Span::styled("━", style),
+      false => Span::styled("─", style_default(light)),
+    })
+    .collect();
+  let mut markers: Vec<_> = timeline
+    .markers()
+    .into_iter()
+    .map(|(label, time)| {
+      let label_style = match label {
+        "now" => style,
+        _ => style_default(light),
+      };
+      (
+        label,
+        timeline.position(time, inner.width) as usize,
+        label_style,
+      )
+    })
+    .collect();
+  markers.sort_by_key(|(_, position, _)| *position);
+  for (label, position, label_style) in &markers {
+    if let Some(cell) = bar.get_mut(*position) {
+      let marker = if *label == "now" { "●" } else { "┃" };
+      *cell = Span::styled(marker, *label_style);
+    }
+  }
+
+  // each label starts at its marker, moved right and then left so that none overlap or are cut off
+  let mut starts = vec![];
+  let mut next = 0;
+  for (label, position, _) in &markers {
+    let start = (*position).max(next);
+    next = start + label.len() + 1;
+    starts.push(start);
+  }
+  let mut limit = width;
+  for (start, (label, _, _)) in starts.iter_mut().zip(&markers).rev() {
+    *start = (*start).min(limit.saturating_sub(label.len()));
+    limit = start.saturating_sub(1);
+  }
+  let mut labels = vec![Span::raw(" "); width];
+  for (start, (label, _, label_style)) in starts.into_iter().zip(&markers) {
+    for (i, c) in label.chars().enumerate() {
+      if let Some(cell) = labels.get_mut(start + i) {
+        *cell = Span::styled(c.to_string(), *label_style);
+      }
+    }
+  }
+
+  let paragraph = Paragraph::new(vec![Line::from(labels), Line::from(bar)]);
+  f.render_widget(paragraph, inner);
 }
 
 fn draw_left_side(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   let chunks = vertical_chunks(
-    vec![Constraint::Percentage(70), Constraint::Percentage(30)],
+    vec![
+      Constraint::Min(4),
+      Constraint::Length(6),
+      Constraint::Length(6),
+    ],
     area,
   );
 
   draw_token_block(f, app, chunks[0]);
   draw_secret_block(f, app, chunks[1]);
+  draw_validation_block(f, app, chunks[2]);
 }
 
 fn draw_right_side(f: &mut Frame<'_>, app: &mut App, area: Rect) {
@@ -51,15 +184,48 @@ fn draw_token_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
 
   f.render_widget(block, area);
 
-  let chunks = vertical_chunks_with_margin(vec![Constraint::Min(2)], area, 1);
-  render_input_widget(f, chunks[0], &app.data.decoder.encoded, app.light_theme);
+  let problems = &app.data.decoder.problems;
+  let chunks = vertical_chunks_with_margin(
+    vec![
+      Constraint::Min(2),
+      Constraint::Length(problems.len() as u16),
+    ],
+    area,
+    1,
+  );
+  let highlight = style_failure(app.light_theme).add_modifier(Modifier::REVERSED);
+  let mut styles = token_styles(
+    app.data.decoder.encoded.input.value(),
+    app.data.decoder.highlighted_segment(),
+    app.light_theme,
+  );
+  styles.extend(problems.iter().map(|p| (p.span.clone(), highlight)));
+  render_styled_input_widget(
+    f,
+    chunks[0],
+    &app.data.decoder.encoded,
+    &styles,
+    app.light_theme,
+  );
+
+  if !problems.is_empty() {
+    let text: Vec<Line<'_>> = problems
+      .iter()
+      .map(|p| Line::from(format!("× {}", p.message)))
+      .collect();
+    let paragraph = Paragraph::new(Text::from(text).patch_style(style_failure(app.light_theme)));
+    f.render_widget(paragraph, chunks[1]);
+  }
 }
 
 fn draw_secret_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   app.update_block_map(get_route(ActiveBlock::DecoderSecret), area);
 
   let block = get_selectable_block(
-    check_verification_status(app.data.decoder.signature_verified),
+    check_verification_status(
+      app.data.decoder.signature_verified,
+      app.data.decoder.is_unsigned(),
+    ),
     *app.data.decoder.blocks.get_active_block() == ActiveBlock::DecoderSecret,
     Some(&app.data.decoder.secret.input_mode),
     app.light_theme,
@@ -70,10 +236,18 @@ fn draw_secret_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   let chunks =
     vertical_chunks_with_margin(vec![Constraint::Length(1), Constraint::Min(2)], area, 1);
 
-  let mut text = Text::from(
-    "Prepend 'b64:' for base64 encoded secret. Prepend '@' for file path (.pem, .pk8, .der, .json)",
-  );
-  text = text.patch_style(style_default(app.light_theme));
+  let text = match &app.data.decoder.trust_entry {
+    _ if app.data.decoder.is_unsigned() => {
+      Text::from("UNSIGNED token (`alg: none`), anyone can forge its claims")
+        .patch_style(style_failure(app.light_theme))
+    }
+    Some(entry) => Text::from(format!("Using the key of trust store entry {entry}"))
+      .patch_style(style_success(app.light_theme)),
+    None => Text::from(
+      "Prepend 'b64:' for base64 encoded secret. Prepend '@' for file path (.pem, .pk8, .der, .json)",
+    )
+    .patch_style(style_default(app.light_theme)),
+  };
   let paragraph = Paragraph::new(text).block(Block::default());
 
   f.render_widget(paragraph, chunks[0]);
@@ -81,61 +255,326 @@ fn draw_secret_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   render_input_widget(f, chunks[1], &app.data.decoder.secret, app.light_theme);
 }
 
-fn check_verification_status(signature_verified: bool) -> &'static str {
-  if signature_verified {
-    "Signature: Valid ✔"
-  } else {
-    "Signature: Invalid ×"
-  }
-}
-
-fn draw_header_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
-  app.update_block_map(get_route(ActiveBlock::DecoderHeader), area);
+fn draw_validation_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::DecoderValidation), area);
 
   let block = get_selectable_block(
-    "Header: Algorithm & Token Type",
-    *app.data.decoder.blocks.get_active_block() == ActiveBlock::DecoderHeader,
-    None,
+    "Claim Validation",
+    *app.data.decoder.blocks.get_active_block() == ActiveBlock::DecoderValidation,
+    Some(&app.data.decoder.validation.input_mode),
     app.light_theme,
   );
 
   f.render_widget(block, area);
 
-  let chunks = vertical_chunks_with_margin(vec![Constraint::Min(2)], area, 1);
+  let chunks =
+    vertical_chunks_with_margin(vec![Constraint::Length(1), Constraint::Min(2)], area, 1);
 
-  let header = app.data.decoder.header.get_txt();
-  let mut txt = Text::from(header.clone());
-  txt = txt.patch_style(style_primary(app.light_theme));
+  let mut text = Text::from("Rules: iss=a,b aud=a,b sub=s leeway=secs require=claim,claim nbf");
+  text = text.patch_style(style_default(app.light_theme));
+  let paragraph = Paragraph::new(text).block(Block::default());
 
-  let paragraph = Paragraph::new(txt)
-    .block(Block::default())
-    .wrap(Wrap { trim: false })
-    .scroll((app.data.decoder.header.offset, 0));
   f.render_widget(paragraph, chunks[0]);
+
+  render_input_widget(f, chunks[1], &app.data.decoder.validation, app.light_theme);
 }
 
-fn draw_payload_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
-  app.update_block_map(get_route(ActiveBlock::DecoderPayload), area);
+fn draw_report_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::DecoderReport), area);
+
+  let block = get_selectable_block(
+    "Validation Report",
+    *app.data.decoder.blocks.get_active_block() == ActiveBlock::DecoderReport,
+    None,
+    app.light_theme,
+  );
+
+  let light_theme = app.light_theme;
+  let rows = app.data.decoder.report.items.iter().map(|c: &Check| {
+    let style = match c.status {
+      CheckStatus::Pass => style_success(light_theme),
+      CheckStatus::Fail => style_failure(light_theme),
+      CheckStatus::Warn => style_warning(light_theme),
+      CheckStatus::Skip => style_default(light_theme),
+    };
+    Row::new(vec![
+      c.check.clone(),
+      c.status.to_string(),
+      c.expected.clone(),
+      c.actual.clone(),
+      c.explanation.clone(),
+    ])
+    .style(style)
+  });
+
+  let table = Table::new(
+    rows,
+    [
+      Constraint::Length(10),
+      Constraint::Length(6),
+      Constraint::Fill(2),
+      Constraint::Fill(2),
+      Constraint::Fill(3),
+    ],
+  )
+  .header(
+    Row::new(vec!["Check", "Status", "Expected", "Actual", "Explanation"])
+      .style(style_secondary(light_theme)),
+  )
+  .block(block)
+  .row_highlight_style(style_highlight())
+  .highlight_symbol(HIGHLIGHT);
+
+  f.render_stateful_widget(table, area, &mut app.data.decoder.report.state);
+}
+
+fn draw_findings_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::DecoderFindings), area);
 
   let block = get_selectable_block(
-    "Payload: Claims",
-    *app.data.decoder.blocks.get_active_block() == ActiveBlock::DecoderPayload,
+    "Findings",
+    *app.data.decoder.blocks.get_active_block() == ActiveBlock::DecoderFindings,
     None,
     app.light_theme,
   );
+
+  let light_theme = app.light_theme;
+  let rows = app.data.decoder.findings.items.iter().map(|f: &Finding| {
+    let style = match f.severity {
+      Severity::Error => style_failure(light_theme),
+      Severity::Warning => style_warning(light_theme),
+      Severity::Info => style_default(light_theme),
+    };
+    Row::new(vec![
+      f.severity.to_string(),
+      f.rule.to_string(),
+      f.message.clone(),
+    ])
+    .style(style)
+  });
+
+  let table = Table::new(
+    rows,
+    [
+      Constraint::Length(8),
+      Constraint::Length(14),
+      Constraint::Fill(1),
+    ],
+  )
+  .header(Row::new(vec!["Severity", "Rule", "Message"]).style(style_secondary(light_theme)))
+  .block(block)
+  .row_highlight_style(style_highlight())
+  .highlight_symbol(HIGHLIGHT);
+
+  f.render_stateful_widget(table, area, &mut app.data.decoder.findings.state);
+}
+
+fn check_verification_status(signature_verified: bool, unsigned: bool) -> &'static str {
+  if unsigned {
+    "Signature: UNSIGNED ⚠"
+  } else if signature_verified {
+    "Signature: Valid ✔"
+  } else {
+    "Signature: Invalid ×"
+  }
+}
+
+fn draw_header_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  draw_json_block(
+    f,
+    app,
+    area,
+    ActiveBlock::DecoderHeader,
+    "Header: Algorithm & Token Type",
+  );
+}
+
+fn draw_payload_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  draw_json_block(f, app, area, ActiveBlock::DecoderPayload, "Payload: Claims");
+}
+
+fn draw_json_block(
+  f: &mut Frame<'_>,
+  app: &mut App,
+  area: Rect,
+  active_block: ActiveBlock,
+  title: &str,
+) {
+  app.update_block_map(get_route(active_block), area);
+
+  let light = app.light_theme;
+  let is_active = *app.data.decoder.blocks.get_active_block() == active_block;
+  let annotate = app.data.decoder.annotations;
+  let is_header = active_block == ActiveBlock::DecoderHeader;
+  let now = app
+    .data
+    .decoder
+    .validated_at()
+    .unwrap_or_else(|| Utc::now().timestamp());
+  let key_style = match active_block {
+    ActiveBlock::DecoderHeader => json_style(app, TokenSegment::Header),
+    _ => json_style(app, TokenSegment::Payload),
+  };
+  let tree = match active_block {
+    ActiveBlock::DecoderHeader => &mut app.data.decoder.header,
+    _ => &mut app.data.decoder.payload,
+  };
+
+  let title_hint = match (is_active, &tree.search.input_mode) {
+    (true, InputMode::Normal) => "(<enter> fold | </> search | <c> copy) ",
+    (true, InputMode::Editing) => "(<enter> next match | <esc> stop searching) ",
+    (false, _) => "",
+  };
+  let block = layout_block_with_line(
+    title_with_dual_style(format!(" {title} "), title_hint.into()),
+    light,
+    is_active,
+  );
   f.render_widget(block, area);
 
-  let chunks = vertical_chunks_with_margin(vec![Constraint::Min(2)], area, 1);
+  let annotation = |row: &TreeRow| {
+    annotate
+      .then(|| annotations::annotate(row, is_header, now))
+      .flatten()
+  };
+  // the full annotation of the selected row, as it may be cut off
+  let selected_annotation = tree.selected().and_then(annotation).filter(|_| is_active);
+  let annotation_height = match &selected_annotation {
+    Some(text) => (text.chars().count() as u16 / area.width.saturating_sub(2).max(1) + 1).min(3),
+    None => 0,
+  };
+  let searching =
+    tree.search.input_mode == InputMode::Editing || !tree.search.input.value().is_empty();
+  let chunks = vertical_chunks_with_margin(
+    vec![
+      Constraint::Min(1),
+      Constraint::Length(annotation_height),
+      Constraint::Length(searching as u16),
+    ],
+    area,
+    1,
+  );
 
-  let payload = app.data.decoder.payload.get_txt();
-  let mut txt = Text::from(payload.clone());
-  txt = txt.patch_style(style_primary(app.light_theme));
+  let rows: Vec<Row<'_>> = tree
+    .rows
+    .items
+    .iter()
+    .map(|row| {
+      let mut line = tree_line(row, tree.is_match(row), key_style, light);
+      if let Some(annotation) = annotation(row) {
+        line.push_span(Span::styled(
+          format!("  // {annotation}"),
+          style_annotation(light),
+        ));
+      }
+      Row::new(vec![line])
+    })
+    .collect();
+  let mut table = Table::new(rows, [Constraint::Percentage(100)]);
+  if is_active {
+    table = table.row_highlight_style(style_highlight());
+  }
+  f.render_stateful_widget(table, chunks[0], &mut tree.rows.state);
 
-  let paragraph = Paragraph::new(txt)
-    .block(Block::default())
-    .wrap(Wrap { trim: false })
-    .scroll((app.data.decoder.payload.offset, 0));
-  f.render_widget(paragraph, chunks[0]);
+  if let Some(text) = selected_annotation {
+    let paragraph = Paragraph::new(Text::from(text).patch_style(style_annotation(light)))
+      .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, chunks[1]);
+  }
+  if searching {
+    draw_search_line(f, tree, chunks[2], light);
+  }
+}
+
+fn style_annotation(light: bool) -> Style {
+  style_default(light).add_modifier(Modifier::ITALIC)
+}
+
+/// a row of the JSON tree, with the keys in the given style and the values coloured by type
+fn tree_line(row: &TreeRow, is_match: bool, key_style: Style, light: bool) -> Line<'static> {
+  let marker = match row.kind {
+    RowKind::Open {
+      collapsed: true, ..
+    } => "▸ ",
+    RowKind::Open { .. } => "▾ ",
+    _ => "  ",
+  };
+  let mut spans = vec![Span::raw(format!("{}{marker}", "  ".repeat(row.depth)))];
+  if let Some(key) = &row.key {
+    let style = match is_match {
+      true => style_warning(light).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+      false => key_style,
+    };
+    spans.push(Span::styled(format!("\"{key}\""), style));
+    spans.push(Span::styled(": ", key_style));
+  }
+  match &row.kind {
+    RowKind::Leaf(value) => {
+      let style = match value {
+        Value::String(_) => style_success(light),
+        Value::Number(_) => style_warning(light),
+        _ => style_help(light),
+      };
+      spans.push(Span::styled(value.to_string(), style));
+    }
+    RowKind::Open {
+      bracket,
+      len,
+      collapsed: true,
+    } => {
+      let (close, entries) = match bracket {
+        '{' => ('}', "key"),
+        _ => (']', "item"),
+      };
+      let plural = if *len == 1 { "" } else { "s" };
+      spans.push(Span::styled(format!("{bracket}…{close}"), key_style));
+      spans.push(Span::styled(
+        format!(" {len} {entries}{plural}"),
+        style_default(light),
+      ));
+    }
+    RowKind::Open { bracket, .. } => spans.push(Span::styled(bracket.to_string(), key_style)),
+    RowKind::Close(bracket) => spans.push(Span::styled(bracket.to_string(), key_style)),
+  }
+  if row.comma {
+    spans.push(Span::styled(",", key_style));
+  }
+  Line::from(spans)
+}
+
+fn draw_search_line(f: &mut Frame<'_>, tree: &JsonTree, area: Rect, light: bool) {
+  let matches = tree.matches();
+  let status = match tree
+    .rows
+    .state
+    .selected()
+    .and_then(|selected| matches.iter().position(|i| *i == selected))
+  {
+    _ if matches.is_empty() => "  no matches".to_string(),
+    Some(i) => format!("  {} of {}", i + 1, matches.len()),
+    None => format!("  {} matches", matches.len()),
+  };
+  let line = Line::from(vec![
+    Span::styled("/", style_secondary(light)),
+    Span::styled(tree.search.input.value().to_string(), style_default(light)),
+    Span::styled(status, style_secondary(light)),
+  ]);
+  f.render_widget(Paragraph::new(line), area);
+
+  if tree.search.input_mode == InputMode::Editing {
+    f.set_cursor_position(Position {
+      x: area.x + tree.search.input.visual_cursor() as u16 + 1,
+      y: area.y,
+    });
+  }
+}
+
+/// the JSON of the highlighted segment is shown in the colour of the segment
+fn json_style(app: &App, segment: TokenSegment) -> Style {
+  match app.data.decoder.highlighted_segment() == Some(segment) {
+    true => style_segment(segment, app.light_theme),
+    false => style_primary(app.light_theme),
+  }
 }
 
 fn get_route(active_block: ActiveBlock) -> Route {
@@ -156,7 +595,10 @@ mod tests {
   };
 
   use super::*;
-  use crate::ui::utils::{COLOR_CYAN, COLOR_WHITE, COLOR_YELLOW};
+  use crate::app::timeline::Timeline;
+  use crate::ui::utils::{
+    COLOR_CYAN, COLOR_GREEN, COLOR_ORANGE, COLOR_RED, COLOR_WHITE, COLOR_YELLOW,
+  };
 
   #[test]
   fn test_draw_decoder() {
@@ -165,9 +607,11 @@ mod tests {
         "secret".into()
     );
 
+    // the times of the timestamp claims depend on the clock and the time zone
+    app.data.decoder.annotations = false;
     app.on_tick();
 
-    let backend = TestBackend::new(100, 20);
+    let backend = TestBackend::new(100, 41);
     let mut terminal = Terminal::new(backend).unwrap();
 
     terminal
@@ -178,67 +622,199 @@ mod tests {
 
     let mut expected = Buffer::with_lines(vec![
       r#"┌ Encoded Token (<enter> edit | <c> copy) ───────┐┌ Header: Algorithm & Token Type ────────────────┐"#,
-      r#"│┌──────────────────────────────────────────────┐││{                                               │"#,
-      r#"││eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiO│││  "typ": "JWT",                                 │"#,
-      r#"││iIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF│││  "alg": "HS256"                                │"#,
-      r#"││0IjoxNTE2MjM5MDIyfQ.XbPfbIHMI6arZ3Y922BhjWgQzW│││}                                               │"#,
+      r#"│┌──────────────────────────────────────────────┐││▾ {                                             │"#,
+      r#"││eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiO│││    "alg": "HS256",                             │"#,
+      r#"││iIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF│││    "typ": "JWT"                                │"#,
+      r#"││0IjoxNTE2MjM5MDIyfQ.XbPfbIHMI6arZ3Y922BhjWgQzW│││  }                                             │"#,
       r#"││XcXNrz0ogtVhfEd2o                             │││                                                │"#,
       r#"││                                              │││                                                │"#,
+      r#"││                                              │││                                                │"#,
       r#"││                                              ││└────────────────────────────────────────────────┘"#,
-      r#"││                                              ││┌ Payload: Claims ───────────────────────────────┐"#,
-      r#"││                                              │││{                                               │"#,
-      r#"││                                              │││  "iat": 1516239022,                            │"#,
-      r#"││                                              │││  "name": "John Doe",                           │"#,
-      r#"│└──────────────────────────────────────────────┘││  "sub": "1234567890"                           │"#,
-      r#"└────────────────────────────────────────────────┘│}                                               │"#,
-      r#"┌ Signature: Valid ✔ ────────────────────────────┐│                                                │"#,
-      r#"│Prepend 'b64:' for base64 encoded secret. Prepen││                                                │"#,
+      r#"│└──────────────────────────────────────────────┘│┌ Payload: Claims ───────────────────────────────┐"#,
+      r#"└────────────────────────────────────────────────┘│▾ {                                             │"#,
+      r#"┌ Signature: Valid ✔ ────────────────────────────┐│    "iat": 1516239022,                          │"#,
+      r#"│Prepend 'b64:' for base64 encoded secret. Prepen││    "name": "John Doe",                         │"#,
+      r#"│┌──────────────────────────────────────────────┐││    "sub": "1234567890"                         │"#,
+      r#"││secret                                        │││  }                                             │"#,
+      r#"│└──────────────────────────────────────────────┘││                                                │"#,
+      r#"└────────────────────────────────────────────────┘│                                                │"#,
+      r#"┌ Claim Validation ──────────────────────────────┐│                                                │"#,
+      r#"│Rules: iss=a,b aud=a,b sub=s leeway=secs require││                                                │"#,
       r#"│┌──────────────────────────────────────────────┐││                                                │"#,
-      r#"││secret                                        │││                                                │"#,
+      r#"││leeway=1000                                   │││                                                │"#,
       r#"│└──────────────────────────────────────────────┘││                                                │"#,
       r#"└────────────────────────────────────────────────┘└────────────────────────────────────────────────┘"#,
+      r#"┌ Validation Report ───────────────────────────────────────────────────────────────────────────────┐"#,
+      r#"│   Check      Status Expected              Actual                 Explanation                     │"#,
+      r#"│=> signature  ✔ pass valid                 valid                  Signature verified with the secr│"#,
+      r#"│   alg        ✔ pass HS256, HS384, HS512   HS256                  Algorithm is supported by the se│"#,
+      r#"│   kid lookup - skip key id present in the not set                Keys are only looked up by `kid`│"#,
+      r#"│   typ        ✔ pass JWT or *+jwt          JWT                    Token type is JWT               │"#,
+      r#"│   exp        ! warn not expired (leeway 1 not set                The token never expires         │"#,
+      r#"│   nbf        - skip not in the future (le not set                No `nbf` claim                  │"#,
+      r#"│   iat        ✔ pass not in the future (le 2018-01-18T01:30:22Z   The token was issued in the past│"#,
+      r#"│   iss        - skip -                     not set                No expected `iss` configured    │"#,
+      r#"│   aud        - skip -                     not set                No expected `aud` configured    │"#,
+      r#"│   sub        - skip -                     1234567890             No expected `sub` configured    │"#,
+      r#"└──────────────────────────────────────────────────────────────────────────────────────────────────┘"#,
+      r#"┌ Findings ────────────────────────────────────────────────────────────────────────────────────────┐"#,
+      r#"│   Severity Rule           Message                                                                │"#,
+      r#"│=> error    weak-secret    The HMAC secret is a common password and can be guessed                │"#,
+      r#"│   warning  missing-exp    The token has no `exp` claim and never expires                         │"#,
+      r#"└──────────────────────────────────────────────────────────────────────────────────────────────────┘"#,
     ]);
 
     // set expected row styles
-    for row in 0..=19 {
+    for row in 0..=40 {
       for col in 0..=99 {
         match (col, row) {
-          (1..=15, 0) => {
+          (0 | 16..=49, 0)
+          | (0 | 49, 1 | 5..=9)
+          | (0 | 39..=47 | 49, 2)
+          | (0 | 2..=47 | 49, 3)
+          | (0 | 2..=20 | 49, 4)
+          | (0..=49, 10)
+          | (1..=98, 24 | 37) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_YELLOW));
+          }
+          (62..=68, 2) | (62..=66, 3) | (63..=72, 12) | (62..=73, 13) | (1..=98, 26 | 28 | 31) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_GREEN));
+          }
+          (62..=71, 11) | (1..=98, 29 | 39) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_ORANGE));
+          }
+          (51..=82, 0)
+          | (51..=67, 9)
+          | (1..=20, 11)
+          | (1..=18, 17)
+          | (1..=19, 23)
+          | (1..=10, 36) => {
             expected
               .cell_mut(Position::new(col, row))
               .unwrap()
               .set_style(
                 Style::default()
-                  .fg(COLOR_YELLOW)
+                  .fg(COLOR_WHITE)
                   .add_modifier(Modifier::BOLD),
               );
           }
-          (51..=82, 0) | (51..=67, 8) | (1..=20, 14) => {
+          (1..=98, 25) => {
             expected
               .cell_mut(Position::new(col, row))
               .unwrap()
               .set_style(
                 Style::default()
-                  .fg(COLOR_WHITE)
-                  .add_modifier(Modifier::BOLD),
+                  .fg(COLOR_GREEN)
+                  .add_modifier(Modifier::REVERSED),
               );
           }
-          (0 | 16..=49, 0) | (0..=49, 13) | (0 | 49, 1..=13 | 20..=99) => {
+          (1..=98, 38) => {
             expected
               .cell_mut(Position::new(col, row))
               .unwrap()
-              .set_style(Style::default().fg(COLOR_YELLOW));
+              .set_style(
+                Style::default()
+                  .fg(COLOR_RED)
+                  .add_modifier(Modifier::REVERSED),
+              );
           }
-          (51, 1 | 4 | 9 | 11 | 13)
-          | (51..=65, 2)
-          | (51..=66, 3)
-          | (51..=70, 10 | 12)
-          | (52..=71, 11 | 12) => {
+          (53, 1 | 10 | 14)
+          | (55..=61 | 69, 2)
+          | (55..=61, 3 | 13)
+          | (22..=47 | 53, 4)
+          | (2..=18, 5)
+          | (55..=61 | 72, 11)
+          | (55..=62 | 73, 12) => {
             expected
               .cell_mut(Position::new(col, row))
               .unwrap()
               .set_style(Style::default().fg(COLOR_CYAN));
           }
+          (2..=37, 2) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_RED));
+          }
+          (1..=15, 0) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_YELLOW)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          _ => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_WHITE));
+          }
+        }
+      }
+    }
+
+    terminal.backend().assert_buffer(&expected);
+  }
+
+  #[test]
+  fn test_draw_timeline() {
+    let mut app = App::default();
+    app.data.decoder.timeline = Some(Timeline {
+      nbf: None,
+      iat: Some(1000),
+      exp: 4000,
+      now: 3700,
+      leeway: 0,
+    });
+
+    let backend = TestBackend::new(40, 4);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    terminal
+      .draw(|f| {
+        draw_timeline_block(f, &app, f.area());
+      })
+      .unwrap();
+
+    let mut expected = Buffer::with_lines(vec![
+      r#"┌ Lifetime: expires in 5m ─────────────┐"#,
+      r#"│iat                            now exp│"#,
+      r#"│┃━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━●───┃│"#,
+      r#"└──────────────────────────────────────┘"#,
+    ]);
+
+    // set expected row styles
+    for row in 0..=3 {
+      for col in 0..=39 {
+        match (col, row) {
+          (12..=25, 0) | (32..=34, 1) | (2..=34, 2) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_ORANGE));
+          }
+          (1..=11, 0) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_WHITE)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
           _ => {
             expected
               .cell_mut(Position::new(col, row))
@@ -1,15 +1,17 @@
 use ratatui::{
   layout::{Constraint, Rect},
-  text::Text,
+  style::Style,
+  text::{Line, Text},
   widgets::{Block, Borders, Paragraph, Wrap},
   Frame,
 };
 
 use super::utils::{
-  get_input_style, get_selectable_block, horizontal_chunks, render_input_widget, style_default,
-  style_primary, vertical_chunks, vertical_chunks_with_margin,
+  get_input_style, get_selectable_block, horizontal_chunks, layout_block_with_line,
+  render_input_widget, style_default, style_segment, style_warning, styled_line,
+  title_with_dual_style, token_styles, vertical_chunks, vertical_chunks_with_margin,
 };
-use crate::app::{ActiveBlock, App, Route, RouteId, TextAreaInput};
+use crate::app::{jwt_decoder::TokenSegment, ActiveBlock, App, Route, RouteId, TextAreaInput};
 
 pub fn draw_encoder(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   let chunks = horizontal_chunks(
@@ -21,38 +23,93 @@ pub fn draw_encoder(f: &mut Frame<'_>, app: &mut App, area: Rect) {
 }
 
 fn draw_left_side(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  let violations = app.data.encoder.schema_violations.len();
+  let area = match violations {
+    0 => area,
+    len => {
+      let chunks = vertical_chunks(
+        vec![
+          Constraint::Min(0),
+          Constraint::Length(len.min(5) as u16 + 2),
+        ],
+        area,
+      );
+      draw_schema_violations_block(f, app, chunks[1]);
+      chunks[0]
+    }
+  };
+  if app.data.encoder.resolved_payload.is_empty() {
+    let chunks = vertical_chunks(
+      vec![Constraint::Percentage(40), Constraint::Percentage(60)],
+      area,
+    );
+    draw_header_block(f, app, chunks[0]);
+    draw_payload_block(f, app, chunks[1]);
+    return;
+  }
+
   let chunks = vertical_chunks(
-    vec![Constraint::Percentage(40), Constraint::Percentage(60)],
+    vec![
+      Constraint::Percentage(30),
+      Constraint::Percentage(40),
+      Constraint::Percentage(30),
+    ],
     area,
   );
-
   draw_header_block(f, app, chunks[0]);
   draw_payload_block(f, app, chunks[1]);
+  draw_resolved_payload_block(f, app, chunks[2]);
 }
 
 fn draw_right_side(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   let chunks = vertical_chunks(
-    vec![Constraint::Percentage(30), Constraint::Percentage(70)],
+    vec![
+      Constraint::Length(6),
+      Constraint::Length(6),
+      Constraint::Length(6),
+      Constraint::Min(4),
+    ],
     area,
   );
 
   draw_secret_block(f, app, chunks[0]);
-  draw_token_block(f, app, chunks[1]);
+  draw_encryption_block(f, app, chunks[1]);
+  draw_encryption_key_block(f, app, chunks[2]);
+  draw_token_block(f, app, chunks[3]);
 }
 
 fn draw_header_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   app.update_block_map(get_route(ActiveBlock::EncoderHeader), area);
 
-  let block = get_selectable_block(
+  let mut block = get_selectable_block(
     "Header: Algorithm & Token Type",
     *app.data.encoder.blocks.get_active_block() == ActiveBlock::EncoderHeader,
     Some(&app.data.encoder.header.input_mode),
     app.light_theme,
   );
+  // the first problem of the header, checked as it's typed
+  let problems = &app.data.encoder.header_problems;
+  if let Some(problem) = problems.first() {
+    let more = match problems.len() {
+      1 => String::new(),
+      len => format!(" (and {} more)", len - 1),
+    };
+    block = block.title_bottom(Line::styled(
+      format!(" ⚠ {problem}{more} "),
+      style_warning(app.light_theme),
+    ));
+  }
 
   f.render_widget(block, area);
 
-  render_text_area_widget(f, area, &mut app.data.encoder.header, app.light_theme);
+  let style = text_area_style(app, TokenSegment::Header);
+  render_text_area_widget(
+    f,
+    area,
+    &mut app.data.encoder.header,
+    style,
+    app.light_theme,
+  );
 }
 
 fn draw_payload_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
@@ -66,7 +123,51 @@ fn draw_payload_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   );
   f.render_widget(block, area);
 
-  render_text_area_widget(f, area, &mut app.data.encoder.payload, app.light_theme);
+  let style = text_area_style(app, TokenSegment::Payload);
+  render_text_area_widget(
+    f,
+    area,
+    &mut app.data.encoder.payload,
+    style,
+    app.light_theme,
+  );
+}
+
+/// the claims as signed, with `$now` and the placeholders resolved
+fn draw_resolved_payload_block(f: &mut Frame<'_>, app: &App, area: Rect) {
+  let title_hint = match app.data.encoder.refresh_claims {
+    true => "(refreshed every tick | <f> stop) ",
+    false => "(<f> refresh every tick) ",
+  };
+  let block = layout_block_with_line(
+    title_with_dual_style(" Resolved Payload ".into(), title_hint.into()),
+    app.light_theme,
+    false,
+  );
+
+  let mut text = Text::from(app.data.encoder.resolved_payload.as_str());
+  text = text.patch_style(style_default(app.light_theme));
+  let paragraph = Paragraph::new(text).block(block);
+  f.render_widget(paragraph, area);
+}
+
+/// the claims that don't match the JSON Schema of the token, by JSON pointer
+fn draw_schema_violations_block(f: &mut Frame<'_>, app: &App, area: Rect) {
+  let block = layout_block_with_line(
+    title_with_dual_style(" Schema Violations ".into(), "".into()),
+    app.light_theme,
+    false,
+  );
+
+  let lines: Vec<Line<'_>> = app
+    .data
+    .encoder
+    .schema_violations
+    .iter()
+    .map(|violation| Line::styled(format!("× {violation}"), style_warning(app.light_theme)))
+    .collect();
+  let paragraph = Paragraph::new(lines).block(block);
+  f.render_widget(paragraph, area);
 }
 
 fn draw_secret_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
@@ -85,7 +186,7 @@ fn draw_secret_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
     vertical_chunks_with_margin(vec![Constraint::Length(1), Constraint::Min(2)], area, 1);
 
   let mut text = Text::from(
-    "Prepend 'b64:' for base64 encoded secret. Prepend '@' for file path (.pem, .pk8, .der, .json)",
+    "Prepend 'b64:' for base64 encoded secret. Prepend '@' for file path (.pem, .pk8, .der, .json, .jwk) or paste a JWK/JWKS",
   );
   text = text.patch_style(style_default(app.light_theme));
   let paragraph = Paragraph::new(text).block(Block::default());
@@ -95,11 +196,71 @@ fn draw_secret_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   render_input_widget(f, chunks[1], &app.data.encoder.secret, app.light_theme);
 }
 
+fn draw_encryption_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::EncoderEncryption), area);
+
+  let block = get_selectable_block(
+    "Encryption",
+    *app.data.encoder.blocks.get_active_block() == ActiveBlock::EncoderEncryption,
+    Some(&app.data.encoder.encryption.input_mode),
+    app.light_theme,
+  );
+
+  f.render_widget(block, area);
+
+  let chunks =
+    vertical_chunks_with_margin(vec![Constraint::Length(1), Constraint::Min(2)], area, 1);
+
+  let mut text =
+    Text::from("Settings: alg=RSA-OAEP-256 enc=A256GCM kid=id nested. Empty to only sign");
+  text = text.patch_style(style_default(app.light_theme));
+  let paragraph = Paragraph::new(text).block(Block::default());
+
+  f.render_widget(paragraph, chunks[0]);
+
+  render_input_widget(f, chunks[1], &app.data.encoder.encryption, app.light_theme);
+}
+
+fn draw_encryption_key_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::EncoderEncryptionKey), area);
+
+  let block = get_selectable_block(
+    "Encryption Key",
+    *app.data.encoder.blocks.get_active_block() == ActiveBlock::EncoderEncryptionKey,
+    Some(&app.data.encoder.encryption_key.input_mode),
+    app.light_theme,
+  );
+
+  f.render_widget(block, area);
+
+  let chunks =
+    vertical_chunks_with_margin(vec![Constraint::Length(1), Constraint::Min(2)], area, 1);
+
+  let mut text = Text::from(
+    "Recipient public key or shared key. Prepend 'b64:' for base64, '@' for file path (.pem, .der, .json)",
+  );
+  text = text.patch_style(style_default(app.light_theme));
+  let paragraph = Paragraph::new(text).block(Block::default());
+
+  f.render_widget(paragraph, chunks[0]);
+
+  render_input_widget(
+    f,
+    chunks[1],
+    &app.data.encoder.encryption_key,
+    app.light_theme,
+  );
+}
+
 fn draw_token_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   app.update_block_map(get_route(ActiveBlock::EncoderToken), area);
 
+  let title = match app.data.encoder.unsigned {
+    true => "Encoded Token: UNSIGNED ⚠",
+    false => "Encoded Token",
+  };
   let block = get_selectable_block(
-    "Encoded Token",
+    title,
     *app.data.encoder.blocks.get_active_block() == ActiveBlock::EncoderToken,
     None,
     app.light_theme,
@@ -110,8 +271,12 @@ fn draw_token_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   let chunks = vertical_chunks_with_margin(vec![Constraint::Min(2)], area, 1);
 
   let encoded = app.data.encoder.encoded.get_txt();
-  let mut txt = Text::from(encoded.clone());
-  txt = txt.patch_style(style_primary(app.light_theme));
+  let styles = token_styles(
+    &encoded,
+    app.data.encoder.highlighted_segment(),
+    app.light_theme,
+  );
+  let txt = Text::from(styled_line(&encoded, &styles));
 
   let paragraph = Paragraph::new(txt)
     .block(Block::default())
@@ -120,15 +285,25 @@ fn draw_token_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
   f.render_widget(paragraph, chunks[0]);
 }
 
+/// the JSON of the highlighted segment is shown in the colour of the segment
+fn text_area_style(app: &App, segment: TokenSegment) -> Option<Style> {
+  (app.data.encoder.highlighted_segment() == Some(segment))
+    .then(|| style_segment(segment, app.light_theme))
+}
+
 // Utility methods
 fn render_text_area_widget(
   f: &mut Frame<'_>,
   area: Rect,
   text_input: &mut TextAreaInput<'_>,
+  style: Option<Style>,
   light_theme: bool,
 ) {
   let chunks = vertical_chunks_with_margin(vec![Constraint::Min(2)], area, 1);
   let mut textarea = text_input.input.clone();
+  if let Some(style) = style {
+    textarea.set_style(style);
+  }
   textarea.set_block(
     Block::default()
       .borders(Borders::ALL)
@@ -158,7 +333,7 @@ mod tests {
   use super::*;
   use crate::{
     app::RouteId,
-    ui::utils::{COLOR_CYAN, COLOR_WHITE, COLOR_YELLOW},
+    ui::utils::{COLOR_CYAN, COLOR_RED, COLOR_WHITE, COLOR_YELLOW},
   };
 
   #[test]
@@ -179,7 +354,7 @@ mod tests {
 
     app.on_tick();
 
-    let backend = TestBackend::new(100, 20);
+    let backend = TestBackend::new(100, 30);
     let mut terminal = Terminal::new(backend).unwrap();
 
     terminal
@@ -195,16 +370,26 @@ mod tests {
       r#"││  "alg": "HS256",                             ││││secret                                        ││"#,
       r#"││  "typ": "JWT"                                │││└──────────────────────────────────────────────┘│"#,
       r#"││}                                             ││└────────────────────────────────────────────────┘"#,
-      r#"│└──────────────────────────────────────────────┘│┌ Encoded Token ─────────────────────────────────┐"#,
-      r#"└────────────────────────────────────────────────┘│eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9.eyJhZG1pbiI│"#,
-      r#"┌ Payload: Claims ───────────────────────────────┐│6dHJ1ZSwiaWF0IjoxNTE2MjM5MDIyLCJuYW1lIjoiSm9obiB│"#,
-      r#"│┌──────────────────────────────────────────────┐││Eb2UiLCJzdWIiOiIxMjM0NTY3ODkwIn0.g7Ern-srhIi_7ZX│"#,
-      r#"││{                                             │││qrl6uyey7xxWJjr-LTn4p2Nv-DOY                    │"#,
-      r#"││  "sub": "1234567890",                        │││                                                │"#,
-      r#"││  "name": "John Doe",                         │││                                                │"#,
-      r#"││  "admin": true,                              │││                                                │"#,
-      r#"││  "iat": 1516239022                           │││                                                │"#,
-      r#"││}                                             │││                                                │"#,
+      r#"││                                              ││┌ Encryption ────────────────────────────────────┐"#,
+      r#"││                                              │││Settings: alg=RSA-OAEP-256 enc=A256GCM kid=id ne│"#,
+      r#"││                                              │││┌──────────────────────────────────────────────┐│"#,
+      r#"││                                              ││││                                              ││"#,
+      r#"│└──────────────────────────────────────────────┘││└──────────────────────────────────────────────┘│"#,
+      r#"└────────────────────────────────────────────────┘└────────────────────────────────────────────────┘"#,
+      r#"┌ Payload: Claims ───────────────────────────────┐┌ Encryption Key ────────────────────────────────┐"#,
+      r#"│┌──────────────────────────────────────────────┐││Recipient public key or shared key. Prepend 'b64│"#,
+      r#"││{                                             │││┌──────────────────────────────────────────────┐│"#,
+      r#"││  "sub": "1234567890",                        ││││                                              ││"#,
+      r#"││  "name": "John Doe",                         │││└──────────────────────────────────────────────┘│"#,
+      r#"││  "admin": true,                              ││└────────────────────────────────────────────────┘"#,
+      r#"││  "iat": 1516239022                           ││┌ Encoded Token ─────────────────────────────────┐"#,
+      r#"││}                                             │││eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9.eyJhZG1pbiI│"#,
+      r#"││                                              │││6dHJ1ZSwiaWF0IjoxNTE2MjM5MDIyLCJuYW1lIjoiSm9obiB│"#,
+      r#"││                                              │││Eb2UiLCJzdWIiOiIxMjM0NTY3ODkwIn0.g7Ern-srhIi_7ZX│"#,
+      r#"││                                              │││qrl6uyey7xxWJjr-LTn4p2Nv-DOY                    │"#,
+      r#"││                                              │││                                                │"#,
+      r#"││                                              │││                                                │"#,
+      r#"││                                              │││                                                │"#,
       r#"││                                              │││                                                │"#,
       r#"││                                              │││                                                │"#,
       r#"│└──────────────────────────────────────────────┘││                                                │"#,
@@ -212,51 +397,82 @@ mod tests {
     ]);
 
     // set expected row styles
-    for row in 0..=19 {
+    for row in 0..=29 {
       for col in 0..=99 {
         match (col, row) {
-          (2, 2 | 10) => {
+          (3..=47, 2) | (2..=47, 3..=9) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_RED));
+          }
+          (0 | 33..=49, 0)
+          | (0 | 49, 1..=10)
+          | (0..=49, 11)
+          | (88..=98, 19)
+          | (51..=98, 20)
+          | (51..=82, 21) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_YELLOW));
+          }
+          (51..=66, 0) | (51..=62, 6) | (1..=17 | 51..=66, 12) | (51..=65, 18) => {
             expected
               .cell_mut(Position::new(col, row))
               .unwrap()
               .set_style(
                 Style::default()
                   .fg(COLOR_WHITE)
-                  .add_modifier(Modifier::REVERSED),
+                  .add_modifier(Modifier::BOLD),
               );
           }
-          (1..=32, 0) => {
+          (84..=98, 21) | (51..=78, 22) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_CYAN));
+          }
+          (51..=86, 19) => {
             expected
               .cell_mut(Position::new(col, row))
               .unwrap()
               .set_style(
                 Style::default()
-                  .fg(COLOR_YELLOW)
-                  .add_modifier(Modifier::BOLD),
+                  .fg(COLOR_RED)
+                  .add_modifier(Modifier::BOLD)
+                  .add_modifier(Modifier::UNDERLINED),
               );
           }
-          (51..=66, 0) | (51..=65, 6) | (1..=17, 8) => {
+          (1..=32, 0) => {
             expected
               .cell_mut(Position::new(col, row))
               .unwrap()
               .set_style(
                 Style::default()
-                  .fg(COLOR_WHITE)
+                  .fg(COLOR_YELLOW)
                   .add_modifier(Modifier::BOLD),
               );
           }
-          (0 | 16..=49, 0) | (0 | 49, 1..=6 | 20..=99) | (0..=49, 7) => {
+          (2, 2) => {
             expected
               .cell_mut(Position::new(col, row))
               .unwrap()
-              .set_style(Style::default().fg(COLOR_YELLOW));
+              .set_style(
+                Style::default()
+                  .fg(COLOR_RED)
+                  .add_modifier(Modifier::REVERSED),
+              );
           }
-
-          (51, 9) | (51..=98, 7..=9) | (51..=78, 10) => {
+          (2, 14) => {
             expected
               .cell_mut(Position::new(col, row))
               .unwrap()
-              .set_style(Style::default().fg(COLOR_CYAN));
+              .set_style(
+                Style::default()
+                  .fg(COLOR_WHITE)
+                  .add_modifier(Modifier::REVERSED),
+              );
           }
           _ => {
             expected
@@ -0,0 +1,265 @@
+use chrono::{Local, TimeZone, Utc};
+use ratatui::{
+  layout::{Constraint, Rect},
+  text::Text,
+  widgets::{Block, Paragraph, Row, Table},
+  Frame,
+};
+
+use super::{
+  utils::{
+    get_selectable_block, layout_block_with_line, render_input_widget, style_default,
+    style_failure, style_highlight, style_secondary, style_success, title_with_dual_style,
+    vertical_chunks, vertical_chunks_with_margin,
+  },
+  HIGHLIGHT,
+};
+use crate::app::{
+  history::{HistoryEntry, HistoryKind},
+  ActiveBlock, App, Route, RouteId,
+};
+
+pub fn draw_history(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  let chunks = vertical_chunks(vec![Constraint::Length(6), Constraint::Min(4)], area);
+  draw_search_block(f, app, chunks[0]);
+  draw_entries_block(f, app, chunks[1]);
+}
+
+fn draw_search_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::HistorySearch), area);
+
+  let block = get_selectable_block(
+    "Search",
+    *app.data.history.blocks.get_active_block() == ActiveBlock::HistorySearch,
+    Some(&app.data.history.search.input_mode),
+    app.light_theme,
+  );
+
+  f.render_widget(block, area);
+
+  let chunks =
+    vertical_chunks_with_margin(vec![Constraint::Length(1), Constraint::Min(2)], area, 1);
+
+  let mut text = Text::from("Filter by token, iss, sub, kind or verification");
+  text = text.patch_style(style_default(app.light_theme));
+  let paragraph = Paragraph::new(text).block(Block::default());
+
+  f.render_widget(paragraph, chunks[0]);
+
+  render_input_widget(f, chunks[1], &app.data.history.search, app.light_theme);
+}
+
+fn draw_entries_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::HistoryEntries), area);
+
+  let is_active = *app.data.history.blocks.get_active_block() == ActiveBlock::HistoryEntries;
+  let title_hint = if is_active {
+    "(<enter> open | <p> pin | <x> delete) "
+  } else {
+    ""
+  };
+  let block = layout_block_with_line(
+    title_with_dual_style(" Tokens ".into(), title_hint.into()),
+    app.light_theme,
+    is_active,
+  );
+
+  let history = &app.data.history;
+  let rows = history.table.items.iter().map(|i| {
+    let entry = &history.entries[*i];
+    let style = if entry.verified {
+      style_success(app.light_theme)
+    } else {
+      style_failure(app.light_theme)
+    };
+    Row::new(vec![
+      if entry.pinned { "★" } else { "" }.to_string(),
+      format_timestamp(entry, app.data.decoder.utc_dates),
+      match entry.kind {
+        HistoryKind::Decoded => "decoded",
+        HistoryKind::Encoded => "encoded",
+      }
+      .to_string(),
+      if entry.verified { "yes" } else { "no" }.to_string(),
+      entry.iss.clone().unwrap_or_default(),
+      entry.sub.clone().unwrap_or_default(),
+      entry.token.clone(),
+    ])
+    .style(style)
+  });
+
+  let table = Table::new(
+    rows,
+    [
+      Constraint::Length(1),
+      Constraint::Length(19),
+      Constraint::Length(7),
+      Constraint::Length(8),
+      Constraint::Percentage(20),
+      Constraint::Percentage(15),
+      Constraint::Min(10),
+    ],
+  )
+  .header(
+    Row::new(vec![
+      "", "Time", "Kind", "Verified", "Issuer", "Subject", "Token",
+    ])
+    .style(style_secondary(app.light_theme)),
+  )
+  .block(block)
+  .row_highlight_style(style_highlight())
+  .highlight_symbol(HIGHLIGHT);
+
+  f.render_stateful_widget(table, area, &mut app.data.history.table.state);
+}
+
+fn format_timestamp(entry: &HistoryEntry, utc: bool) -> String {
+  const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+  if utc {
+    Utc
+      .timestamp_opt(entry.timestamp, 0)
+      .single()
+      .map(|t| t.format(FORMAT).to_string())
+  } else {
+    Local
+      .timestamp_opt(entry.timestamp, 0)
+      .single()
+      .map(|t| t.format(FORMAT).to_string())
+  }
+  .unwrap_or_default()
+}
+
+fn get_route(active_block: ActiveBlock) -> Route {
+  Route {
+    id: RouteId::History,
+    active_block,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ratatui::{
+    backend::TestBackend,
+    layout::Position,
+    prelude::Buffer,
+    style::{Modifier, Style},
+    Terminal,
+  };
+
+  use super::*;
+  use crate::{
+    app::history::History,
+    ui::utils::{COLOR_GREEN, COLOR_RED, COLOR_WHITE, COLOR_YELLOW},
+  };
+
+  #[test]
+  fn test_draw_history() {
+    let mut app = App::default();
+    app.route_history();
+    app.data.decoder.utc_dates = true;
+    app.data.history = History::new(
+      vec![
+        HistoryEntry {
+          token: "eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiJhbGljZSJ9.sig".into(),
+          kind: HistoryKind::Decoded,
+          timestamp: 1516239022,
+          iss: Some("https://auth".into()),
+          sub: Some("alice".into()),
+          verified: true,
+          pinned: false,
+        },
+        HistoryEntry {
+          token: "eyJhbGciOiJSUzI1NiJ9.eyJzdWIiOiJib2IifQ.sig".into(),
+          kind: HistoryKind::Encoded,
+          timestamp: 1516239000,
+          iss: None,
+          sub: Some("bob".into()),
+          verified: false,
+          pinned: true,
+        },
+      ],
+      None,
+    );
+
+    let backend = TestBackend::new(100, 11);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    terminal
+      .draw(|f| {
+        draw_history(f, &mut app, f.area());
+      })
+      .unwrap();
+
+    let mut expected = Buffer::with_lines(vec![
+      r#"┌ Search ──────────────────────────────────────────────────────────────────────────────────────────┐"#,
+      r#"│Filter by token, iss, sub, kind or verification                                                   │"#,
+      r#"│┌────────────────────────────────────────────────────────────────────────────────────────────────┐│"#,
+      r#"││                                                                                                ││"#,
+      r#"│└────────────────────────────────────────────────────────────────────────────────────────────────┘│"#,
+      r#"└──────────────────────────────────────────────────────────────────────────────────────────────────┘"#,
+      r#"┌ Tokens (<enter> open | <p> pin | <x> delete) ────────────────────────────────────────────────────┐"#,
+      r#"│     Time                Kind    Verified Issuer              Subject        Token                │"#,
+      r#"│=> ★ 2018-01-18 01:30:00 encoded no                           bob            eyJhbGciOiJSUzI1NiJ9.│"#,
+      r#"│     2018-01-18 01:30:22 decoded yes      https://auth        alice          eyJhbGciOiJIUzI1NiJ9.│"#,
+      r#"└──────────────────────────────────────────────────────────────────────────────────────────────────┘"#,
+    ]);
+
+    // set expected row styles
+    for row in 0..=10 {
+      for col in 0..=99 {
+        match (col, row) {
+          (0 | 9..=99, 6) | (0..=99, 7 | 10) | (0 | 99, 8..=9) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_YELLOW));
+          }
+          (1..=98, 8) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_RED)
+                  .add_modifier(Modifier::REVERSED),
+              );
+          }
+          (1..=98, 9) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_GREEN));
+          }
+          (1..=8, 0) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_WHITE)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          (1..=8, 6) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_YELLOW)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          _ => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_WHITE));
+          }
+        }
+      }
+    }
+
+    terminal.backend().assert_buffer(&expected);
+  }
+}
@@ -0,0 +1,205 @@
+use ratatui::{
+  layout::{Constraint, Rect},
+  text::Text,
+  widgets::{Block, Paragraph, Row, Table, Wrap},
+  Frame,
+};
+
+use super::{
+  utils::{
+    get_selectable_block, horizontal_chunks, layout_block_with_line, render_input_widget,
+    style_default, style_highlight, style_primary, style_secondary, title_with_dual_style,
+    vertical_chunks, vertical_chunks_with_margin,
+  },
+  HIGHLIGHT,
+};
+use crate::app::{keygen::KeyType, ActiveBlock, App, Route, RouteId};
+
+pub fn draw_keygen(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  let chunks = horizontal_chunks(vec![Constraint::Length(30), Constraint::Min(0)], area);
+  draw_key_types_block(f, app, chunks[0]);
+
+  let chunks = vertical_chunks(vec![Constraint::Length(6), Constraint::Min(4)], chunks[1]);
+  draw_output_block(f, app, chunks[0]);
+  draw_message_block(f, app, chunks[1]);
+}
+
+fn draw_key_types_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::KeygenTypes), area);
+
+  let is_active = *app.data.keygen.blocks.get_active_block() == ActiveBlock::KeygenTypes;
+  let title_hint = if is_active { "(<enter> generate) " } else { "" };
+  let block = layout_block_with_line(
+    title_with_dual_style(" Key Type ".into(), title_hint.into()),
+    app.light_theme,
+    is_active,
+  );
+
+  let rows = app
+    .data
+    .keygen
+    .key_types
+    .items
+    .iter()
+    .map(|k: &KeyType| Row::new(vec![k.name(), k.alg()]).style(style_primary(app.light_theme)));
+
+  let table = Table::new(rows, [Constraint::Length(10), Constraint::Min(5)])
+    .header(Row::new(vec!["Type", "Algorithm"]).style(style_secondary(app.light_theme)))
+    .block(block)
+    .row_highlight_style(style_highlight())
+    .highlight_symbol(HIGHLIGHT);
+
+  f.render_stateful_widget(table, area, &mut app.data.keygen.key_types.state);
+}
+
+fn draw_output_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(get_route(ActiveBlock::KeygenOutput), area);
+
+  let block = get_selectable_block(
+    "Output Path",
+    *app.data.keygen.blocks.get_active_block() == ActiveBlock::KeygenOutput,
+    Some(&app.data.keygen.output.input_mode),
+    app.light_theme,
+  );
+
+  f.render_widget(block, area);
+
+  let chunks =
+    vertical_chunks_with_margin(vec![Constraint::Length(1), Constraint::Min(2)], area, 1);
+
+  let mut text = Text::from(
+    "Path prefix of the written files: .pem, .pub.pem, .der, .pub.der, .jwk, .pub.jwk, .jwks.json",
+  );
+  text = text.patch_style(style_default(app.light_theme));
+  let paragraph = Paragraph::new(text).block(Block::default());
+
+  f.render_widget(paragraph, chunks[0]);
+
+  render_input_widget(f, chunks[1], &app.data.keygen.output, app.light_theme);
+}
+
+fn draw_message_block(f: &mut Frame<'_>, app: &App, area: Rect) {
+  let block = layout_block_with_line(
+    title_with_dual_style(" Generated Key ".into(), "".into()),
+    app.light_theme,
+    false,
+  );
+
+  let mut text = Text::from(app.data.keygen.message.clone());
+  text = text.patch_style(style_primary(app.light_theme));
+
+  let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+  f.render_widget(paragraph, area);
+}
+
+fn get_route(active_block: ActiveBlock) -> Route {
+  Route {
+    id: RouteId::Keygen,
+    active_block,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ratatui::{
+    backend::TestBackend,
+    layout::Position,
+    prelude::Buffer,
+    style::{Modifier, Style},
+    Terminal,
+  };
+
+  use super::*;
+  use crate::ui::utils::{COLOR_CYAN, COLOR_WHITE, COLOR_YELLOW};
+
+  #[test]
+  fn test_draw_keygen() {
+    let mut app = App::default();
+    app.push_navigation_route(*app.data.keygen.blocks.get_active_item());
+    app.data.keygen.message = "Generated P-256 key with kid abc".into();
+
+    let backend = TestBackend::new(80, 14);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    terminal
+      .draw(|f| {
+        draw_keygen(f, &mut app, f.area());
+      })
+      .unwrap();
+
+    let mut expected = Buffer::with_lines(vec![
+      r#"┌ Key Type (<enter> generate)┐┌ Output Path ───────────────────────────────────┐"#,
+      r#"│   Type       Algorithm     ││Path prefix of the written files: .pem, .pub.pem│"#,
+      r#"│=> HS256      HS256         ││┌──────────────────────────────────────────────┐│"#,
+      r#"│   HS384      HS384         │││./jwtui-key                                   ││"#,
+      r#"│   HS512      HS512         ││└──────────────────────────────────────────────┘│"#,
+      r#"│   RSA-2048   RS256         │└────────────────────────────────────────────────┘"#,
+      r#"│   RSA-3072   RS256         │┌ Generated Key ─────────────────────────────────┐"#,
+      r#"│   RSA-4096   RS256         ││Generated P-256 key with kid abc                │"#,
+      r#"│   P-256      ES256         ││                                                │"#,
+      r#"│   P-384      ES384         ││                                                │"#,
+      r#"│   Ed25519    EdDSA         ││                                                │"#,
+      r#"│                            ││                                                │"#,
+      r#"│                            ││                                                │"#,
+      r#"└────────────────────────────┘└────────────────────────────────────────────────┘"#,
+    ]);
+
+    // set expected row styles
+    for row in 0..=13 {
+      for col in 0..=79 {
+        match (col, row) {
+          (1..=28, 3..=6 | 8..=10) | (1..=28 | 31..=62, 7) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_CYAN));
+          }
+          (0 | 11..=29, 0) | (0..=29, 1 | 11..=13) | (0 | 29, 2..=10) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_YELLOW));
+          }
+          (31..=43, 0) | (31..=45, 6) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_WHITE)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          (1..=28, 2) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_CYAN)
+                  .add_modifier(Modifier::REVERSED),
+              );
+          }
+          (1..=10, 0) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_YELLOW)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          _ => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_WHITE));
+          }
+        }
+      }
+    }
+
+    terminal.backend().assert_buffer(&expected);
+  }
+}
@@ -1,6 +1,11 @@
+mod algorithms;
+mod compare;
 mod decoder;
 mod encoder;
 mod help;
+mod history;
+mod keygen;
+mod templates;
 pub mod utils;
 
 use ratatui::{
@@ -12,15 +17,21 @@ use ratatui::{
 };
 
 use self::{
+  algorithms::draw_algorithms,
+  compare::draw_compare,
   decoder::draw_decoder,
   encoder::draw_encoder,
   help::draw_help,
+  history::draw_history,
+  keygen::draw_keygen,
+  templates::draw_templates,
   utils::{
     horizontal_chunks_with_margin, style_default, style_failure, style_header, style_header_text,
-    style_help, style_main_background, style_primary, style_secondary, vertical_chunks,
+    style_help, style_main_background, style_primary, style_secondary, style_warning,
+    vertical_chunks,
   },
 };
-use crate::app::{App, RouteId};
+use crate::app::{validation::format_instant, App, RouteId};
 
 pub static HIGHLIGHT: &str = "=> ";
 
@@ -68,6 +79,21 @@ pub fn draw(f: &mut Frame<'_>, app: &mut App) {
     RouteId::Encoder => {
       draw_encoder(f, app, main_chunk);
     }
+    RouteId::Keygen => {
+      draw_keygen(f, app, main_chunk);
+    }
+    RouteId::Templates => {
+      draw_templates(f, app, main_chunk);
+    }
+    RouteId::Algorithms => {
+      draw_algorithms(f, app, main_chunk);
+    }
+    RouteId::History => {
+      draw_history(f, app, main_chunk);
+    }
+    RouteId::Compare => {
+      draw_compare(f, app, main_chunk);
+    }
   }
 }
 
@@ -100,8 +126,20 @@ fn draw_app_header(f: &mut Frame<'_>, app: &App, area: Rect) {
     .iter()
     .map(|t| Line::from(Span::styled(&t.title, style_default(app.light_theme))))
     .collect();
+  let mut block = Block::default().borders(Borders::ALL);
+  if let (RouteId::Decoder, Some(at)) =
+    (app.get_current_route().id, app.data.decoder.validated_at())
+  {
+    block = block.title(
+      Line::from(Span::styled(
+        format!(" validating as of {} ", format_instant(at)),
+        style_warning(app.light_theme),
+      ))
+      .right_aligned(),
+    );
+  }
   let tabs = Tabs::new(titles)
-    .block(Block::default().borders(Borders::ALL))
+    .block(block)
     .highlight_style(style_secondary(app.light_theme))
     .select(app.main_tabs.index);
 
@@ -112,10 +150,25 @@ fn draw_app_header(f: &mut Frame<'_>, app: &App, area: Rect) {
 fn draw_header_text(f: &mut Frame<'_>, app: &App, area: Rect) {
   let text: Vec<Line<'_>> = match app.get_current_route().id {
     RouteId::Decoder => vec![Line::from(
-      "<?> help | <tab> switch tabs | <←→>, <click> select block | <u> toggle UTC dates | <↑↓> scroll ",
+      "<?> help | <tab> switch tabs | <←→>, <click> select block | <u> toggle UTC dates | <s> to encoder | <↑↓> scroll ",
     )],
     RouteId::Encoder => vec![Line::from(
-      "<?> help | <tab> switch tabs | <←→>, <click> select block | <↑↓> scroll ",
+      "<?> help | <tab> switch tabs | <←→>, <click> select block | <o> templates | <a> algorithm | <v> verify | <↑↓> scroll ",
+    )],
+    RouteId::Keygen => vec![Line::from(
+      "<esc> close | <←→>, <click> select block | <↑↓> select key type | <enter> generate ",
+    )],
+    RouteId::Templates => vec![Line::from(
+      "<esc> close | <↑↓> select template | <enter> use template ",
+    )],
+    RouteId::Algorithms => vec![Line::from(
+      "<esc> close | <↑↓> select algorithm | <enter> use algorithm ",
+    )],
+    RouteId::History => vec![Line::from(
+      "<?> help | <tab> switch tabs | <←→>, <click> select block | <↑↓> select token ",
+    )],
+    RouteId::Compare => vec![Line::from(
+      "<?> help | <tab> switch tabs | <←→>, <click> select block | <↑↓> scroll diff ",
     )],
     RouteId::Help => vec![],
   };
@@ -0,0 +1,193 @@
+use ratatui::{
+  layout::{Constraint, Rect},
+  text::{Line, Text},
+  widgets::{Paragraph, Row, Table, Wrap},
+  Frame,
+};
+
+use super::{
+  utils::{
+    horizontal_chunks, layout_block_with_line, style_default, style_highlight, style_primary,
+    style_secondary, title_with_dual_style,
+  },
+  HIGHLIGHT,
+};
+use crate::app::{templates::Template, ActiveBlock, App, Route, RouteId};
+
+pub fn draw_templates(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  let chunks = horizontal_chunks(vec![Constraint::Length(30), Constraint::Min(0)], area);
+  draw_template_list_block(f, app, chunks[0]);
+  draw_preview_block(f, app, chunks[1]);
+}
+
+fn draw_template_list_block(f: &mut Frame<'_>, app: &mut App, area: Rect) {
+  app.update_block_map(
+    Route {
+      id: RouteId::Templates,
+      active_block: ActiveBlock::TemplateList,
+    },
+    area,
+  );
+
+  let block = layout_block_with_line(
+    title_with_dual_style(" Template ".into(), "(<enter> use) ".into()),
+    app.light_theme,
+    true,
+  );
+
+  let rows = app
+    .data
+    .templates
+    .items
+    .items
+    .iter()
+    .map(|t: &Template| Row::new(vec![t.name.as_str()]).style(style_primary(app.light_theme)));
+
+  let table = Table::new(rows, [Constraint::Min(5)])
+    .block(block)
+    .row_highlight_style(style_highlight())
+    .highlight_symbol(HIGHLIGHT);
+
+  f.render_stateful_widget(table, area, &mut app.data.templates.items.state);
+}
+
+/// the description, header and payload of the selected template, with the placeholders
+fn draw_preview_block(f: &mut Frame<'_>, app: &App, area: Rect) {
+  let block = layout_block_with_line(
+    title_with_dual_style(" Preview ".into(), "".into()),
+    app.light_theme,
+    false,
+  );
+
+  let mut text = Text::default();
+  if let Some(template) = app.data.templates.selected() {
+    let light = app.light_theme;
+    let section = |title: &str| Line::styled(title.to_string(), style_secondary(light));
+    text.push_line(Line::styled(
+      template.description.clone(),
+      style_default(light),
+    ));
+    text.push_line("");
+    text.push_line(section("Header"));
+    for line in template.header.lines() {
+      text.push_line(Line::styled(line.to_string(), style_primary(light)));
+    }
+    text.push_line("");
+    text.push_line(section("Payload"));
+    for line in template.payload.lines() {
+      text.push_line(Line::styled(line.to_string(), style_primary(light)));
+    }
+  }
+
+  let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+  f.render_widget(paragraph, area);
+}
+
+#[cfg(test)]
+mod tests {
+  use ratatui::{
+    backend::TestBackend,
+    layout::Position,
+    prelude::Buffer,
+    style::{Modifier, Style},
+    Terminal,
+  };
+
+  use super::*;
+  use crate::ui::utils::{COLOR_CYAN, COLOR_WHITE, COLOR_YELLOW};
+
+  #[test]
+  fn test_draw_templates() {
+    let mut app = App::default();
+    app.push_navigation_stack(RouteId::Templates, ActiveBlock::TemplateList);
+    app.data.templates.items.state.select(Some(2));
+
+    let backend = TestBackend::new(80, 14);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    terminal
+      .draw(|f| {
+        draw_templates(f, &mut app, f.area());
+      })
+      .unwrap();
+
+    let mut expected = Buffer::with_lines(vec![
+      r#"┌ Template (<enter> use) ────┐┌ Preview ───────────────────────────────────────┐"#,
+      r#"│   OIDC ID token            ││Short-lived token a service calls another       │"#,
+      r#"│   OAuth access token       ││service with                                    │"#,
+      r#"│=> Service token            ││                                                │"#,
+      r#"│   Refresh token            ││Header                                          │"#,
+      r#"│                            ││{                                               │"#,
+      r#"│                            ││  "typ": "JWT"                                  │"#,
+      r#"│                            ││}                                               │"#,
+      r#"│                            ││                                                │"#,
+      r#"│                            ││Payload                                         │"#,
+      r#"│                            ││{                                               │"#,
+      r#"│                            ││  "iss": "service-a",                           │"#,
+      r#"│                            ││  "sub": "service-a",                           │"#,
+      r#"└────────────────────────────┘└────────────────────────────────────────────────┘"#,
+    ]);
+
+    // set expected row styles
+    for row in 0..=13 {
+      for col in 0..=79 {
+        match (col, row) {
+          (0 | 11..=29, 0)
+          | (0 | 29, 1..=3)
+          | (0 | 29 | 31..=36, 4)
+          | (0..=29, 5..=8 | 10..=13)
+          | (0..=29 | 31..=37, 9) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_YELLOW));
+          }
+          (1..=28, 1..=2 | 4) | (31, 5 | 7 | 10) | (31..=44, 6) | (31..=51, 11..=12) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_CYAN));
+          }
+          (1..=28, 3) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_CYAN)
+                  .add_modifier(Modifier::REVERSED),
+              );
+          }
+          (1..=10, 0) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_YELLOW)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          (31..=39, 0) => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(
+                Style::default()
+                  .fg(COLOR_WHITE)
+                  .add_modifier(Modifier::BOLD),
+              );
+          }
+          _ => {
+            expected
+              .cell_mut(Position::new(col, row))
+              .unwrap()
+              .set_style(Style::default().fg(COLOR_WHITE));
+          }
+        }
+      }
+    }
+
+    terminal.backend().assert_buffer(&expected);
+  }
+}
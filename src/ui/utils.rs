@@ -1,4 +1,4 @@
-use std::{collections::BTreeMap, rc::Rc};
+use std::{collections::BTreeMap, ops::Range, rc::Rc};
 
 use ratatui::{
   layout::{Constraint, Direction, Layout, Position, Rect},
@@ -8,7 +8,10 @@ use ratatui::{
   Frame,
 };
 
-use crate::app::{InputMode, TextInput};
+use crate::app::{
+  jwt_decoder::{token_segments, TokenSegment},
+  InputMode, TextInput,
+};
 
 // Utils
 
@@ -97,6 +100,14 @@ pub fn style_failure(light: bool) -> Style {
   *theme_styles(light).get(&Styles::Failure).unwrap()
 }
 
+pub fn style_warning(light: bool) -> Style {
+  *theme_styles(light).get(&Styles::Warning).unwrap()
+}
+
+pub fn style_success(light: bool) -> Style {
+  *theme_styles(light).get(&Styles::Success).unwrap()
+}
+
 pub fn style_primary(light: bool) -> Style {
   *theme_styles(light).get(&Styles::Primary).unwrap()
 }
@@ -116,6 +127,33 @@ pub fn style_highlight() -> Style {
   Style::default().add_modifier(Modifier::REVERSED)
 }
 
+/// colours of the token segments, like jwt.io
+pub fn style_segment(segment: TokenSegment, light: bool) -> Style {
+  match segment {
+    TokenSegment::Header => style_failure(light),
+    TokenSegment::Payload => style_secondary(light),
+    TokenSegment::Signature => style_primary(light),
+  }
+}
+
+/// the segments of a token in their colours, the highlighted one underlined
+pub fn token_styles(
+  token: &str,
+  highlighted: Option<TokenSegment>,
+  light: bool,
+) -> Vec<(Range<usize>, Style)> {
+  token_segments(token)
+    .into_iter()
+    .map(|(range, segment)| {
+      let style = match highlighted == Some(segment) {
+        true => style_segment(segment, light).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        false => style_segment(segment, light),
+      };
+      (range, style)
+    })
+    .collect()
+}
+
 pub fn horizontal_chunks(constraints: Vec<Constraint>, size: Rect) -> Rc<[Rect]> {
   Layout::default()
     .constraints(<Vec<Constraint> as AsRef<[Constraint]>>::as_ref(
@@ -187,11 +225,22 @@ pub fn render_input_widget(
   chunk: Rect,
   text_input: &TextInput,
   light_theme: bool,
+) {
+  render_styled_input_widget(f, chunk, text_input, &[], light_theme);
+}
+
+/// render an input with styles patched over byte ranges of its value, later ranges take precedence
+pub fn render_styled_input_widget(
+  f: &mut Frame<'_>,
+  chunk: Rect,
+  text_input: &TextInput,
+  styles: &[(Range<usize>, Style)],
+  light_theme: bool,
 ) {
   let width = chunk.width.max(3) - 3;
   // keep 2 for borders and 1 for cursor
   let scroll = text_input.input.visual_scroll(width as usize);
-  let input = Paragraph::new(text_input.input.value())
+  let input = Paragraph::new(styled_line(text_input.input.value(), styles))
     .wrap(Wrap { trim: false })
     .style(get_input_style(&text_input.input_mode, light_theme))
     .scroll((0, scroll as u16))
@@ -220,6 +269,28 @@ pub fn render_input_widget(
   }
 }
 
+/// a line with styles patched over byte ranges of the value, later ranges take precedence
+pub fn styled_line<'a>(value: &'a str, styles: &[(Range<usize>, Style)]) -> Line<'a> {
+  let style_at = |i: usize| {
+    styles
+      .iter()
+      .filter(|(range, _)| range.contains(&i))
+      .fold(Style::default(), |style, (_, s)| style.patch(*s))
+  };
+  let mut spans: Vec<Span<'a>> = vec![];
+  let mut start = 0;
+  for (i, _) in value.char_indices().skip(1) {
+    if style_at(i) != style_at(start) {
+      spans.push(Span::styled(&value[start..i], style_at(start)));
+      start = i;
+    }
+  }
+  if start < value.len() {
+    spans.push(Span::styled(&value[start..], style_at(start)));
+  }
+  Line::from(spans)
+}
+
 pub fn get_hint(input_mode: &InputMode, is_active: bool) -> &str {
   if is_active {
     match input_mode {